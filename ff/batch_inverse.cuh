@@ -0,0 +1,140 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+#ifndef __SPPARK_FF_BATCH_INVERSE_CUH__
+#define __SPPARK_FF_BATCH_INVERSE_CUH__
+
+// Inversion of arrays of elements of any of the field types in ff/ with
+// Montgomery's trick, i.e. with a single reciprocal() per chunk of elements
+// and three multiplications per element. Zeros are left intact, same as
+// reciprocal() maps zero to zero, and their number is reported back, so
+// that callers that can't tolerate them can fail, see batch_inverse in
+// rust/src/ff/mod.rs.
+
+#include <algorithm>
+
+#include <util/exception.cuh>
+#include <util/rusterror.h>
+#include <util/gpu_t.cuh>
+
+// Every thread inverts BATCH_INVERSE_STRIDE elements, interleaved with the
+// other threads' ones for coalesced access. |d_prefix| holds the running
+// products of the non-zero elements preceding each one.
+#ifndef BATCH_INVERSE_STRIDE
+# define BATCH_INVERSE_STRIDE 32
+#endif
+
+template<class T>
+__launch_bounds__(256) __global__
+void batch_inverse_kernel(T* d_inout, T* d_prefix, size_t n,
+                          unsigned long long* d_nzeros)
+{
+    const size_t nthreads = blockDim.x * (size_t)gridDim.x;
+    const size_t tid = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+
+    if (tid >= n)
+        return;
+
+    T acc = T::one();
+    unsigned int nzeros = 0;
+
+    for (size_t i = tid; i < n; i += nthreads) {
+        T x = d_inout[i];
+        d_prefix[i] = acc;
+        if (x.is_zero())
+            nzeros++;
+        else
+            acc *= x;
+    }
+
+    T inv = acc.reciprocal();
+
+    size_t last = tid + (n - 1 - tid) / nthreads * nthreads;
+    for (size_t i = last + nthreads; i > tid; ) {
+        i -= nthreads;
+        T x = d_inout[i];
+        if (!x.is_zero()) {
+            d_inout[i] = inv * d_prefix[i];
+            inv *= x;
+        }
+    }
+
+    if (nzeros)
+        atomicAdd(d_nzeros, (unsigned long long)nzeros);
+}
+
+#ifndef __CUDA_ARCH__
+
+// Invert |n| elements at |d_inout| in device memory on |stream|, adding the
+// number of zeros to |*d_nzeros|. |d_prefix| is scratch space of |n|
+// elements.
+template<class T>
+static void batch_inverse_launch(stream_t& stream, T* d_inout, T* d_prefix,
+                                 size_t n, unsigned long long* d_nzeros)
+{
+    if (n == 0)
+        return;
+
+    size_t nthreads = (n + BATCH_INVERSE_STRIDE - 1) / BATCH_INVERSE_STRIDE;
+    const uint32_t bsize = 256;
+    size_t nblocks = (nthreads + bsize - 1) / bsize;
+
+    batch_inverse_kernel<<<nblocks, bsize, 0, stream>>>
+                        (d_inout, d_prefix, n, d_nzeros);
+    CUDA_OK(cudaGetLastError());
+}
+
+// Invert |n| elements at |inout| in host memory on |gpu|, in batches that
+// fit the device memory, and store the number of zeros to |*nzeros|.
+template<class T>
+static RustError batch_inverse(const gpu_t& gpu, T* inout, size_t n,
+                               size_t* nzeros)
+{
+    try {
+        gpu.select();
+
+        size_t total = 0;
+
+        if (n != 0) {
+            // the data and the prefix products in a quarter of the memory
+            size_t batch = gpu.props().totalGlobalMem / 8 / sizeof(T);
+            batch = std::min(n, std::max(batch, (size_t)1024));
+
+            dev_ptr_t<T> d_inout{batch, gpu};
+            dev_ptr_t<T> d_prefix{batch, gpu};
+            dev_ptr_t<unsigned long long> d_nzeros{1, gpu};
+            CUDA_OK(cudaMemsetAsync(&d_nzeros[0], 0, sizeof(unsigned long long),
+                                    gpu));
+
+            for (size_t off = 0; off < n; off += batch) {
+                size_t len = std::min(batch, n - off);
+
+                gpu.HtoD(&d_inout[0], inout + off, len);
+                batch_inverse_launch(gpu, &d_inout[0], &d_prefix[0], len,
+                                     &d_nzeros[0]);
+                gpu.DtoH(inout + off, &d_inout[0], len);
+            }
+
+            unsigned long long count;
+            gpu.DtoH(&count, &d_nzeros[0], 1);
+            gpu.sync();
+            total = (size_t)count;
+        }
+
+        if (nzeros != nullptr)
+            *nzeros = total;
+    } catch (const cuda_error& e) {
+        gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), fmt("batch_inverse: %s", e.what())};
+#else
+        return RustError{e.code()};
+#endif
+    }
+
+    return RustError{cudaSuccess};
+}
+
+#endif
+#endif
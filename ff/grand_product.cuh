@@ -0,0 +1,183 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+#ifndef __SPPARK_FF_GRAND_PRODUCT_CUH__
+#define __SPPARK_FF_GRAND_PRODUCT_CUH__
+
+// Running products z_i = Π num_j/den_j of any of the field types in ff/,
+// as in Plonk's permutation and lookup arguments, see grand_product in
+// rust/src/poly.rs for the host implementation. The scan is carried out
+// in segments of GRAND_PRODUCT_STRIDE elements, one per thread, the
+// segments' products being scanned recursively the same way.
+
+#include <util/exception.cuh>
+#include <util/rusterror.h>
+#include <util/gpu_t.cuh>
+
+#include "batch_inverse.cuh"
+
+#ifndef GRAND_PRODUCT_STRIDE
+# define GRAND_PRODUCT_STRIDE 32
+#endif
+
+template<class T>
+__launch_bounds__(256) __global__
+void grand_product_div(T* d_num, const T* d_den_inv, size_t n)
+{
+    size_t idx = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+
+    if (idx < n)
+        d_num[idx] *= d_den_inv[idx];
+}
+
+// Inclusive scan of every segment, with its product stored to |d_totals|.
+template<class T>
+__launch_bounds__(256) __global__
+void grand_product_segments(T* d_inout, size_t n, T* d_totals)
+{
+    size_t tid = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+    size_t start = tid * GRAND_PRODUCT_STRIDE;
+
+    if (start >= n)
+        return;
+
+    size_t end = min(start + GRAND_PRODUCT_STRIDE, n);
+
+    T acc = d_inout[start];
+    for (size_t i = start + 1; i < end; i++) {
+        acc *= d_inout[i];
+        d_inout[i] = acc;
+    }
+
+    d_totals[tid] = acc;
+}
+
+// Multiply every segment by the product of the preceding ones, |d_carries|
+// being the exclusive scan of the segments' products, or null if there's
+// only one. Exclusive scans shift every segment by one element on the way.
+// The last thread stores the product of all elements to |d_total|, if
+// it's non-null.
+template<class T>
+__launch_bounds__(256) __global__
+void grand_product_carry(T* d_inout, size_t n, const T* d_carries,
+                         bool exclusive, T* d_total)
+{
+    size_t tid = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+    size_t start = tid * GRAND_PRODUCT_STRIDE;
+
+    if (start >= n)
+        return;
+
+    size_t end = min(start + GRAND_PRODUCT_STRIDE, n);
+    T carry = d_carries != nullptr ? d_carries[tid] : T::one();
+
+    if (d_total != nullptr && end == n)
+        *d_total = carry * d_inout[n - 1];
+
+    if (exclusive) {
+        for (size_t i = end - 1; i > start; i--)
+            d_inout[i] = carry * d_inout[i - 1];
+        d_inout[start] = carry;
+    } else if (d_carries != nullptr) {
+        for (size_t i = start; i < end; i++)
+            d_inout[i] = carry * d_inout[i];
+    }
+}
+
+#ifndef __CUDA_ARCH__
+
+// Scan |n| elements at |d_inout| in device memory on |stream| in place,
+// and store the product of all of them to |d_total|, if it's non-null.
+template<class T>
+static void grand_product_launch(stream_t& stream, T* d_inout, size_t n,
+                                 bool exclusive, T* d_total = nullptr)
+{
+    if (n == 0)
+        return;
+
+    size_t nsegments = (n + GRAND_PRODUCT_STRIDE - 1) / GRAND_PRODUCT_STRIDE;
+    const uint32_t bsize = (uint32_t)std::min(nsegments, (size_t)256);
+    size_t nblocks = (nsegments + bsize - 1) / bsize;
+
+    dev_ptr_t<T> d_totals{nsegments, stream};
+
+    grand_product_segments<<<nblocks, bsize, 0, stream>>>
+                          (d_inout, n, &d_totals[0]);
+    CUDA_OK(cudaGetLastError());
+
+    if (nsegments > 1)
+        grand_product_launch(stream, &d_totals[0], nsegments, true);
+
+    grand_product_carry<<<nblocks, bsize, 0, stream>>>
+                       (d_inout, n, nsegments > 1 ? &d_totals[0] : nullptr,
+                        exclusive, d_total);
+    CUDA_OK(cudaGetLastError());
+}
+
+// Replace |n| factors at |inout| in host memory with their running
+// products on |gpu|, divided by the running products of |den|, unless
+// it's null. The product of all factors is stored to |*total|, if it's
+// non-null. A zero denominator is SPPARK_ERR_DIVISION_BY_ZERO.
+template<class T>
+static RustError grand_product(const gpu_t& gpu, T* inout, const T* den,
+                               size_t n, bool exclusive, T* total)
+{
+    try {
+        gpu.select();
+
+        if (n == 0) {
+            if (total != nullptr)
+                *total = T::one();
+            return RustError{cudaSuccess};
+        }
+
+        dev_ptr_t<T> d_inout{n + 1, gpu};
+        T* d_total = &d_inout[n];
+
+        gpu.HtoD(&d_inout[0], inout, n);
+
+        if (den != nullptr) {
+            dev_ptr_t<T> d_den{n, gpu};
+            dev_ptr_t<T> d_prefix{n, gpu};
+            dev_ptr_t<unsigned long long> d_nzeros{1, gpu};
+            CUDA_OK(cudaMemsetAsync(&d_nzeros[0], 0, sizeof(unsigned long long),
+                                    gpu));
+
+            gpu.HtoD(&d_den[0], den, n);
+            batch_inverse_launch(gpu, &d_den[0], &d_prefix[0], n,
+                                 &d_nzeros[0]);
+
+            unsigned long long nzeros;
+            gpu.DtoH(&nzeros, &d_nzeros[0], 1);
+            gpu.sync();
+            if (nzeros != 0)
+                throw cuda_error{SPPARK_ERR_DIVISION_BY_ZERO,
+                                 fmt("%llu zero denominator(s)", nzeros)};
+
+            const uint32_t bsize = 256;
+            grand_product_div<<<(n + bsize - 1) / bsize, bsize, 0, gpu>>>
+                             (&d_inout[0], &d_den[0], n);
+            CUDA_OK(cudaGetLastError());
+        }
+
+        grand_product_launch(gpu, &d_inout[0], n, exclusive, d_total);
+
+        gpu.DtoH(inout, &d_inout[0], n);
+        if (total != nullptr)
+            gpu.DtoH(total, d_total, 1);
+        gpu.sync();
+    } catch (const cuda_error& e) {
+        gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), fmt("grand_product: %s", e.what())};
+#else
+        return RustError{e.code()};
+#endif
+    }
+
+    return RustError{cudaSuccess};
+}
+
+#endif
+#endif
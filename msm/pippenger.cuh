@@ -559,7 +559,7 @@ public:
         } catch (const cuda_error& e) {
             gpu.sync();
 #ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
-            return RustError{e.code(), e.what()};
+            return RustError{e.code(), fmt("msm_t::invoke: %s", e.what())};
 #else
             return RustError{e.code()};
 #endif
@@ -708,16 +708,19 @@ private:
 template<class bucket_t, class point_t, class affine_t, class scalar_t> static
 RustError mult_pippenger(point_t *out, const affine_t points[], size_t npoints,
                                        const scalar_t scalars[], bool mont = true,
-                                       size_t ffi_affine_sz = sizeof(affine_t))
+                                       size_t ffi_affine_sz = sizeof(affine_t),
+                                       int device_id = -1)
 {
     try {
-        msm_t<bucket_t, point_t, affine_t, scalar_t> msm{nullptr, npoints};
+        msm_t<bucket_t, point_t, affine_t, scalar_t> msm{nullptr, npoints,
+                                                         sizeof(affine_t),
+                                                         device_id};
         return msm.invoke(*out, slice_t<affine_t>{points, npoints},
                                 scalars, mont, ffi_affine_sz);
     } catch (const cuda_error& e) {
         out->inf();
 #ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
-        return RustError{e.code(), e.what()};
+        return RustError{e.code(), fmt("mult_pippenger: %s", e.what())};
 #else
         return RustError{e.code()};
 #endif
@@ -248,7 +248,8 @@ static void mult_pippenger(point_t& ret, const affine_t points[], size_t npoints
             return;
         }
 
-        std::vector<bucket_t> buckets(1 << window); /* zeroed */
+        std::vector<bucket_t> buckets(1 << window);
+        for (auto& b : buckets) b.inf(); /* bucket_t needn't be trivial */
 
         point_t p;
         ret.inf();
@@ -313,7 +314,8 @@ static void mult_pippenger(point_t& ret, const affine_t points[], size_t npoints
         da_pool->spawn([&, window, total, nbits, nx, counter]() {
             size_t work;
             if ((work = counter++) < total) {
-                std::vector<bucket_t> buckets(1 << window); /* zeroed */
+                std::vector<bucket_t> buckets(1 << window);
+                for (auto& b : buckets) b.inf();
 
                 do {
                     size_t x  = grid[work].x,
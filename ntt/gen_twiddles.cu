@@ -25,6 +25,34 @@ void generate_partial_twiddles(fr_t (*roots)[WINDOW_SIZE],
     }
 }
 
+// Same layout as above, for powers of an arbitrary coset shift, or of its
+// inverse, so that the caller doesn't have to invert on the host.
+__global__
+void generate_partial_powers(fr_t (*powers)[WINDOW_SIZE], const fr_t shift,
+                             bool invert)
+{
+    const unsigned int tid = threadIdx.x + blockDim.x * blockIdx.x;
+    assert(tid < WINDOW_SIZE);
+    fr_t base = shift;
+    fr_t power;
+
+    if (invert)
+        base = base.reciprocal();
+    power = base^tid;
+
+    powers[0][tid] = power;
+
+    for (int off = 1; off < WINDOW_NUM; off++) {
+        for (int i = 0; i < LG_WINDOW_SIZE; i++)
+#if defined(__CUDA_ARCH__)
+            power.sqr();
+#else
+            power *= power;
+#endif
+        powers[off][tid] = power;
+    }
+}
+
 __global__
 void generate_all_twiddles(fr_t* d_radixX_twiddles, const fr_t root6,
                                                     const fr_t root7,
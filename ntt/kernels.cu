@@ -245,6 +245,71 @@ void LDE_spread_distribute_powers(fr_t* out, fr_t* in,
     }
 }
 
+// |out| is |in| transposed, both are row-major, |in| being nrows x ncols.
+__launch_bounds__(1024) __global__
+void transpose_matrix(fr_t* out, const fr_t* in, size_t nrows, size_t ncols)
+{
+    size_t size = nrows * ncols;
+    size_t stride = blockDim.x * (size_t)gridDim.x;
+
+    for (size_t idx = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+         idx < size; idx += stride) {
+        size_t row = idx / ncols, col = idx % ncols;
+        out[col * nrows + row] = in[idx];
+    }
+}
+
+// [shift^-1, 2^-lg_arity] for NTT::FriFold, computed on the device, as the
+// host-side stand-ins of the field types can't.
+__global__
+void fri_fold_consts(fr_t* d_consts, fr_t shift, uint32_t lg_arity)
+{
+    fr_t scale = fr_t::one();
+    for (uint32_t i = 0; i < lg_arity; i++)
+        scale = scale + scale;
+
+    d_consts[0] = shift.reciprocal();
+    d_consts[1] = scale.reciprocal();
+}
+
+// Fold 2^lg_domain_size evaluations in bit-reversed order by 2. Pairs at
+// ±x, x = shift·ω^bit_rev(idx), are adjacent, and are replaced with
+// v0 + v1 + β·(v0 - v1)/x, scaled by |d_scale| if it's non-null. β and
+// the shift are squared |round| times, so that rounds can be chained to
+// fold by larger arities, see NTT::FriFold.
+__launch_bounds__(1024) __global__
+void fri_fold_by_2(fr_t* d_out, const fr_t* d_inp, uint32_t lg_domain_size,
+                   uint32_t round, fr_t challenge, const fr_t* d_shift_inv,
+                   const fr_t* d_scale,
+                   const fr_t (*inverse_roots)[WINDOW_SIZE])
+{
+    index_t idx = threadIdx.x + blockDim.x * (index_t)blockIdx.x;
+
+    if (idx >= (index_t)1 << (lg_domain_size - 1))
+        return;
+
+    fr_t x_inv = *d_shift_inv;
+    for (uint32_t i = 0; i < round; i++) {
+        x_inv.sqr();
+        challenge.sqr();
+    }
+
+    if (lg_domain_size > 1) {
+        index_t pow = bit_rev(idx, lg_domain_size - 1);
+        x_inv *= get_intermediate_root(pow << (MAX_LG_DOMAIN_SIZE -
+                                               lg_domain_size),
+                                       inverse_roots);
+    }
+
+    fr_t v0 = d_inp[2*idx], v1 = d_inp[2*idx + 1];
+    fr_t r = v0 + v1;
+    r += challenge * ((v0 - v1) * x_inv);
+    if (d_scale != nullptr)
+        r = r * *d_scale;
+
+    d_out[idx] = r;
+}
+
 __device__ __forceinline__
 void get_intermediate_roots(fr_t& root0, fr_t& root1,
                             index_t idx0, index_t idx1,
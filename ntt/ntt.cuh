@@ -23,12 +23,25 @@ public:
     enum class Direction { forward, inverse };
     enum class Type { standard, coset };
     enum class Algorithm { GS, CT };
+    // Batch data layout, column-major places polynomials one after another,
+    // row-major interleaves them, i.e. it's a domain_size x ncolumns matrix.
+    enum class Layout { column_major, row_major };
 
 protected:
+    // Twiddle tables cover domains up to 2^MAX_LG_DOMAIN_SIZE, larger ones
+    // would produce garbage. Keep the message in sync with rust/src/lib.rs.
+    static void check_domain_size(uint32_t lg_domain_size)
+    {
+        if (lg_domain_size > MAX_LG_DOMAIN_SIZE)
+            throw cuda_error{SPPARK_ERR_DOMAIN_TOO_LARGE,
+                             fmt("lg_domain_size %u exceeds %u",
+                                 lg_domain_size, (uint32_t)MAX_LG_DOMAIN_SIZE)};
+    }
+
     static void bit_rev(fr_t* d_out, const fr_t* d_inp,
                         uint32_t lg_domain_size, stream_t& stream)
     {
-        assert(lg_domain_size <= MAX_LG_DOMAIN_SIZE);
+        check_domain_size(lg_domain_size);
 
         size_t domain_size = (size_t)1 << lg_domain_size;
         // aim to read 4 cache lines of consecutive data per read
@@ -55,14 +68,37 @@ protected:
         CUDA_OK(cudaGetLastError());
     }
 
+    static void transpose(fr_t* d_out, const fr_t* d_inp,
+                          size_t nrows, size_t ncols, stream_t& stream)
+    {
+        const uint32_t bsize = 1024;
+        size_t nblocks = (nrows * ncols + bsize - 1) / bsize;
+        nblocks = std::min(nblocks,
+                           (size_t)gpu_props(stream).multiProcessorCount * 8);
+
+        transpose_matrix<<<nblocks, bsize, 0, stream>>>
+                        (d_out, d_inp, nrows, ncols);
+
+        CUDA_OK(cudaGetLastError());
+    }
+
 private:
     static void LDE_powers(fr_t* inout, bool innt, bool bitrev,
                            uint32_t lg_domain_size, uint32_t lg_blowup,
-                           stream_t& stream, bool ext_pow = false)
+                           stream_t& stream, bool ext_pow = false,
+                           const fr_t* coset_shift = nullptr)
     {
         size_t domain_size = (size_t)1 << lg_domain_size;
-        const auto gen_powers =
-            NTTParameters::all(innt)[stream]->partial_group_gen_powers;
+        const auto& params = *NTTParameters::all(innt)[stream];
+        auto gen_powers = params.partial_group_gen_powers;
+
+        dev_ptr_t<fr_t> d_coset_powers{coset_shift ? WINDOW_NUM * WINDOW_SIZE
+                                                   : 0, stream};
+        if (coset_shift) {
+            gen_powers = reinterpret_cast<decltype(gen_powers)>
+                         (&d_coset_powers[0]);
+            params.coset_powers(gen_powers, *coset_shift, stream);
+        }
 
         if (domain_size < WARP_SZ)
             LDE_distribute_powers<<<1, domain_size, 0, stream>>>
@@ -78,12 +114,16 @@ private:
     }
 
 protected:
-    // coset_ext_pow is only used when NTT type is coset
+    // coset_ext_pow and coset_shift are only used when NTT type is coset,
+    // the latter replaces the group generator if specified
     static void NTT_internal(fr_t* d_inout, uint32_t lg_domain_size,
                              InputOutputOrder order, Direction direction,
                              Type type, stream_t& stream,
-                             bool coset_ext_pow = false)
+                             bool coset_ext_pow = false,
+                             const fr_t* coset_shift = nullptr)
     {
+        check_domain_size(lg_domain_size);
+
         // Pick an NTT algorithm based on the input order and the desired output
         // order of the data. In certain cases, bit reversal can be avoided which
         // results in a considerable performance gain.
@@ -117,7 +157,7 @@ protected:
 
         if (!intt && type == Type::coset)
             LDE_powers(d_inout, intt, bitrev, lg_domain_size, 0, stream,
-                       coset_ext_pow);
+                       coset_ext_pow, coset_shift);
 
         switch (algorithm) {
             case Algorithm::GS:
@@ -130,7 +170,7 @@ protected:
 
         if (intt && type == Type::coset)
             LDE_powers(d_inout, intt, !bitrev, lg_domain_size, 0, stream,
-                       coset_ext_pow);
+                       coset_ext_pow, coset_shift);
 
         if (order == InputOutputOrder::RR)
             bit_rev(d_inout, d_inout, lg_domain_size, stream);
@@ -147,6 +187,8 @@ public:
         try {
             gpu.select();
 
+            check_domain_size(lg_domain_size);
+
             size_t domain_size = (size_t)1 << lg_domain_size;
             dev_ptr_t<fr_t> d_inout{domain_size, gpu};
             gpu.HtoD(&d_inout[0], inout, domain_size);
@@ -159,7 +201,7 @@ public:
         } catch (const cuda_error& e) {
             gpu.sync();
 #ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
-            return RustError{e.code(), e.what()};
+            return RustError{e.code(), fmt("NTT::Base: %s", e.what())};
 #else
             return RustError{e.code()};
 #endif
@@ -168,6 +210,164 @@ public:
         return RustError{cudaSuccess};
     }
 
+    // Same as Base with Type::coset, but over the coset |shift|·H instead
+    // of the group generator one. Powers of |shift| are generated with
+    // each call.
+    static RustError Coset(const gpu_t& gpu, fr_t* inout,
+                           uint32_t lg_domain_size, const fr_t& shift,
+                           InputOutputOrder order, Direction direction)
+    {
+        if (lg_domain_size == 0)
+            return RustError{cudaSuccess};
+
+        try {
+            gpu.select();
+
+            check_domain_size(lg_domain_size);
+
+            size_t domain_size = (size_t)1 << lg_domain_size;
+            dev_ptr_t<fr_t> d_inout{domain_size, gpu};
+            gpu.HtoD(&d_inout[0], inout, domain_size);
+
+            NTT_internal(&d_inout[0], lg_domain_size, order, direction,
+                         Type::coset, gpu, false, &shift);
+
+            gpu.DtoH(inout, &d_inout[0], domain_size);
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("NTT::Coset: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
+
+    // Transform |ncolumns| polynomials of the same size, as many at a time
+    // as fit into a quarter of the device memory.
+    static RustError Batch(const gpu_t& gpu, fr_t* inout,
+                           uint32_t lg_domain_size, size_t ncolumns,
+                           Layout layout, InputOutputOrder order,
+                           Direction direction, Type type,
+                           bool coset_ext_pow = false)
+    {
+        if (lg_domain_size == 0 || ncolumns == 0)
+            return RustError{cudaSuccess};
+
+        try {
+            gpu.select();
+
+            check_domain_size(lg_domain_size);
+
+            size_t domain_size = (size_t)1 << lg_domain_size;
+            // row-major data is transposed on device, hence an extra buffer
+            size_t nbufs = layout == Layout::row_major ? 2 : 1;
+            size_t batch = gpu.props().totalGlobalMem / 4
+                         / (nbufs * domain_size * sizeof(fr_t));
+            batch = std::max<size_t>(1, std::min(batch, ncolumns));
+
+            dev_ptr_t<fr_t> d_buf{nbufs * batch * domain_size, gpu};
+            fr_t* d_cols = &d_buf[0];
+            fr_t* d_rows = &d_buf[(nbufs - 1) * batch * domain_size];
+
+            for (size_t col = 0; col < ncolumns; col += batch) {
+                size_t ncols = std::min(batch, ncolumns - col);
+
+                if (layout == Layout::column_major) {
+                    gpu.HtoD(d_cols, &inout[col * domain_size],
+                             ncols * domain_size);
+                } else {
+                    CUDA_OK(cudaMemcpy2DAsync(d_rows, ncols * sizeof(fr_t),
+                                              &inout[col], ncolumns * sizeof(fr_t),
+                                              ncols * sizeof(fr_t), domain_size,
+                                              cudaMemcpyHostToDevice, gpu));
+                    transpose(d_cols, d_rows, domain_size, ncols, gpu);
+                }
+
+                for (size_t i = 0; i < ncols; i++)
+                    NTT_internal(&d_cols[i * domain_size], lg_domain_size,
+                                 order, direction, type, gpu, coset_ext_pow);
+
+                if (layout == Layout::column_major) {
+                    gpu.DtoH(&inout[col * domain_size], d_cols,
+                             ncols * domain_size);
+                } else {
+                    transpose(d_rows, d_cols, ncols, domain_size, gpu);
+                    CUDA_OK(cudaMemcpy2DAsync(&inout[col], ncolumns * sizeof(fr_t),
+                                              d_rows, ncols * sizeof(fr_t),
+                                              ncols * sizeof(fr_t), domain_size,
+                                              cudaMemcpyDeviceToHost, gpu));
+                }
+            }
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("NTT::Batch: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
+
+    // Permute |ncolumns| polynomials of 2^lg_domain_size elements each,
+    // laid out one after another, between natural and bit-reversed order,
+    // as many at a time as fit into a quarter of the device memory. |out|
+    // and |inp| may be the same.
+    static RustError BitRev(const gpu_t& gpu, fr_t* out, const fr_t* inp,
+                            uint32_t lg_domain_size, size_t ncolumns = 1)
+    {
+        if (ncolumns == 0)
+            return RustError{cudaSuccess};
+
+        try {
+            gpu.select();
+
+            check_domain_size(lg_domain_size);
+
+            if (lg_domain_size == 0) {
+                if (out != inp)
+                    std::copy(inp, inp + ncolumns, out);
+                return RustError{cudaSuccess};
+            }
+
+            size_t domain_size = (size_t)1 << lg_domain_size;
+            size_t batch = gpu.props().totalGlobalMem / 4
+                         / (domain_size * sizeof(fr_t));
+            batch = std::max<size_t>(1, std::min(batch, ncolumns));
+
+            dev_ptr_t<fr_t> d_buf{batch * domain_size, gpu};
+
+            for (size_t col = 0; col < ncolumns; col += batch) {
+                size_t ncols = std::min(batch, ncolumns - col);
+
+                gpu.HtoD(&d_buf[0], &inp[col * domain_size],
+                         ncols * domain_size);
+                BitRev_dev_ptr(gpu, &d_buf[0], &d_buf[0], lg_domain_size,
+                               ncols);
+                gpu.DtoH(&out[col * domain_size], &d_buf[0],
+                         ncols * domain_size);
+            }
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("NTT::BitRev: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
+
+    // Evaluations over the coset shifted by group_gen, or by
+    // group_gen^(2^lg_blowup) if |ext_pow| is set, see CHANGELOG.md.
     static RustError LDE(const gpu_t& gpu, fr_t* inout,
                          uint32_t lg_domain_size, uint32_t lg_blowup,
                          bool ext_pow = false)
@@ -175,6 +375,8 @@ public:
         try {
             gpu.select();
 
+            check_domain_size(lg_domain_size + lg_blowup);
+
             size_t domain_size = (size_t)1 << lg_domain_size;
             size_t ext_domain_size = domain_size << lg_blowup;
             dev_ptr_t<fr_t> d_ext_domain{ext_domain_size, gpu};
@@ -182,26 +384,79 @@ public:
 
             gpu.HtoD(&d_domain[0], inout, domain_size);
 
-            NTT_internal(&d_domain[0], lg_domain_size,
-                         InputOutputOrder::NR, Direction::inverse,
-                         Type::standard, gpu);
+            LDE_internal(gpu, &d_ext_domain[0], &d_domain[0],
+                         lg_domain_size, lg_blowup, ext_pow);
 
-            const auto gen_powers =
-                NTTParameters::all()[gpu.id()]->partial_group_gen_powers;
+            gpu.DtoH(inout, &d_ext_domain[0], ext_domain_size);
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("NTT::LDE: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
 
-            LDE_launch(gpu, &d_ext_domain[0], &d_domain[0],
-                       gen_powers, lg_domain_size, lg_blowup, ext_pow);
+    // Fold 2^lg_domain_size evaluations over the coset shift·H, in
+    // bit-reversed order, by 2^lg_arity with |challenge| for the next FRI
+    // layer, see rust/src/ntt/fri.rs. The twiddles are the inverse ones in
+    // NTTParameters.
+    static RustError FriFold(const gpu_t& gpu, fr_t* out, const fr_t* inp,
+                             uint32_t lg_domain_size, uint32_t lg_arity,
+                             const fr_t& challenge, const fr_t& shift)
+    {
+        try {
+            gpu.select();
 
-            NTT_internal(&d_ext_domain[0], lg_domain_size + lg_blowup,
-                         InputOutputOrder::RN, Direction::forward,
-                         Type::standard, gpu);
+            check_domain_size(lg_domain_size);
+            if (lg_arity > lg_domain_size)
+                throw cuda_error{SPPARK_ERR_LENGTH_MISMATCH,
+                                 fmt("folding arity 2^%u exceeds domain size 2^%u",
+                                     lg_arity, lg_domain_size)};
 
-            gpu.DtoH(inout, &d_ext_domain[0], ext_domain_size);
+            size_t domain_size = (size_t)1 << lg_domain_size;
+
+            if (lg_arity == 0) {
+                std::copy(inp, inp + domain_size, out);
+                return RustError{cudaSuccess};
+            }
+
+            // rounds alternate between the two halves
+            dev_ptr_t<fr_t> d_buf{domain_size + domain_size / 2, gpu};
+            dev_ptr_t<fr_t> d_consts{2, gpu};
+            const auto& params = *NTTParameters::all(true)[gpu.id()];
+
+            gpu.HtoD(&d_buf[0], inp, domain_size);
+            fri_fold_consts<<<1, 1, 0, gpu>>>(&d_consts[0], shift, lg_arity);
+            CUDA_OK(cudaGetLastError());
+
+            fr_t* d_inp = &d_buf[0];
+            fr_t* d_out = &d_buf[domain_size];
+            for (uint32_t round = 0; round < lg_arity; round++) {
+                uint32_t lg = lg_domain_size - round;
+                size_t half = (size_t)1 << (lg - 1);
+                const uint32_t bsize = (uint32_t)std::min(half, (size_t)1024);
+                const fr_t* d_scale = round == lg_arity - 1 ? &d_consts[1]
+                                                           : nullptr;
+
+                fri_fold_by_2<<<(half + bsize - 1) / bsize, bsize, 0, gpu>>>
+                             (d_out, d_inp, lg, round, challenge,
+                              &d_consts[0], d_scale, params.partial_twiddles);
+                CUDA_OK(cudaGetLastError());
+
+                std::swap(d_inp, d_out);
+            }
+
+            gpu.DtoH(out, d_inp, domain_size >> lg_arity);
             gpu.sync();
         } catch (const cuda_error& e) {
             gpu.sync();
 #ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
-            return RustError{e.code(), e.what()};
+            return RustError{e.code(), fmt("NTT::FriFold: %s", e.what())};
 #else
             return RustError{e.code()};
 #endif
@@ -211,13 +466,33 @@ public:
     }
 
 protected:
+    // |d_domain| is expected to be aligned to the end of |d_ext_domain|
+    static void LDE_internal(stream_t& stream, fr_t* d_ext_domain,
+                             fr_t* d_domain, uint32_t lg_domain_size,
+                             uint32_t lg_blowup, bool ext_pow)
+    {
+        NTT_internal(d_domain, lg_domain_size,
+                     InputOutputOrder::NR, Direction::inverse,
+                     Type::standard, stream);
+
+        const auto gen_powers =
+            NTTParameters::all()[stream.id()]->partial_group_gen_powers;
+
+        LDE_launch(stream, d_ext_domain, d_domain,
+                   gen_powers, lg_domain_size, lg_blowup, true, ext_pow);
+
+        NTT_internal(d_ext_domain, lg_domain_size + lg_blowup,
+                     InputOutputOrder::RN, Direction::forward,
+                     Type::standard, stream);
+    }
+
     static void LDE_launch(stream_t& stream,
                            fr_t* ext_domain_data, fr_t* domain_data,
                            const fr_t (*gen_powers)[WINDOW_SIZE],
                            uint32_t lg_domain_size, uint32_t lg_blowup,
                            bool perform_shift = true, bool ext_pow = false)
     {
-        assert(lg_domain_size + lg_blowup <= MAX_LG_DOMAIN_SIZE);
+        check_domain_size(lg_domain_size + lg_blowup);
         size_t domain_size = (size_t)1 << lg_domain_size;
         size_t ext_domain_size = domain_size << lg_blowup;
 
@@ -255,6 +530,8 @@ public:
                              bool ext_pow = false)
     {
         try {
+            check_domain_size(lg_domain_size + lg_blowup);
+
             size_t domain_size = (size_t)1 << lg_domain_size;
             size_t ext_domain_size = domain_size << lg_blowup;
             // The 2nd to last 'domain_size' chunk will hold the original data
@@ -287,7 +564,7 @@ public:
         } catch (const cuda_error& e) {
             gpu.sync();
 #ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
-            return RustError{e.code(), e.what()};
+            return RustError{e.code(), fmt("NTT::LDE_aux: %s", e.what())};
 #else
             return RustError{e.code()};
 #endif
@@ -314,6 +591,44 @@ public:
         LDE_powers(d_inout, false, true, lg_domain_size, 0, stream, ext_pow);
     }
 
+    // Same as LDE, but in place on device memory, the input being the
+    // first 2^lg_domain_size elements of |d_inout|.
+    static void LDE_dev_ptr(stream_t& stream, fr_t* d_inout,
+                            uint32_t lg_domain_size, uint32_t lg_blowup,
+                            bool ext_pow = false)
+    {
+        size_t domain_size = (size_t)1 << lg_domain_size;
+        size_t ext_domain_size = domain_size << lg_blowup;
+        fr_t* d_domain = &d_inout[ext_domain_size - domain_size];
+
+        if (d_domain != d_inout)
+            CUDA_OK(cudaMemcpyAsync(d_domain, d_inout,
+                                    domain_size * sizeof(fr_t),
+                                    cudaMemcpyDeviceToDevice, stream));
+
+        LDE_internal(stream, d_inout, d_domain, lg_domain_size, lg_blowup,
+                     ext_pow);
+    }
+
+    // Same as BitRev, but on device memory.
+    static void BitRev_dev_ptr(stream_t& stream, fr_t* d_out,
+                               const fr_t* d_inp, uint32_t lg_domain_size,
+                               size_t ncolumns = 1)
+    {
+        size_t domain_size = (size_t)1 << lg_domain_size;
+
+        if (lg_domain_size == 0) {
+            if (d_out != d_inp)
+                CUDA_OK(cudaMemcpyAsync(d_out, d_inp, ncolumns * sizeof(fr_t),
+                                        cudaMemcpyDeviceToDevice, stream));
+            return;
+        }
+
+        for (size_t i = 0; i < ncolumns; i++)
+            bit_rev(&d_out[i * domain_size], &d_inp[i * domain_size],
+                    lg_domain_size, stream);
+    }
+
     // If d_out and d_in overlap, d_out is expected to encompass d_in and
     // d_in is expected to be aligned to the end of d_out
     // The input is expected to be in bit-reversed order
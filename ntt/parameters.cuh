@@ -166,6 +166,18 @@ public:
 
     inline void sync() const    { gpu.sync(); }
 
+    // Powers of |shift|, or of its inverse for the inverse parameters, laid
+    // out as partial_group_gen_powers, to the caller's |powers| of
+    // WINDOW_NUM * WINDOW_SIZE elements. The table is not cached, as
+    // generating it is cheap compared to the transform that uses it.
+    void coset_powers(fr_t (*powers)[WINDOW_SIZE], const fr_t& shift,
+                      stream_t& stream) const
+    {
+        generate_partial_powers<<<WINDOW_SIZE/32, 32, 0, stream>>>
+            (powers, shift, inverse);
+        CUDA_OK(cudaGetLastError());
+    }
+
 private:
     class all_params { friend class NTTParameters;
         std::vector<const NTTParameters*> forward;
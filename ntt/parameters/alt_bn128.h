@@ -9,7 +9,10 @@
 #endif
 
 // `group_gen = 7 mod r` is a generator of the `r - 1` order multiplicative
-// subgroup, or in other words a primitive root of the field.
+// subgroup, or in other words a primitive root of the field. Note that
+// arkworks derives its roots of unity and coset shift from 5 instead, so
+// that coset transforms, and ones over domains larger than 2^5, match
+// ark-bn254's only over a caller-chosen Domain.
 const fr_t group_gen = FR_T(vec256, 0x3057819e4fffffdbu, 0x307f6d866832bb01u, 0x5c65ec9f484e3a89u, 0x0180a96573d3d9f8u);
 
 // modular inverse of group_generator in r
@@ -4,68 +4,55 @@
 
 use criterion::{criterion_group, criterion_main, Criterion};
 
-#[cfg(feature = "bls12_377")]
-use ark_bls12_377::{G1Affine, G2Affine};
-#[cfg(feature = "bls12_381")]
-use ark_bls12_381::{G1Affine, G2Affine};
-#[cfg(feature = "bn254")]
-use ark_bn254::G1Affine;
-use ark_ff::BigInteger256;
+use ark_ff::PrimeField;
 
 use std::str::FromStr;
 
 use msm_cuda::*;
 
-fn criterion_benchmark(c: &mut Criterion) {
+fn bench_msm<G: MsmCurve>(c: &mut Criterion, group: &str, sample_size: usize) {
     let bench_npow = std::env::var("BENCH_NPOW").unwrap_or("23".to_string());
     let npoints_npow = i32::from_str(&bench_npow).unwrap();
 
     let (points, scalars) =
-        util::generate_points_scalars::<G1Affine>(1usize << npoints_npow);
+        util::generate_points_scalars::<G>(1usize << npoints_npow);
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
 
-    let mut group = c.benchmark_group("CUDA");
-    group.sample_size(20);
+    let mut group = c.benchmark_group(group);
+    group.sample_size(sample_size);
 
     let name = format!("2**{}", npoints_npow);
     group.bench_function(name, |b| {
         b.iter(|| {
-            let _ = multi_scalar_mult_arkworks(&points.as_slice(), unsafe {
-                std::mem::transmute::<&[_], &[BigInteger256]>(
-                    scalars.as_slice(),
-                )
-            });
+            let _ = multi_scalar_mult_arkworks(
+                Device::default(),
+                &points,
+                &scalars,
+            );
         })
     });
 
     group.finish();
 }
 
+fn criterion_benchmark(c: &mut Criterion) {
+    #[cfg(feature = "bn254")]
+    bench_msm::<ark_bn254::G1Affine>(c, "CUDA/bn254", 20);
+    #[cfg(feature = "bls12_377")]
+    bench_msm::<ark_bls12_377::G1Affine>(c, "CUDA/bls12_377", 20);
+    #[cfg(feature = "bls12_381")]
+    bench_msm::<ark_bls12_381::G1Affine>(c, "CUDA/bls12_381", 20);
+}
+
 #[cfg(not(any(feature = "bls12_381", feature = "bls12_377")))]
 criterion_group!(benches, criterion_benchmark);
 
 #[cfg(any(feature = "bls12_381", feature = "bls12_377"))]
 fn criterion_benchmark_fp2(c: &mut Criterion) {
-    let bench_npow = std::env::var("BENCH_NPOW").unwrap_or("23".to_string());
-    let npoints_npow = i32::from_str(&bench_npow).unwrap();
-
-    let (points, scalars) =
-        util::generate_points_scalars::<G2Affine>(1usize << npoints_npow);
-
-    let mut group = c.benchmark_group("CUDA");
-    group.sample_size(10);
-
-    let name = format!("2**{}", npoints_npow);
-    group.bench_function(name, |b| {
-        b.iter(|| {
-            let _ = multi_scalar_mult_fp2_arkworks(&points.as_slice(), unsafe {
-                std::mem::transmute::<&[_], &[BigInteger256]>(
-                    scalars.as_slice(),
-                )
-            });
-        })
-    });
-
-    group.finish();
+    #[cfg(feature = "bls12_377")]
+    bench_msm::<ark_bls12_377::G2Affine>(c, "CUDA/bls12_377/G2", 10);
+    #[cfg(feature = "bls12_381")]
+    bench_msm::<ark_bls12_381::G2Affine>(c, "CUDA/bls12_381/G2", 10);
 }
 
 #[cfg(any(feature = "bls12_381", feature = "bls12_377"))]
@@ -5,35 +5,14 @@
 use std::env;
 use std::path::PathBuf;
 
-fn feature_check() {
-    let curves = ["bn254", "bls12_377", "bls12_381"];
-    let curves_as_features: Vec<String> = (0..curves.len())
-        .map(|i| format!("CARGO_FEATURE_{}", curves[i].to_uppercase()))
-        .collect();
-
-    let mut curve_counter = 0;
-    for curve_feature in curves_as_features.iter() {
-        curve_counter += env::var(&curve_feature).is_ok() as i32;
-    }
-
-    match curve_counter {
-        0 => panic!("Can't run without a curve being specified, please select one with --features=<curve>. Available options are\n{:#?}\n", curves),
-        2.. => panic!("Multiple curves are not supported, please select only one."),
-        _ => (),
-    };
-}
+use sppark_build::{Build, Field};
 
 fn main() {
-    feature_check();
-
-    let mut curve = "";
-    if cfg!(feature = "bn254") {
-        curve = "FEATURE_BN254";
-    } else if cfg!(feature = "bls12_377") {
-        curve = "FEATURE_BLS12_377";
-    } else if cfg!(feature = "bls12_381") {
-        curve = "FEATURE_BLS12_381";
-    }
+    let curves = Field::all_from_features(&[
+        Field::Bn254,
+        Field::Bls12_377,
+        Field::Bls12_381,
+    ]);
 
     // account for cross-compilation [by examining environment variable]
     let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
@@ -87,38 +66,57 @@ fn main() {
     }
     cc.files(&files).compile("msm_cuda");
 
-    if cfg!(target_os = "windows") && !cfg!(target_env = "msvc") {
-        return;
-    }
-    // Detect if there is CUDA compiler and engage "cuda" feature accordingly
-    let nvcc = match env::var("NVCC") {
-        Ok(var) => which::which(var),
-        Err(_) => which::which("nvcc"),
-    };
-
-    if nvcc.is_ok() {
-        let mut nvcc = cc::Build::new();
-        nvcc.cuda(true);
-        nvcc.flag("-arch=sm_80");
-        nvcc.flag("-gencode").flag("arch=compute_70,code=sm_70");
-        nvcc.flag("-t0");
-        #[cfg(not(target_env = "msvc"))]
-        nvcc.flag("-Xcompiler").flag("-Wno-unused-function");
-        nvcc.define("TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE", None);
-        nvcc.define(curve, None);
+    // Host implementation, used when there is no CUDA compiler or device.
+    // One library per curve, each exporting host_mult_pippenger_*_<feature>.
+    for curve in &curves {
+        let mut cpp = cc::Build::new();
+        cpp.cpp(true);
+        if cfg!(target_env = "msvc") {
+            cpp.flag("/std:c++17");
+        } else {
+            cpp.flag("-std=c++17");
+        }
+        cpp.flag_if_supported("-mno-avx") // avoid costly transitions
+            .flag_if_supported("-fno-builtin")
+            .flag_if_supported("-Wno-unused-function")
+            .flag_if_supported("-Wno-unused-command-line-argument");
+        if !cfg!(debug_assertions) {
+            cpp.opt_level(2);
+        }
+        cpp.define(curve.define(), None)
+            .define("MSM_CURVE", Some(curve.feature()));
         if let Some(def) = cc_opt {
-            nvcc.define(def, None);
+            cpp.define(def, None);
         }
         if let Some(include) = env::var_os("DEP_BLST_C_SRC") {
-            nvcc.include(include);
+            cpp.include(include);
         }
         if let Some(include) = env::var_os("DEP_SPPARK_ROOT") {
-            nvcc.include(include);
+            cpp.include(include);
         }
-        nvcc.file("cuda/pippenger_inf.cu").compile("blst_cuda_msm");
+        cpp.file("src/pippenger_host.cpp")
+            .compile(&format!("msm_host_{}", curve.feature()));
+    }
+    println!("cargo:rerun-if-changed=src/pippenger_host.cpp");
 
-        println!("cargo:rustc-cfg=feature=\"cuda\"");
-        println!("cargo:rerun-if-changed=cuda");
-        println!("cargo:rerun-if-env-changed=CXXFLAGS");
+    if cfg!(target_os = "windows") && !cfg!(target_env = "msvc") {
+        return;
+    }
+    // Detect if there is CUDA compiler and engage "cuda" feature accordingly
+    if sppark_build::nvcc().is_some() {
+        // One library per curve, each exporting mult_pippenger_*_<feature>.
+        for curve in curves {
+            let mut nvcc = Build::new();
+            nvcc.field(curve)
+                .define("MSM_CURVE", Some(curve.feature()))
+                .arch(80)
+                .arch(70)
+                .flag("-t0");
+            if let Some(def) = cc_opt {
+                nvcc.define(def, None);
+            }
+            nvcc.file("cuda/pippenger_inf.cu")
+                .compile(&format!("blst_cuda_msm_{}", curve.feature()));
+        }
     }
 }
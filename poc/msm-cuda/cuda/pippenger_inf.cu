@@ -4,6 +4,32 @@
 
 #include <cuda.h>
 
+// Every enabled curve is compiled separately, with MSM_CURVE set to the
+// name of the cargo feature, into a namespace of its own, so that kernels
+// and constants of different curves don't collide at link time.
+// Field-independent headers are included upfront to keep them out of it.
+#ifndef MSM_CURVE
+# error "no MSM_CURVE"
+#endif
+#define MSM_PASTE_(a, b) a##_##b
+#define MSM_PASTE(a, b) MSM_PASTE_(a, b)
+
+#include <cstddef>
+#include <cstdint>
+#include <cassert>
+#include <vector>
+#include <cooperative_groups.h>
+#include <util/vec2d_t.hpp>
+#include <util/slice_t.hpp>
+#include <util/exception.cuh>
+#include <util/rusterror.h>
+#include <util/gpu_t.cuh>
+#ifndef __CUDA_ARCH__
+# include <blst_t.hpp>
+#endif
+
+namespace MSM_PASTE(msm, MSM_CURVE) {
+
 #if defined(FEATURE_BLS12_381)
 # include <ff/bls12-381-fp2.hpp>
 #elif defined(FEATURE_BLS12_377)
@@ -22,27 +48,41 @@ typedef xyzz_t<fp_t> bucket_t;
 typedef bucket_t::affine_inf_t affine_t;
 typedef fr_t scalar_t;
 
+#if defined(FEATURE_BLS12_381) || defined(FEATURE_BLS12_377)
+typedef jacobian_t<fp2_t> point_fp2_t;
+typedef xyzz_t<fp2_t> bucket_fp2_t;
+typedef bucket_fp2_t::affine_inf_t affine_fp2_t;
+#endif
+
 #define SPPARK_DONT_INSTANTIATE_TEMPLATES
 #include <msm/pippenger.cuh>
 
+}
+
+#ifndef __CUDA_ARCH__
+
+using namespace MSM_PASTE(msm, MSM_CURVE);
+
 extern "C"
-RustError::by_value mult_pippenger_inf(point_t* out, const affine_t points[],
-                                       size_t npoints, const scalar_t scalars[],
-                                       size_t ffi_affine_sz)
+RustError::by_value MSM_PASTE(mult_pippenger_inf, MSM_CURVE)(
+                        point_t* out, const affine_t points[], size_t npoints,
+                        const scalar_t scalars[], size_t ffi_affine_sz,
+                        int device_id)
 {
-    return mult_pippenger<bucket_t>(out, points, npoints, scalars, false, ffi_affine_sz);
+    return mult_pippenger<bucket_t>(out, points, npoints, scalars, false,
+                                    ffi_affine_sz, device_id);
 }
 
 #if defined(FEATURE_BLS12_381) || defined(FEATURE_BLS12_377)
-typedef jacobian_t<fp2_t> point_fp2_t;
-typedef xyzz_t<fp2_t> bucket_fp2_t;
-typedef bucket_fp2_t::affine_inf_t affine_fp2_t;
-
 extern "C"
-RustError::by_value mult_pippenger_fp2_inf(point_fp2_t* out, const affine_fp2_t points[],
-                                           size_t npoints, const scalar_t scalars[],
-                                           size_t ffi_affine_sz)
+RustError::by_value MSM_PASTE(mult_pippenger_fp2_inf, MSM_CURVE)(
+                        point_fp2_t* out, const affine_fp2_t points[],
+                        size_t npoints, const scalar_t scalars[],
+                        size_t ffi_affine_sz, int device_id)
 {
-    return mult_pippenger<bucket_fp2_t>(out, points, npoints, scalars, false, ffi_affine_sz);
+    return mult_pippenger<bucket_fp2_t>(out, points, npoints, scalars, false,
+                                        ffi_affine_sz, device_id);
 }
 #endif
+
+#endif
@@ -2,12 +2,7 @@
 // Licensed under the Apache License, Version 2.0, see LICENSE for details.
 // SPDX-License-Identifier: Apache-2.0
 
-#[cfg(feature = "bls12_377")]
-use ark_bls12_377::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
-#[cfg(feature = "bls12_381")]
-use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
-#[cfg(feature = "bn254")]
-use ark_bn254::{Fr, G1Affine, G1Projective};
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
 use ark_ec::AffineCurve;
 use ark_ff::PrimeField;
 use ark_std::Zero;
@@ -15,10 +10,34 @@ use blst::*;
 
 pub mod util;
 
-pub fn multi_scalar_mult(
+pub use sppark::device::Device;
+
+// Is there a device for the GPU implementation to run on? If not, the
+// host implementation is used instead.
+#[cfg(feature = "cuda")]
+fn cuda_available() -> bool {
+    extern "C" {
+        fn cuda_available() -> bool;
+    }
+    unsafe { cuda_available() }
+}
+
+// C++ validates the id as well, but only if there is a device to run on.
+fn check_device(device: Device) -> Result<(), sppark::Error> {
+    if let Some(id) = device.id() {
+        let available = sppark::device::devices().len();
+        if id >= available {
+            return Err(sppark::Error::invalid_device(id, available));
+        }
+    }
+    Ok(())
+}
+
+pub fn try_multi_scalar_mult(
+    device: Device,
     points: &[blst_p1_affine],
     scalars: &[blst_scalar],
-) -> blst_p1 {
+) -> Result<blst_p1, sppark::Error> {
     #[cfg_attr(feature = "quiet", allow(improper_ctypes))]
     extern "C" {
         fn mult_pippenger(
@@ -26,94 +45,206 @@ pub fn multi_scalar_mult(
             points: *const blst_p1_affine,
             npoints: usize,
             scalars: *const blst_scalar,
+            device_id: i32,
         ) -> sppark::Error;
     }
 
+    check_device(device)?;
     let npoints = points.len();
     if npoints != scalars.len() {
-        panic!("length mismatch")
+        return Err(sppark::Error::length_mismatch(npoints, scalars.len()));
     }
 
     let mut ret = blst_p1::default();
-    let err =
-        unsafe { mult_pippenger(&mut ret, &points[0], npoints, &scalars[0]) };
+    if npoints == 0 {
+        return Ok(ret);
+    }
+    let err = unsafe {
+        mult_pippenger(
+            &mut ret,
+            points.as_ptr(),
+            npoints,
+            scalars.as_ptr(),
+            device.as_raw(),
+        )
+    };
     if err.code != 0 {
-        panic!("{}", String::from(err));
+        return Err(err.with_op("mult_pippenger"));
     }
-    ret
+    Ok(ret)
 }
 
-pub fn multi_scalar_mult_arkworks<G: AffineCurve>(
+pub fn multi_scalar_mult(
+    device: Device,
+    points: &[blst_p1_affine],
+    scalars: &[blst_scalar],
+) -> blst_p1 {
+    try_multi_scalar_mult(device, points, scalars)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// A group the MSM was compiled for, i.e. G1 of every curve enabled with
+/// the cargo feature of the same name, and G2 of the BLS12 ones. More than
+/// one curve can be enabled at a time.
+pub trait MsmCurve: AffineCurve {
+    #[doc(hidden)]
+    unsafe fn mult_pippenger(
+        out: *mut Self::Projective,
+        points_with_infinity: *const Self,
+        npoints: usize,
+        scalars: *const <Self::ScalarField as PrimeField>::BigInt,
+        ffi_affine_sz: usize,
+        device_id: i32,
+    ) -> sppark::Error;
+}
+
+// Tie a group to its [host_]mult_pippenger_*_<feature> symbols, see
+// cuda/pippenger_inf.cu and src/pippenger_host.cpp.
+macro_rules! msm_curve {
+    ($feature:literal, $affine:ty, $gpu:ident, $host:ident) => {
+        #[cfg(feature = $feature)]
+        impl MsmCurve for $affine {
+            unsafe fn mult_pippenger(
+                out: *mut Self::Projective,
+                points_with_infinity: *const Self,
+                npoints: usize,
+                scalars: *const <Self::ScalarField as PrimeField>::BigInt,
+                ffi_affine_sz: usize,
+                device_id: i32,
+            ) -> sppark::Error {
+                #[cfg_attr(feature = "quiet", allow(improper_ctypes))]
+                extern "C" {
+                    #[cfg(feature = "cuda")]
+                    fn $gpu(
+                        out: *mut <$affine as AffineCurve>::Projective,
+                        points_with_infinity: *const $affine,
+                        npoints: usize,
+                        scalars: *const <<$affine as AffineCurve>::ScalarField as PrimeField>::BigInt,
+                        ffi_affine_sz: usize,
+                        device_id: i32,
+                    ) -> sppark::Error;
+                    fn $host(
+                        out: *mut <$affine as AffineCurve>::Projective,
+                        points_with_infinity: *const $affine,
+                        npoints: usize,
+                        scalars: *const <<$affine as AffineCurve>::ScalarField as PrimeField>::BigInt,
+                        ffi_affine_sz: usize,
+                        device_id: i32,
+                    ) -> sppark::Error;
+                }
+
+                let mult_pippenger = $host;
+                #[cfg(feature = "cuda")]
+                let mult_pippenger = if cuda_available() {
+                    $gpu
+                } else {
+                    mult_pippenger
+                };
+
+                mult_pippenger(
+                    out,
+                    points_with_infinity,
+                    npoints,
+                    scalars,
+                    ffi_affine_sz,
+                    device_id,
+                )
+            }
+        }
+    };
+}
+
+// Types are spelled out, because coherence can't tell G1Affine aliases,
+// i.e. GroupAffine<P::G1Parameters>, of different curves apart.
+msm_curve!(
+    "bn254",
+    GroupAffine<ark_bn254::g1::Parameters>,
+    mult_pippenger_inf_bn254,
+    host_mult_pippenger_inf_bn254
+);
+msm_curve!(
+    "bls12_377",
+    GroupAffine<ark_bls12_377::g1::Parameters>,
+    mult_pippenger_inf_bls12_377,
+    host_mult_pippenger_inf_bls12_377
+);
+msm_curve!(
+    "bls12_377",
+    GroupAffine<ark_bls12_377::g2::Parameters>,
+    mult_pippenger_fp2_inf_bls12_377,
+    host_mult_pippenger_fp2_inf_bls12_377
+);
+msm_curve!(
+    "bls12_381",
+    GroupAffine<ark_bls12_381::g1::Parameters>,
+    mult_pippenger_inf_bls12_381,
+    host_mult_pippenger_inf_bls12_381
+);
+msm_curve!(
+    "bls12_381",
+    GroupAffine<ark_bls12_381::g2::Parameters>,
+    mult_pippenger_fp2_inf_bls12_381,
+    host_mult_pippenger_fp2_inf_bls12_381
+);
+
+pub fn try_multi_scalar_mult_arkworks<G: MsmCurve>(
+    device: Device,
     points: &[G],
     scalars: &[<G::ScalarField as PrimeField>::BigInt],
-) -> G::Projective {
-    #[cfg_attr(feature = "quiet", allow(improper_ctypes))]
-    extern "C" {
-        fn mult_pippenger_inf(
-            out: *mut G1Projective,
-            points_with_infinity: *const G1Affine,
-            npoints: usize,
-            scalars: *const Fr,
-            ffi_affine_sz: usize,
-        ) -> sppark::Error;
-    }
-
+) -> Result<G::Projective, sppark::Error> {
+    check_device(device)?;
     let npoints = points.len();
     if npoints != scalars.len() {
-        panic!("length mismatch")
+        return Err(sppark::Error::length_mismatch(npoints, scalars.len()));
     }
 
     let mut ret = G::Projective::zero();
+    if npoints == 0 {
+        return Ok(ret);
+    }
+
     let err = unsafe {
-        mult_pippenger_inf(
-            &mut ret as *mut _ as *mut _,
-            points.as_ptr() as *const _,
+        G::mult_pippenger(
+            &mut ret,
+            points.as_ptr(),
             npoints,
-            scalars.as_ptr() as *const _,
+            scalars.as_ptr(),
             std::mem::size_of::<G>(),
+            device.as_raw(),
         )
     };
     if err.code != 0 {
-        panic!("{}", String::from(err));
+        return Err(err.with_op("mult_pippenger"));
     }
 
-    ret
+    Ok(ret)
 }
 
-#[cfg(any(feature = "bls12_381", feature = "bls12_377"))]
-pub fn multi_scalar_mult_fp2_arkworks<G: AffineCurve>(
+pub fn multi_scalar_mult_arkworks<G: MsmCurve>(
+    device: Device,
     points: &[G],
     scalars: &[<G::ScalarField as PrimeField>::BigInt],
 ) -> G::Projective {
-    #[cfg_attr(feature = "quiet", allow(improper_ctypes))]
-    extern "C" {
-        fn mult_pippenger_fp2_inf(
-            out: *mut G2Projective,
-            points_with_infinity: *const G2Affine,
-            npoints: usize,
-            scalars: *const Fr,
-            ffi_affine_sz: usize,
-        ) -> sppark::Error;
-    }
-
-    let npoints = points.len();
-    if npoints != scalars.len() {
-        panic!("length mismatch")
-    }
+    try_multi_scalar_mult_arkworks(device, points, scalars)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
 
-    let mut ret = G::Projective::zero();
-    let err = unsafe {
-        mult_pippenger_fp2_inf(
-            &mut ret as *mut _ as *mut _,
-            points.as_ptr() as *const _,
-            npoints,
-            scalars.as_ptr() as *const _,
-            std::mem::size_of::<G>(),
-        )
-    };
-    if err.code != 0 {
-        panic!("{}", String::from(err));
-    }
+/// Same as [`try_multi_scalar_mult_arkworks`], which handles G2 too.
+#[cfg(any(feature = "bls12_381", feature = "bls12_377"))]
+pub fn try_multi_scalar_mult_fp2_arkworks<G: MsmCurve>(
+    device: Device,
+    points: &[G],
+    scalars: &[<G::ScalarField as PrimeField>::BigInt],
+) -> Result<G::Projective, sppark::Error> {
+    try_multi_scalar_mult_arkworks(device, points, scalars)
+}
 
-    ret
+#[cfg(any(feature = "bls12_381", feature = "bls12_377"))]
+pub fn multi_scalar_mult_fp2_arkworks<G: MsmCurve>(
+    device: Device,
+    points: &[G],
+    scalars: &[<G::ScalarField as PrimeField>::BigInt],
+) -> G::Projective {
+    try_multi_scalar_mult_fp2_arkworks(device, points, scalars)
+        .unwrap_or_else(|e| panic!("{}", e))
 }
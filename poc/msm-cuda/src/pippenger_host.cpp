@@ -0,0 +1,121 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+// Host counterpart of cuda/pippenger_inf.cu, used when there is no CUDA
+// compiler or no suitable device.
+
+// Like the CUDA side, every enabled curve is compiled separately, with
+// MSM_CURVE set to the name of the cargo feature, into a namespace of its
+// own. Field-independent headers are included upfront to keep them out of
+// it.
+#ifndef MSM_CURVE
+# error "no MSM_CURVE"
+#endif
+#define MSM_PASTE_(a, b) a##_##b
+#define MSM_PASTE(a, b) MSM_PASTE_(a, b)
+
+#include <cerrno>
+#include <cstddef>
+#include <cstdint>
+#include <cstring>
+#include <new>
+#include <vector>
+#include <memory>
+#include <tuple>
+#include <blst_t.hpp>
+#include <util/thread_pool_t.hpp>
+#include <util/slice_t.hpp>
+#include <util/rusterror.h>
+
+namespace MSM_PASTE(msm, MSM_CURVE) {
+
+#if defined(FEATURE_BLS12_381)
+# include <ff/bls12-381-fp2.hpp>
+#elif defined(FEATURE_BLS12_377)
+# include <ff/bls12-377-fp2.hpp>
+#elif defined(FEATURE_BN254)
+# include <ff/alt_bn128.hpp>
+#else
+# error "no FEATURE"
+#endif
+
+#include <ec/jacobian_t.hpp>
+#include <ec/xyzz_t.hpp>
+#include <msm/pippenger.hpp>
+
+// Points come as [X, Y, bool inf] records |ffi_affine_sz| bytes apart,
+// which is the layout of both affine_inf_t and arkworks' GroupAffine.
+// The host mult_pippenger wants infinity as X = Y = 0, so repack...
+template<class bucket_t, class point_t, class scalar_t>
+static RustError host_mult_pippenger(point_t* out, const void* points_with_inf,
+                                     size_t npoints, const scalar_t scalars[],
+                                     size_t ffi_affine_sz)
+{
+    typedef typename bucket_t::affine_t affine_t;
+    typedef decltype(affine_t::X) field_t;
+
+    try {
+        const char* inp = static_cast<const char*>(points_with_inf);
+        std::vector<affine_t> points(npoints);
+        for (size_t i = 0; i < npoints; i++, inp += ffi_affine_sz) {
+            bool inf;
+            std::memcpy(&inf, inp + 2*sizeof(field_t), sizeof(inf));
+            if (inf)
+                points[i].X = points[i].Y = field_t::one(true); // zero
+            else
+                std::memcpy(&points[i], inp, 2*sizeof(field_t));
+        }
+
+        static thread_pool_t da_pool;
+        mult_pippenger<bucket_t>(*out, points.data(), npoints, scalars, false,
+                                 &da_pool);
+    } catch (const std::bad_alloc&) {
+        return RustError{SPPARK_ERR_OUT_OF_MEMORY,
+                         "host_mult_pippenger: out of memory"};
+    } catch (...) {
+        return RustError{ENOTRECOVERABLE,
+                         "host_mult_pippenger: unexpected exception"};
+    }
+
+    return RustError{0};
+}
+
+typedef jacobian_t<fp_t> point_t;
+typedef xyzz_t<fp_t> bucket_t;
+typedef fr_t scalar_t;
+
+#if defined(FEATURE_BLS12_381) || defined(FEATURE_BLS12_377)
+typedef jacobian_t<fp2_t> point_fp2_t;
+typedef xyzz_t<fp2_t> bucket_fp2_t;
+#endif
+
+}
+
+using namespace MSM_PASTE(msm, MSM_CURVE);
+
+extern "C"
+RustError::by_value MSM_PASTE(host_mult_pippenger_inf, MSM_CURVE)(
+                        point_t* out, const void* points, size_t npoints,
+                        const scalar_t scalars[], size_t ffi_affine_sz,
+                        int device_id)
+{
+    (void)device_id;    // validated on the Rust side, there is no device
+
+    return host_mult_pippenger<bucket_t>(out, points, npoints, scalars,
+                                         ffi_affine_sz);
+}
+
+#if defined(FEATURE_BLS12_381) || defined(FEATURE_BLS12_377)
+extern "C"
+RustError::by_value MSM_PASTE(host_mult_pippenger_fp2_inf, MSM_CURVE)(
+                        point_fp2_t* out, const void* points, size_t npoints,
+                        const scalar_t scalars[], size_t ffi_affine_sz,
+                        int device_id)
+{
+    (void)device_id;
+
+    return host_mult_pippenger<bucket_fp2_t>(out, points, npoints, scalars,
+                                             ffi_affine_sz);
+}
+#endif
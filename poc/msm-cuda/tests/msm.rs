@@ -2,62 +2,139 @@
 // Licensed under the Apache License, Version 2.0, see LICENSE for details.
 // SPDX-License-Identifier: Apache-2.0
 
-#[cfg(feature = "bls12_377")]
-use ark_bls12_377::{G1Affine, G2Affine};
-#[cfg(feature = "bls12_381")]
-use ark_bls12_381::{G1Affine, G2Affine};
-#[cfg(feature = "bn254")]
-use ark_bn254::G1Affine;
 use ark_ec::msm::VariableBaseMSM;
 use ark_ec::ProjectiveCurve;
-use ark_ff::BigInteger256;
+use ark_ff::PrimeField;
 
 use std::str::FromStr;
 
 use msm_cuda::*;
 
-#[test]
-fn msm_correctness() {
-    let test_npow = std::env::var("TEST_NPOW").unwrap_or("15".to_string());
+fn check_correctness<G: MsmCurve>(default_npow: &str) {
+    let test_npow =
+        std::env::var("TEST_NPOW").unwrap_or(default_npow.to_string());
     let npoints_npow = i32::from_str(&test_npow).unwrap();
 
     let (points, scalars) =
-        util::generate_points_scalars::<G1Affine>(1usize << npoints_npow);
+        util::generate_points_scalars::<G>(1usize << npoints_npow);
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
 
-    let msm_result = multi_scalar_mult_arkworks(points.as_slice(), unsafe {
-        std::mem::transmute::<&[_], &[BigInteger256]>(scalars.as_slice())
-    })
+    let msm_result = multi_scalar_mult_arkworks(
+        Device::default(),
+        points.as_slice(),
+        scalars.as_slice(),
+    )
     .into_affine();
 
-    let arkworks_result =
-        VariableBaseMSM::multi_scalar_mul(points.as_slice(), unsafe {
-            std::mem::transmute::<&[_], &[BigInteger256]>(scalars.as_slice())
-        })
-        .into_affine();
+    let arkworks_result = VariableBaseMSM::multi_scalar_mul(
+        points.as_slice(),
+        scalars.as_slice(),
+    )
+    .into_affine();
 
     assert_eq!(msm_result, arkworks_result);
 }
 
+#[test]
+fn msm_correctness() {
+    #[cfg(feature = "bn254")]
+    check_correctness::<ark_bn254::G1Affine>("15");
+    #[cfg(feature = "bls12_377")]
+    check_correctness::<ark_bls12_377::G1Affine>("15");
+    #[cfg(feature = "bls12_381")]
+    check_correctness::<ark_bls12_381::G1Affine>("15");
+}
+
 #[cfg(any(feature = "bls12_381", feature = "bls12_377"))]
 #[test]
 fn msm_fp2_correctness() {
-    let test_npow = std::env::var("TEST_NPOW").unwrap_or("14".to_string());
-    let npoints_npow = i32::from_str(&test_npow).unwrap();
+    #[cfg(feature = "bls12_377")]
+    check_correctness::<ark_bls12_377::G2Affine>("14");
+    #[cfg(feature = "bls12_381")]
+    check_correctness::<ark_bls12_381::G2Affine>("14");
+}
 
-    let (points, scalars) =
-        util::generate_points_scalars::<G2Affine>(1usize << npoints_npow);
+fn check_length_mismatch<G: MsmCurve>() {
+    let (points, scalars) = util::generate_points_scalars::<G>(4);
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
 
-    let msm_result =
-        multi_scalar_mult_fp2_arkworks(points.as_slice(), unsafe {
-            std::mem::transmute::<&[_], &[BigInteger256]>(scalars.as_slice())
-        })
-        .into_affine();
+    let err = try_multi_scalar_mult_arkworks(
+        Device::default(),
+        &points[..3],
+        &scalars,
+    )
+    .unwrap_err();
+
+    assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+    assert!(err.is_invalid_argument());
+}
 
-    let arkworks_result =
-        VariableBaseMSM::multi_scalar_mul(points.as_slice(), unsafe {
-            std::mem::transmute::<&[_], &[BigInteger256]>(scalars.as_slice())
+#[test]
+fn msm_length_mismatch() {
+    #[cfg(feature = "bn254")]
+    check_length_mismatch::<ark_bn254::G1Affine>();
+    #[cfg(feature = "bls12_377")]
+    check_length_mismatch::<ark_bls12_377::G1Affine>();
+    #[cfg(feature = "bls12_381")]
+    check_length_mismatch::<ark_bls12_381::G1Affine>();
+}
+
+fn check_invalid_device<G: MsmCurve>() {
+    use sppark::device::{with_host_table, DeviceInfo};
+
+    // a handle obtained on a machine with more devices than this one
+    let table: Vec<DeviceInfo> = (0..64)
+        .map(|id| DeviceInfo {
+            id,
+            cuda_id: id as i32,
+            name: format!("device #{}", id),
+            sm_count: 1,
+            total_mem: 0,
+            compute_capability: (8, 0),
         })
-        .into_affine();
+        .collect();
+    let device = with_host_table(&table, || Device::new(63).unwrap());
 
-    assert_eq!(msm_result, arkworks_result);
+    let (points, scalars) = util::generate_points_scalars::<G>(4);
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+
+    let err =
+        try_multi_scalar_mult_arkworks(device, &points, &scalars).unwrap_err();
+
+    assert_eq!(err.kind(), sppark::ErrorKind::InvalidDevice);
+    assert!(!err.is_retryable());
+}
+
+#[test]
+fn msm_invalid_device() {
+    #[cfg(feature = "bn254")]
+    check_invalid_device::<ark_bn254::G1Affine>();
+    #[cfg(feature = "bls12_377")]
+    check_invalid_device::<ark_bls12_377::G1Affine>();
+    #[cfg(feature = "bls12_381")]
+    check_invalid_device::<ark_bls12_381::G1Affine>();
+}
+
+// Both curves linked into one binary, each dispatching to its own symbols.
+#[cfg(all(feature = "bn254", feature = "bls12_381"))]
+#[test]
+fn msm_multiple_curves() {
+    fn msm<G: MsmCurve>(npoints: usize) -> (G, G) {
+        let (points, scalars) = util::generate_points_scalars::<G>(npoints);
+        let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+        (
+            multi_scalar_mult_arkworks(Device::default(), &points, &scalars)
+                .into_affine(),
+            VariableBaseMSM::multi_scalar_mul(&points, &scalars).into_affine(),
+        )
+    }
+
+    for npoints in [1, 7, 1 << 10] {
+        let (bn254, expected) = msm::<ark_bn254::G1Affine>(npoints);
+        assert_eq!(bn254, expected);
+        let (bls12_381, expected) = msm::<ark_bls12_381::G1Affine>(npoints);
+        assert_eq!(bls12_381, expected);
+        let (bls12_381, expected) = msm::<ark_bls12_381::G2Affine>(npoints);
+        assert_eq!(bls12_381, expected);
+    }
 }
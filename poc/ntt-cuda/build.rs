@@ -2,79 +2,48 @@
 // Licensed under the Apache License, Version 2.0, see LICENSE for details.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::env;
-
-fn feature_check() {
-    let fr_s = [
-        "bls12_377",
-        "bls12_381",
-        "pallas",
-        "vesta",
-        "bn254",
-        "gl64",
-        "bb31",
-    ];
-    let fr_s_as_features: Vec<String> = (0..fr_s.len())
-        .map(|i| format!("CARGO_FEATURE_{}", fr_s[i].to_uppercase()))
-        .collect();
-
-    let mut fr_counter = 0;
-    for fr_feature in fr_s_as_features.iter() {
-        fr_counter += env::var(&fr_feature).is_ok() as i32;
-    }
-
-    match fr_counter {
-        0 => panic!("Can't run without a field being specified,\nplease select one with --features=<field>. Available options are\n{:#?}\n", fr_s),
-        2.. => panic!("Multiple fields are not supported, please select only one."),
-        _ => (),
-    };
-}
+use sppark_build::{Build, Field};
 
 fn main() {
     if cfg!(target_os = "windows") && !cfg!(target_env = "msvc") {
         panic!("unsupported compiler");
     }
 
-    feature_check();
-
-    let mut fr = "";
-    if cfg!(feature = "bls12_377") {
-        fr = "FEATURE_BLS12_377";
-    } else if cfg!(feature = "bls12_381") {
-        fr = "FEATURE_BLS12_381";
-    } else if cfg!(feature = "pallas") {
-        fr = "FEATURE_PALLAS";
-    } else if cfg!(feature = "vesta") {
-        fr = "FEATURE_VESTA";
-    } else if cfg!(feature = "bn254") {
-        fr = "FEATURE_BN254"
-    } else if cfg!(feature = "gl64") {
-        fr = "FEATURE_GOLDILOCKS";
-    } else if cfg!(feature = "bb31") {
-        fr = "FEATURE_BABY_BEAR";
+    let fields = Field::all_from_features(&Field::ALL);
+
+    // The domain size limit is fixed here rather than left to the defaults
+    // in ntt/parameters.cuh, so that the crate can tell the library's limit
+    // with or without CUDA, see NttField::MAX_LG_DOMAIN_SIZE.
+    for &fr in &fields {
+        println!(
+            "cargo:rustc-env=MAX_LG_DOMAIN_SIZE_{}={}",
+            fr.feature(),
+            max_lg_domain_size(fr)
+        );
     }
 
-    let mut nvcc = cc::Build::new();
-    nvcc.cuda(true);
-    nvcc.flag("-arch=sm_70");
-    #[cfg(not(target_env = "msvc"))]
-    nvcc.flag("-Xcompiler").flag("-Wno-unused-function");
-    nvcc.define("TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE", None);
-    nvcc.define(fr, None);
-    if cfg!(feature = "gl64") {
-        nvcc.define("GL64_NO_REDUCTION_KLUDGE", None);
+    // Detect if there is CUDA compiler and engage "cuda" feature accordingly,
+    // otherwise fall back to the host implementation.
+    if sppark_build::nvcc().is_some() {
+        // One library per field, each exporting compute_ntt_<feature>.
+        for fr in fields {
+            let mut nvcc = Build::new();
+            nvcc.field(fr)
+                .max_lg_domain_size(max_lg_domain_size(fr))
+                .define("NTT_FIELD", Some(fr.feature()));
+            if fr == Field::Goldilocks {
+                nvcc.define("GL64_NO_REDUCTION_KLUDGE", None);
+            }
+            nvcc.file("cuda/ntt_api.cu")
+                .compile(&format!("ntt_cuda_{}", fr.feature()));
+        }
     }
-    if let Some(include) = env::var_os("DEP_BLST_C_SRC") {
-        nvcc.include(include);
-    }
-    if let Some(include) = env::var_os("DEP_SEMOLINA_C_INCLUDE") {
-        nvcc.include(include);
-    }
-    if let Some(include) = env::var_os("DEP_SPPARK_ROOT") {
-        nvcc.include(include);
-    }
-    nvcc.file("cuda/ntt_api.cu").compile("ntt_cuda");
+}
 
-    println!("cargo:rerun-if-changed=cuda");
-    println!("cargo:rerun-if-env-changed=CXXFLAGS");
+// Same as the ntt/parameters.cuh defaults, BabyBear's two-adicity is 27.
+fn max_lg_domain_size(fr: Field) -> u32 {
+    match fr {
+        Field::BabyBear => 27,
+        _ => 28,
+    }
 }
@@ -4,6 +4,34 @@
 
 #include <cuda.h>
 
+// Every enabled field is compiled separately, with NTT_FIELD set to the
+// name of the cargo feature, into a namespace of its own, so that kernels
+// and twiddle tables of different fields don't collide at link time.
+// Field-independent headers are included upfront to keep them out of it.
+#ifndef NTT_FIELD
+# error "no NTT_FIELD"
+#endif
+#define NTT_PASTE_(a, b) a##_##b
+#define NTT_PASTE(a, b) NTT_PASTE_(a, b)
+
+#include <cstddef>
+#include <cstdint>
+#include <cassert>
+#include <iostream>
+#include <cooperative_groups.h>
+#include <util/exception.cuh>
+#include <util/rusterror.h>
+#include <util/gpu_t.cuh>
+#ifndef __CUDA_ARCH__
+# if defined(FEATURE_PALLAS) || defined(FEATURE_VESTA)
+#  include <pasta_t.hpp>
+# elif !defined(FEATURE_GOLDILOCKS) && !defined(FEATURE_BABY_BEAR)
+#  include <blst_t.hpp>
+# endif
+#endif
+
+namespace NTT_PASTE(ntt, NTT_FIELD) {
+
 #if defined(FEATURE_BLS12_381)
 # include <ff/bls12-381.hpp>
 #elif defined(FEATURE_BLS12_377)
@@ -23,19 +51,310 @@
 #endif
 
 #include <ntt/ntt.cuh>
+#include <ff/batch_inverse.cuh>
+#include <ff/grand_product.cuh>
+#include <sumcheck/sumcheck.cuh>
+
+}
 
 #ifndef __CUDA_ARCH__
 
+using namespace NTT_PASTE(ntt, NTT_FIELD);
+
+extern "C"
+RustError NTT_PASTE(compute_ntt, NTT_FIELD)(int device_id, fr_t* inout,
+                                            uint32_t lg_domain_size,
+                                            NTT::InputOutputOrder ntt_order,
+                                            NTT::Direction ntt_direction,
+                                            NTT::Type ntt_type)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return NTT::Base(gpu, inout, lg_domain_size,
+                         ntt_order, ntt_direction, ntt_type);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(compute_ntt_batch, NTT_FIELD)(int device_id, fr_t* inout,
+                                                  uint32_t lg_domain_size,
+                                                  size_t ncolumns,
+                                                  NTT::Layout layout,
+                                                  NTT::InputOutputOrder ntt_order,
+                                                  NTT::Direction ntt_direction,
+                                                  NTT::Type ntt_type)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return NTT::Batch(gpu, inout, lg_domain_size, ncolumns, layout,
+                          ntt_order, ntt_direction, ntt_type);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(compute_coset_ntt, NTT_FIELD)(int device_id, fr_t* inout,
+                                                  uint32_t lg_domain_size,
+                                                  const fr_t* shift,
+                                                  NTT::InputOutputOrder ntt_order,
+                                                  NTT::Direction ntt_direction)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return NTT::Coset(gpu, inout, lg_domain_size, *shift,
+                          ntt_order, ntt_direction);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(compute_lde, NTT_FIELD)(int device_id, fr_t* inout,
+                                            uint32_t lg_domain_size,
+                                            uint32_t lg_blowup, bool ext_pow)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return NTT::LDE(gpu, inout, lg_domain_size, lg_blowup, ext_pow);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(bit_rev, NTT_FIELD)(int device_id, fr_t* out,
+                                        const fr_t* inp,
+                                        uint32_t lg_domain_size,
+                                        size_t ncolumns)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return NTT::BitRev(gpu, out, inp, lg_domain_size, ncolumns);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(fri_fold, NTT_FIELD)(int device_id, fr_t* out,
+                                         const fr_t* inp,
+                                         uint32_t lg_domain_size,
+                                         uint32_t lg_arity,
+                                         const fr_t* challenge,
+                                         const fr_t* shift)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return NTT::FriFold(gpu, out, inp, lg_domain_size, lg_arity,
+                            *challenge, *shift);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+   }
+}
+#endif
+
+// Invert |len| elements in place, zeros are left intact and counted.
 extern "C"
-RustError compute_ntt(size_t device_id, fr_t* inout, uint32_t lg_domain_size,
-                      NTT::InputOutputOrder ntt_order,
-                      NTT::Direction ntt_direction,
-                      NTT::Type ntt_type)
+RustError NTT_PASTE(batch_inverse, NTT_FIELD)(int device_id, fr_t* inout,
+                                              size_t len, size_t* nzeros)
 {
-    auto& gpu = select_gpu(device_id);
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return batch_inverse(gpu, inout, len, nzeros);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+// Replace |len| factors with their running products, divided by the ones
+// of |den| unless it's null, and return the product of all in |*total|.
+extern "C"
+RustError NTT_PASTE(grand_product, NTT_FIELD)(int device_id, fr_t* inout,
+                                              const fr_t* den, size_t len,
+                                              bool exclusive, fr_t* total)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return grand_product(gpu, inout, den, len, exclusive, total);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+// Multilinear primitives of sumcheck provers, see src/sumcheck.rs.
+extern "C"
+RustError NTT_PASTE(sumcheck_eq_table, NTT_FIELD)(int device_id, fr_t* eq,
+                                                  const fr_t* r,
+                                                  uint32_t nvars)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return Sumcheck::EqTable(gpu, eq, r, nvars);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(sumcheck_fold, NTT_FIELD)(int device_id, fr_t* out,
+                                              const fr_t* inp,
+                                              uint32_t lg_size,
+                                              const fr_t* challenge)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return Sumcheck::Fold(gpu, out, inp, lg_size, *challenge);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+extern "C"
+RustError NTT_PASTE(sumcheck_round_poly, NTT_FIELD)(int device_id,
+                                                    fr_t* evals,
+                                                    const fr_t* tables,
+                                                    uint32_t ntables,
+                                                    uint32_t lg_size)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        return Sumcheck::RoundPoly(gpu, evals, tables, ntables, lg_size);
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+}
+
+// Operations on DeviceVec<T>, see src/lib.rs. The memory stays on the
+// device, all work is complete by the time the calls return.
+extern "C"
+RustError NTT_PASTE(compute_ntt_dev, NTT_FIELD)(int device_id,
+                                                const gpu_ptr_t<fr_t>& inout,
+                                                uint32_t lg_domain_size,
+                                                NTT::InputOutputOrder ntt_order,
+                                                NTT::Direction ntt_direction,
+                                                NTT::Type ntt_type)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        NTT::Base_dev_ptr(gpu, inout, lg_domain_size,
+                          ntt_order, ntt_direction, ntt_type);
+        gpu.sync();
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+
+    return RustError{cudaSuccess};
+}
+
+extern "C"
+RustError NTT_PASTE(compute_lde_dev, NTT_FIELD)(int device_id,
+                                                const gpu_ptr_t<fr_t>& inout,
+                                                uint32_t lg_domain_size,
+                                                uint32_t lg_blowup, bool ext_pow)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        NTT::LDE_dev_ptr(gpu, inout, lg_domain_size, lg_blowup, ext_pow);
+        gpu.sync();
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
+
+    return RustError{cudaSuccess};
+}
+
+extern "C"
+RustError NTT_PASTE(bit_rev_dev, NTT_FIELD)(int device_id,
+                                            const gpu_ptr_t<fr_t>& out,
+                                            const gpu_ptr_t<fr_t>& inp,
+                                            uint32_t lg_domain_size,
+                                            size_t ncolumns)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+
+        NTT::BitRev_dev_ptr(gpu, out, inp, lg_domain_size, ncolumns);
+        gpu.sync();
+    } catch (const cuda_error& e) {
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+        return RustError{e.code(), e.what()};
+#else
+        return RustError{e.code()};
+#endif
+    }
 
-    return NTT::Base(gpu, inout, lg_domain_size,
-                     ntt_order, ntt_direction, ntt_type);
+    return RustError{cudaSuccess};
 }
 
 #endif
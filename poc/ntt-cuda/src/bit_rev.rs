@@ -0,0 +1,149 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bit-reversal permutation without a transform, e.g. for reordering
+//! evaluations before committing to them. The host reference is
+//! [`sppark::ntt::bit_rev_batch`].
+
+use crate::{check_domain_size, cuda, Device, Field, NttField};
+
+// Validate the length of a single-domain buffer, return its lg.
+fn lg_len<F: NttField>(len: usize) -> Result<u32, cuda::Error> {
+    crate::check_len::<F>(len)?;
+    Ok(len.trailing_zeros())
+}
+
+fn bit_rev_internal<T>(
+    device: Device,
+    out: &mut [T],
+    inp: Option<&[T]>,
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_domain_size::<T::Repr>(lg_domain_size)?;
+    let expected = ncolumns.checked_mul(1 << lg_domain_size);
+    if Some(out.len()) != expected {
+        return Err(cuda::Error::length_mismatch(
+            out.len(),
+            expected.unwrap_or(usize::MAX),
+        ));
+    }
+    if let Some(inp) = inp {
+        if inp.len() != out.len() {
+            return Err(cuda::Error::length_mismatch(inp.len(), out.len()));
+        }
+    }
+    if out.is_empty() {
+        return Ok(());
+    }
+
+    T::Repr::bit_rev(
+        device,
+        T::cast_slice_mut(out),
+        inp.map(T::cast_slice),
+        lg_domain_size,
+        ncolumns,
+    )
+}
+
+/// Permute the data in place between natural and bit-reversed order, i.e.
+/// swap inout[i] and inout[bit_reverse(i)].
+pub fn try_bit_reverse<T>(device: Device, inout: &mut [T]) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let lg_domain_size = lg_len::<T::Repr>(inout.len())?;
+    bit_rev_internal(device, inout, None, lg_domain_size, 1)
+}
+
+/// Out-of-place [`try_bit_reverse`], out[bit_reverse(i)] = inp[i].
+pub fn try_bit_reverse_into<T>(device: Device, out: &mut [T], inp: &[T]) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let lg_domain_size = lg_len::<T::Repr>(inp.len())?;
+    bit_rev_internal(device, out, Some(inp), lg_domain_size, 1)
+}
+
+/// Permute |ncolumns| polynomials of 2^|lg_domain_size| elements each, laid
+/// out one after another. Transfers to and from the device are done for as
+/// many polynomials at a time as fit.
+pub fn try_bit_reverse_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    bit_rev_internal(device, inout, None, lg_domain_size, ncolumns)
+}
+
+pub fn try_bit_reverse_batch_into<T>(
+    device: Device,
+    out: &mut [T],
+    inp: &[T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    bit_rev_internal(device, out, Some(inp), lg_domain_size, ncolumns)
+}
+
+/// Permute the data in place, panic on error, see [`try_bit_reverse`].
+pub fn bit_reverse<T>(device: Device, inout: &mut [T])
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse(device, inout) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse_into<T>(device: Device, out: &mut [T], inp: &[T])
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse_into(device, out, inp) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse_batch<T>(device: Device, inout: &mut [T], lg_domain_size: u32, ncolumns: usize)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse_batch(device, inout, lg_domain_size, ncolumns) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse_batch_into<T>(
+    device: Device,
+    out: &mut [T],
+    inp: &[T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse_batch_into(device, out, inp, lg_domain_size, ncolumns) {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Same operations as at the crate level, but in place on a [`DeviceVec`],
+//! so that transforms can be chained without round trips to host memory.
+//! Operations are executed on the device the vector was allocated on.
+//! Transforms are over the compiled root of unity and group generator,
+//! there is no counterpart to [`try_domain_NTT`](super::try_domain_NTT).
+
+use super::*;
+
+fn ntt_internal<T>(
+    inout: &mut DeviceVec<T>,
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    type_: NTTType,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_len::<T::Repr>(inout.len())?;
+    if inout.len() == 1 {
+        return Ok(());
+    }
+
+    T::Repr::compute_ntt_dev(inout, order, direction, type_)
+}
+
+/// Compute an in-place NTT on device-resident data.
+#[allow(non_snake_case)]
+pub fn try_NTT<T>(inout: &mut DeviceVec<T>, order: NTTInputOutputOrder) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(inout, order, NTTDirection::Forward, NTTType::Standard)
+}
+
+/// Compute an in-place iNTT on device-resident data.
+#[allow(non_snake_case)]
+pub fn try_iNTT<T>(inout: &mut DeviceVec<T>, order: NTTInputOutputOrder) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(inout, order, NTTDirection::Inverse, NTTType::Standard)
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_NTT<T>(
+    inout: &mut DeviceVec<T>,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(inout, order, NTTDirection::Forward, NTTType::Coset)
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_iNTT<T>(
+    inout: &mut DeviceVec<T>,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(inout, order, NTTDirection::Inverse, NTTType::Coset)
+}
+
+/// Low-degree extension in place, the input being the first
+/// `inout.len() >> lg_blowup` elements, see [`try_lde`](super::try_lde).
+pub fn try_lde<T>(
+    inout: &mut DeviceVec<T>,
+    lg_blowup: u32,
+    ext_pow: bool,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_lde_len::<T::Repr>(inout.len(), lg_blowup)?;
+
+    T::Repr::compute_lde_dev(inout, lg_blowup, ext_pow)
+}
+
+fn bit_rev_internal<T>(
+    out: &mut DeviceVec<T>,
+    inp: Option<&DeviceVec<T>>,
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_domain_size::<T::Repr>(lg_domain_size)?;
+    let expected = ncolumns.checked_mul(1 << lg_domain_size);
+    if Some(out.len()) != expected {
+        return Err(cuda::Error::length_mismatch(
+            out.len(),
+            expected.unwrap_or(usize::MAX),
+        ));
+    }
+    if let Some(inp) = inp {
+        if inp.len() != out.len() {
+            return Err(cuda::Error::length_mismatch(inp.len(), out.len()));
+        }
+        if inp.device() != out.device() {
+            return Err(cuda::Error::new(
+                cuda::Error::INVALID_DEVICE,
+                &format!("inp is on {:?}, out is on {:?}", inp.device(), out.device()),
+            ));
+        }
+    }
+    if out.is_empty() {
+        return Ok(());
+    }
+
+    T::Repr::bit_rev_dev(out, inp, lg_domain_size, ncolumns)
+}
+
+/// Permute the data between natural and bit-reversed order.
+pub fn try_bit_reverse<T>(inout: &mut DeviceVec<T>) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_len::<T::Repr>(inout.len())?;
+
+    bit_rev_internal(inout, None, inout.len().trailing_zeros(), 1)
+}
+
+/// Out-of-place [`try_bit_reverse`], both vectors are expected to be on
+/// the same device.
+pub fn try_bit_reverse_into<T>(
+    out: &mut DeviceVec<T>,
+    inp: &DeviceVec<T>,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_len::<T::Repr>(inp.len())?;
+
+    bit_rev_internal(out, Some(inp), inp.len().trailing_zeros(), 1)
+}
+
+/// Permute |ncolumns| polynomials of 2^|lg_domain_size| elements each,
+/// see [`try_bit_reverse_batch`](super::try_bit_reverse_batch).
+pub fn try_bit_reverse_batch<T>(
+    inout: &mut DeviceVec<T>,
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    bit_rev_internal(inout, None, lg_domain_size, ncolumns)
+}
+
+pub fn try_bit_reverse_batch_into<T>(
+    out: &mut DeviceVec<T>,
+    inp: &DeviceVec<T>,
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    bit_rev_internal(out, Some(inp), lg_domain_size, ncolumns)
+}
+
+/// Compute an in-place NTT on device-resident data, panic on error.
+#[allow(non_snake_case)]
+pub fn NTT<T>(inout: &mut DeviceVec<T>, order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_NTT(inout, order) {
+        panic!("{}", e);
+    }
+}
+
+/// Compute an in-place iNTT on device-resident data, panic on error.
+#[allow(non_snake_case)]
+pub fn iNTT<T>(inout: &mut DeviceVec<T>, order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_iNTT(inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn coset_NTT<T>(inout: &mut DeviceVec<T>, order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_NTT(inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn coset_iNTT<T>(inout: &mut DeviceVec<T>, order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_iNTT(inout, order) {
+        panic!("{}", e);
+    }
+}
+
+pub fn lde<T>(inout: &mut DeviceVec<T>, lg_blowup: u32, ext_pow: bool)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_lde(inout, lg_blowup, ext_pow) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse<T>(inout: &mut DeviceVec<T>)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse(inout) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse_into<T>(out: &mut DeviceVec<T>, inp: &DeviceVec<T>)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse_into(out, inp) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse_batch<T>(inout: &mut DeviceVec<T>, lg_domain_size: u32, ncolumns: usize)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse_batch(inout, lg_domain_size, ncolumns) {
+        panic!("{}", e);
+    }
+}
+
+pub fn bit_reverse_batch_into<T>(
+    out: &mut DeviceVec<T>,
+    inp: &DeviceVec<T>,
+    lg_domain_size: u32,
+    ncolumns: usize,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_bit_reverse_batch_into(out, inp, lg_domain_size, ncolumns) {
+        panic!("{}", e);
+    }
+}
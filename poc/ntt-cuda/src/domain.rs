@@ -0,0 +1,459 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transforms over a [`Domain`] with caller-chosen root of unity and coset
+//! shift, e.g. to match halo2's or arkworks' conventions, as opposed to the
+//! ones compiled into ntt/parameters/*.h. A primitive root of unity ω' of
+//! the domain's order is a power ω^k of the compiled one, k being odd, so
+//! that evaluations at ω'^j are evaluations at ω^(j·k). Hence the transforms
+//! are executed by the same kernels, and evaluations are permuted on the
+//! host if ω' != ω. Shifts other than one and the field's group generator
+//! are handled as in [`try_coset_NTT_with_shift`](crate::try_coset_NTT_with_shift).
+//!
+//! The permutation takes a copy of the data on the host, and batches with
+//! such shifts are transformed one polynomial at a time. Only host slices
+//! are covered. The operations on a [`DeviceVec`](crate::DeviceVec) in
+//! [`dev`](crate::dev), the four-step transforms, the ones of extension
+//! field elements and FRI folding are over the compiled root of unity and
+//! group generator, same as the rest of the crate.
+
+use crate::{
+    check_domain_size, check_len, cuda, Device, Domain, Field, NTTDirection, NTTInputOutputOrder,
+    NTTLayout, NTTType, NttField,
+};
+use sppark::ff::HostField;
+use sppark::ntt::NTTParameters;
+
+fn check_domain_len(len: usize, domain_size: usize) -> Result<(), cuda::Error> {
+    if len != domain_size {
+        return Err(cuda::Error::length_mismatch(len, domain_size));
+    }
+    Ok(())
+}
+
+// Replace element j of each polynomial with element index(j), polynomials
+// being laid out as specified by |layout|.
+fn gather<F, I>(data: &mut [F], lg_domain_size: u32, layout: NTTLayout, index: I)
+where
+    F: Copy + Send + Sync,
+    I: Fn(usize) -> usize + Sync,
+{
+    let mask = (1usize << lg_domain_size) - 1;
+    let ncolumns = data.len() >> lg_domain_size;
+    let source = |i: usize| match layout {
+        NTTLayout::ColumnMajor => (i & !mask) | index(i & mask),
+        NTTLayout::RowMajor => index(i / ncolumns) * ncolumns + i % ncolumns,
+    };
+
+    let inp = data.to_vec();
+    let nthreads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = data.len().div_ceil(nthreads).max(1 << 12);
+    std::thread::scope(|s| {
+        for (t, out) in data.chunks_mut(chunk).enumerate() {
+            let (inp, source) = (&inp, &source);
+            s.spawn(move || {
+                for (i, x) in out.iter_mut().enumerate() {
+                    *x = inp[source(t * chunk + i)];
+                }
+            });
+        }
+    });
+}
+
+fn bit_rev<F: Copy + Send + Sync>(data: &mut [F], lg_domain_size: u32, layout: NTTLayout) {
+    if lg_domain_size > 1 {
+        let shift = usize::BITS - lg_domain_size;
+        gather(data, lg_domain_size, layout, |j| j.reverse_bits() >> shift);
+    }
+}
+
+// Move evaluation j·k to position j, i.e. from the compiled root's order to
+// the domain's, or back if |k| is the inverse modulo the domain size.
+fn permute<F: Copy + Send + Sync>(
+    data: &mut [F],
+    lg_domain_size: u32,
+    layout: NTTLayout,
+    k: usize,
+) {
+    let mask = (1usize << lg_domain_size) - 1;
+    gather(data, lg_domain_size, layout, |j| j.wrapping_mul(k) & mask);
+}
+
+// Inverse of odd |k| modulo 2^usize::BITS, hence modulo any domain size.
+fn inverse_mod_2n(k: usize) -> usize {
+    // Newton's iteration, each step doubles the number of correct bits
+    let mut inv = k;
+    for _ in 0..6 {
+        inv = inv.wrapping_mul(2usize.wrapping_sub(k.wrapping_mul(inv)));
+    }
+    inv
+}
+
+// Transform with the compiled root of unity, either all polynomials in
+// |data| with NTT::Batch, or the single one with NTT::Base or NTT::Coset.
+type Transform<'a, F> = dyn Fn(&mut [F], NTTInputOutputOrder) -> Result<(), cuda::Error> + 'a;
+
+// Wrap |transform| with the permutations that turn it into one with the
+// domain's root, the order being interpreted as by the transform itself.
+// Notably NTT::Base leaves evaluations in natural order for RR, same as
+// for NN, see sppark::ntt.
+fn permuted<F: Copy + Send + Sync>(
+    data: &mut [F],
+    lg_domain_size: u32,
+    layout: NTTLayout,
+    k: usize,
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    transform: &Transform<F>,
+) -> Result<(), cuda::Error> {
+    if k == 1 {
+        return transform(data, order);
+    }
+
+    match direction {
+        NTTDirection::Forward => {
+            let reversed = order == NTTInputOutputOrder::NR;
+            let order = if reversed {
+                NTTInputOutputOrder::NN
+            } else {
+                order
+            };
+            transform(data, order)?;
+            permute(data, lg_domain_size, layout, k);
+            if reversed {
+                bit_rev(data, lg_domain_size, layout);
+            }
+        }
+        NTTDirection::Inverse => {
+            let reversed = order == NTTInputOutputOrder::RN;
+            if reversed {
+                bit_rev(data, lg_domain_size, layout);
+            }
+            permute(data, lg_domain_size, layout, inverse_mod_2n(k));
+            let order = if reversed {
+                NTTInputOutputOrder::NN
+            } else {
+                order
+            };
+            transform(data, order)?;
+        }
+    }
+    Ok(())
+}
+
+fn domain_ntt_internal<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_len::<T::Repr>(inout.len())?;
+    check_domain_len(inout.len(), domain.size())?;
+
+    let shift = domain.shift();
+    let transform = |data: &mut [T::Repr], order| {
+        if shift == T::Repr::ONE {
+            T::Repr::compute_ntt(device, data, order, direction, NTTType::Standard)
+        } else {
+            T::Repr::compute_coset_ntt(device, data, shift, order, direction)
+        }
+    };
+
+    permuted(
+        T::cast_slice_mut(inout),
+        domain.lg_size(),
+        NTTLayout::ColumnMajor,
+        domain.root_exponent(),
+        order,
+        direction,
+        &transform,
+    )
+}
+
+/// Compute an in-place NTT, i.e. evaluate the polynomial over |domain|.
+#[allow(non_snake_case)]
+pub fn try_domain_NTT<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    domain_ntt_internal(device, inout, domain, order, NTTDirection::Forward)
+}
+
+/// Compute an in-place iNTT, i.e. interpolate the evaluations over |domain|.
+#[allow(non_snake_case)]
+pub fn try_domain_iNTT<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    domain_ntt_internal(device, inout, domain, order, NTTDirection::Inverse)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn domain_ntt_batch_internal<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let lg_domain_size = domain.lg_size();
+    check_domain_size::<T::Repr>(lg_domain_size)?;
+    let expected = ncolumns.checked_mul(domain.size());
+    if Some(inout.len()) != expected {
+        return Err(cuda::Error::length_mismatch(
+            inout.len(),
+            expected.unwrap_or(usize::MAX),
+        ));
+    }
+    if inout.is_empty() {
+        return Ok(());
+    }
+
+    let shift = domain.shift();
+    let type_ = if shift == T::Repr::ONE {
+        NTTType::Standard
+    } else if shift == T::Repr::GROUP_GEN {
+        NTTType::Coset
+    } else {
+        // NTT::Batch is limited to the group generator, fall back to
+        // transforming polynomials one by one
+        let inout = T::cast_slice_mut(inout);
+        let columns = |data: &mut [T::Repr]| {
+            data.chunks_mut(domain.size()).try_for_each(|column| {
+                domain_ntt_internal(device, column, domain, order, direction)
+            })
+        };
+        return match layout {
+            NTTLayout::ColumnMajor => columns(inout),
+            NTTLayout::RowMajor => {
+                let mut data = transpose(inout, ncolumns);
+                columns(&mut data)?;
+                inout.copy_from_slice(&transpose(&data, domain.size()));
+                Ok(())
+            }
+        };
+    };
+
+    let transform = |data: &mut [T::Repr], order| {
+        T::Repr::compute_ntt_batch(
+            device,
+            data,
+            lg_domain_size,
+            ncolumns,
+            layout,
+            order,
+            direction,
+            type_,
+        )
+    };
+
+    permuted(
+        T::cast_slice_mut(inout),
+        lg_domain_size,
+        layout,
+        domain.root_exponent(),
+        order,
+        direction,
+        &transform,
+    )
+}
+
+// Transpose of |inp|, a row-major matrix of |ncols| columns.
+fn transpose<F: Copy>(inp: &[F], ncols: usize) -> Vec<F> {
+    let nrows = inp.len() / ncols;
+    (0..inp.len())
+        .map(|i| inp[(i % nrows) * ncols + i / nrows])
+        .collect()
+}
+
+/// Compute in-place NTTs of |ncolumns| polynomials over |domain|, laid out
+/// as specified by |layout|, see [`try_NTT_batch`](crate::try_NTT_batch).
+/// Shifts other than one and the field's group generator are applied one
+/// polynomial at a time.
+#[allow(non_snake_case)]
+pub fn try_domain_NTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    domain_ntt_batch_internal(
+        device,
+        inout,
+        domain,
+        ncolumns,
+        layout,
+        order,
+        NTTDirection::Forward,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_domain_iNTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    domain_ntt_batch_internal(
+        device,
+        inout,
+        domain,
+        ncolumns,
+        layout,
+        order,
+        NTTDirection::Inverse,
+    )
+}
+
+/// Low-degree extension in place. The first `inout.len() >> lg_blowup`
+/// elements are taken for evaluations of a polynomial over the subgroup
+/// generated by `domain.root()^(2^lg_blowup)`, and are replaced with
+/// evaluations over |domain|, of `inout.len()` elements. Both are in
+/// natural order. Domains that [`try_lde_in_place`](crate::try_lde_in_place)
+/// covers are handed over to it.
+pub fn try_domain_lde<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    lg_blowup: u32,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    crate::check_lde_len::<T::Repr>(inout.len(), lg_blowup)?;
+    check_domain_len(inout.len(), domain.size())?;
+
+    let shift = domain.shift();
+    if domain.root_exponent() == 1 {
+        let gen = T::Repr::GROUP_GEN;
+        if shift == gen {
+            return crate::try_lde_in_place(device, inout, lg_blowup, false);
+        }
+        if shift == gen.pow(1 << lg_blowup) {
+            return crate::try_lde_in_place(device, inout, lg_blowup, true);
+        }
+    }
+
+    let lg_domain_size = domain.lg_size() - lg_blowup;
+    let root = (0..lg_blowup).fold(domain.root(), |acc, _| acc.sqr());
+    let subgroup = Domain::new(lg_domain_size, root, T::Repr::ONE)?;
+
+    let (evals, rest) = inout.split_at_mut(1 << lg_domain_size);
+    try_domain_iNTT(device, evals, &subgroup, NTTInputOutputOrder::NN)?;
+    T::cast_slice_mut(rest).fill(T::Repr::ZERO);
+    try_domain_NTT(device, inout, domain, NTTInputOutputOrder::NN)
+}
+
+/// Compute an in-place NTT over |domain|, panic on error, see
+/// [`try_domain_NTT`].
+#[allow(non_snake_case)]
+pub fn domain_NTT<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_domain_NTT(device, inout, domain, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn domain_iNTT<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_domain_iNTT(device, inout, domain, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn domain_NTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_domain_NTT_batch(device, inout, domain, ncolumns, layout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn domain_iNTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_domain_iNTT_batch(device, inout, domain, ncolumns, layout, order) {
+        panic!("{}", e);
+    }
+}
+
+pub fn domain_lde<T>(device: Device, inout: &mut [T], domain: &Domain<T::Repr>, lg_blowup: u32)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_domain_lde(device, inout, domain, lg_blowup) {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transforms of vectors over an [`ExtensionField`] of an [`NttField`],
+//! e.g. FRI layers and DEEP composition over the Goldilocks and BabyBear
+//! extensions in [`fields`](crate::fields). The twiddles being in the base
+//! field, a transform of extension elements is the same transform of each
+//! of their coefficients. Hence vectors are handed to NTT::Batch as
+//! row-major matrices of DEGREE base-field columns. Transforms are over
+//! the base field's compiled root of unity and group generator, a
+//! [`Domain`](crate::Domain) isn't accepted.
+
+use crate::{
+    check_domain_size, check_len, cuda, ntt_batch_internal, Device, NTTDirection,
+    NTTInputOutputOrder, NTTLayout, NTTType, NttField,
+};
+use sppark::ff::{ExtensionField, HostField};
+use sppark::ntt::NTTParameters;
+
+fn ext_ntt_internal<E>(
+    device: Device,
+    inout: &mut [E],
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    type_: NTTType,
+) -> Result<(), cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    check_len::<E::Base>(inout.len())?;
+    let lg_domain_size = inout.len().trailing_zeros();
+
+    ntt_batch_internal(
+        device,
+        E::as_base_slice_mut(inout),
+        lg_domain_size,
+        E::DEGREE,
+        NTTLayout::RowMajor,
+        order,
+        direction,
+        type_,
+    )
+}
+
+/// Compute an in-place NTT of extension-field elements, see [`try_NTT`](crate::try_NTT).
+#[allow(non_snake_case)]
+pub fn try_ext_NTT<E>(
+    device: Device,
+    inout: &mut [E],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    ext_ntt_internal(
+        device,
+        inout,
+        order,
+        NTTDirection::Forward,
+        NTTType::Standard,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_ext_iNTT<E>(
+    device: Device,
+    inout: &mut [E],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    ext_ntt_internal(
+        device,
+        inout,
+        order,
+        NTTDirection::Inverse,
+        NTTType::Standard,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_ext_coset_NTT<E>(
+    device: Device,
+    inout: &mut [E],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    ext_ntt_internal(device, inout, order, NTTDirection::Forward, NTTType::Coset)
+}
+
+#[allow(non_snake_case)]
+pub fn try_ext_coset_iNTT<E>(
+    device: Device,
+    inout: &mut [E],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    ext_ntt_internal(device, inout, order, NTTDirection::Inverse, NTTType::Coset)
+}
+
+/// Low-degree extension of extension-field elements, see
+/// [`try_lde`](crate::try_lde). The coset is shifted by the base field's
+/// group generator g, or by g^(2^|lg_blowup|) if |ext_pow| is set.
+pub fn try_ext_lde<E>(
+    device: Device,
+    input: &[E],
+    lg_blowup: u32,
+    ext_pow: bool,
+) -> Result<Vec<E>, cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    let len = input.len();
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    // before allocating the extended domain
+    check_domain_size::<E::Base>(len.trailing_zeros().saturating_add(lg_blowup))?;
+
+    let mut ret = vec![E::ZERO; len << lg_blowup];
+    let coeffs = &mut ret[..len];
+    coeffs.copy_from_slice(input);
+    ext_ntt_internal(
+        device,
+        coeffs,
+        NTTInputOutputOrder::NN,
+        NTTDirection::Inverse,
+        NTTType::Standard,
+    )?;
+
+    if !ext_pow {
+        ext_ntt_internal(
+            device,
+            &mut ret,
+            NTTInputOutputOrder::NN,
+            NTTDirection::Forward,
+            NTTType::Coset,
+        )?;
+        return Ok(ret);
+    }
+
+    // NTTType::Coset would multiply coefficient i by g^i, do it for the
+    // shift that isn't compiled in instead
+    let shift = E::Base::GROUP_GEN.pow(1 << lg_blowup);
+    let mut power = E::Base::ONE;
+    for c in E::as_base_slice_mut(coeffs).chunks_exact_mut(E::DEGREE) {
+        c.iter_mut().for_each(|x| *x *= power);
+        power *= shift;
+    }
+    ext_ntt_internal(
+        device,
+        &mut ret,
+        NTTInputOutputOrder::NN,
+        NTTDirection::Forward,
+        NTTType::Standard,
+    )?;
+    Ok(ret)
+}
+
+/// Compute an in-place NTT of extension-field elements, panic on error.
+#[allow(non_snake_case)]
+pub fn ext_NTT<E>(device: Device, inout: &mut [E], order: NTTInputOutputOrder)
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    if let Err(e) = try_ext_NTT(device, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn ext_iNTT<E>(device: Device, inout: &mut [E], order: NTTInputOutputOrder)
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    if let Err(e) = try_ext_iNTT(device, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn ext_coset_NTT<E>(device: Device, inout: &mut [E], order: NTTInputOutputOrder)
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    if let Err(e) = try_ext_coset_NTT(device, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn ext_coset_iNTT<E>(device: Device, inout: &mut [E], order: NTTInputOutputOrder)
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    if let Err(e) = try_ext_coset_iNTT(device, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+/// Low-degree extension of extension-field elements, panic on error, see
+/// [`try_ext_lde`].
+pub fn ext_lde<E>(device: Device, input: &[E], lg_blowup: u32, ext_pow: bool) -> Vec<E>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    try_ext_lde(device, input, lg_blowup, ext_pow).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,224 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! NTTs over domains that don't fit into a single device's memory, or
+//! exceed MAX_LG_DOMAIN_SIZE, see [`sppark::ntt::compute_ntt_four_step`].
+//! The sub-transforms are batched NTTs streamed through the devices, the
+//! twiddles and transposes are applied on the host. Domains are generated
+//! by the compiled root of unity and shifted by the group generator, other
+//! conventions are covered by the [`Domain`](crate::Domain) transforms up
+//! to MAX_LG_DOMAIN_SIZE only.
+
+use crate::{
+    convert, cuda, Device, Field, NTTDirection, NTTInputOutputOrder, NTTLayout, NTTType, NttField,
+};
+use sppark::ntt::Direction;
+
+// Split the polynomials in |data| evenly between |devices| and transform
+// each share with NTT::Batch on its device.
+fn sub_ntts<F: NttField>(
+    devices: &[Device],
+    data: &mut [F],
+    lg_domain_size: u32,
+    direction: Direction,
+) -> Result<(), cuda::Error> {
+    let run = |device: Device, data: &mut [F]| {
+        let direction = match direction {
+            Direction::Forward => NTTDirection::Forward,
+            Direction::Inverse => NTTDirection::Inverse,
+        };
+        F::compute_ntt_batch(
+            device,
+            data,
+            lg_domain_size,
+            data.len() >> lg_domain_size,
+            NTTLayout::ColumnMajor,
+            NTTInputOutputOrder::NN,
+            direction,
+            NTTType::Standard,
+        )
+    };
+
+    let ncolumns = data.len() >> lg_domain_size;
+    if devices.len() == 1 || ncolumns == 1 {
+        return run(devices[0], data);
+    }
+
+    let share = ncolumns.div_ceil(devices.len()) << lg_domain_size;
+    std::thread::scope(|s| {
+        let threads: Vec<_> = data
+            .chunks_mut(share)
+            .zip(devices)
+            .map(|(data, &device)| s.spawn(move || run(device, data)))
+            .collect();
+        threads
+            .into_iter()
+            .try_for_each(|t| t.join().expect("sub-NTT thread panicked"))
+    })
+}
+
+fn ntt_four_step_internal<T>(
+    devices: &[Device],
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    type_: NTTType,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let len = inout.len();
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    let lg_domain_size = len.trailing_zeros();
+    if lg_domain_size > T::Repr::TWO_ADICITY {
+        return Err(cuda::Error::domain_too_large(
+            lg_domain_size,
+            T::Repr::TWO_ADICITY,
+        ));
+    }
+    // rows are the shorter side, so it's the columns that are bound by
+    // the device-side limit
+    let lg_rows = lg_domain_size / 2;
+    crate::check_domain_size::<T::Repr>(lg_domain_size - lg_rows)?;
+
+    let all;
+    let devices = if devices.is_empty() {
+        all = Device::all();
+        if all.is_empty() {
+            &[Device::default()]
+        } else {
+            &all[..]
+        }
+    } else {
+        devices
+    };
+
+    let (order, direction, type_) = convert(order, direction, type_);
+    sppark::ntt::compute_ntt_four_step(
+        T::cast_slice_mut(inout),
+        lg_rows,
+        order,
+        direction,
+        type_,
+        |data, lg, direction| sub_ntts(devices, data, lg, direction),
+    )
+}
+
+/// Compute an in-place NTT over a domain too large for a single device,
+/// splitting the work between |devices|, or all the available ones if
+/// empty. Domains up to 2^(2 * MAX_LG_DOMAIN_SIZE) are supported, and
+/// results are identical to [`try_NTT`](crate::try_NTT)'s. A scratch
+/// buffer of the size of |inout| is allocated on the host.
+#[allow(non_snake_case)]
+pub fn try_NTT_four_step<T>(
+    devices: &[Device],
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_four_step_internal(
+        devices,
+        inout,
+        order,
+        NTTDirection::Forward,
+        NTTType::Standard,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_iNTT_four_step<T>(
+    devices: &[Device],
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_four_step_internal(
+        devices,
+        inout,
+        order,
+        NTTDirection::Inverse,
+        NTTType::Standard,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_NTT_four_step<T>(
+    devices: &[Device],
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_four_step_internal(devices, inout, order, NTTDirection::Forward, NTTType::Coset)
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_iNTT_four_step<T>(
+    devices: &[Device],
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_four_step_internal(devices, inout, order, NTTDirection::Inverse, NTTType::Coset)
+}
+
+/// Compute an in-place NTT over a large domain, panic on error, see
+/// [`try_NTT_four_step`].
+#[allow(non_snake_case)]
+pub fn NTT_four_step<T>(devices: &[Device], inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_NTT_four_step(devices, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn iNTT_four_step<T>(devices: &[Device], inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_iNTT_four_step(devices, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn coset_NTT_four_step<T>(devices: &[Device], inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_NTT_four_step(devices, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn coset_iNTT_four_step<T>(devices: &[Device], inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_iNTT_four_step(devices, inout, order) {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! FRI folding between layers. Evaluations are expected in bit-reversed
+//! order, as produced by [`NTTInputOutputOrder::NR`](crate::NTTInputOutputOrder::NR),
+//! so that the points that fold into one are adjacent, and the next layer
+//! is returned in the same order. Base field elements are folded on
+//! |device|, extension ones and all without CUDA on the host, see
+//! sppark::ntt::fri_fold.
+
+use crate::poly::zeroed;
+use crate::{check_domain_size, cuda, Device, ExtensionField, Field, NttField};
+
+// Validate the arguments, return log2 of the arity.
+fn check_fri_args<F: NttField>(
+    len: usize,
+    lg_domain_size: u32,
+    folding_arity: usize,
+    shift: F,
+) -> Result<u32, cuda::Error> {
+    if !folding_arity.is_power_of_two() {
+        return Err(cuda::Error::new(
+            cuda::Error::NOT_POWER_OF_TWO,
+            &format!("folding_arity is not power of 2: {}", folding_arity),
+        ));
+    }
+    check_domain_size::<F>(lg_domain_size)?;
+    if len != 1 << lg_domain_size {
+        return Err(cuda::Error::length_mismatch(len, 1 << lg_domain_size));
+    }
+    let lg_arity = folding_arity.trailing_zeros();
+    if lg_arity > lg_domain_size {
+        return Err(cuda::Error::new(
+            cuda::Error::LENGTH_MISMATCH,
+            &format!(
+                "folding arity 2^{} exceeds domain size 2^{}",
+                lg_arity, lg_domain_size
+            ),
+        ));
+    }
+    if shift.is_zero() {
+        return Err(cuda::Error::division_by_zero("coset shift is zero"));
+    }
+    Ok(lg_arity)
+}
+
+/// Fold evaluations of f over the coset |shift|·H of 2^|lg_domain_size|
+/// elements by |folding_arity| with |challenge| β. With f(x) =
+/// Σ x^r·f_r(x^arity) for r < arity, the returned layer holds evaluations
+/// of Σ β^r·f_r over the coset shift^arity·H^arity, of
+/// `evals.len() / folding_arity` elements. H is generated by the compiled
+/// root of unity, i.e. the one of [`Domain::coset`](crate::Domain::coset).
+pub fn try_fri_fold<T>(
+    device: Device,
+    evals: &[T],
+    lg_domain_size: u32,
+    folding_arity: usize,
+    challenge: T,
+    shift: T,
+) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let challenge = T::cast_slice(core::slice::from_ref(&challenge))[0];
+    let shift = T::cast_slice(core::slice::from_ref(&shift))[0];
+    let lg_arity = check_fri_args(evals.len(), lg_domain_size, folding_arity, shift)?;
+
+    let mut ret = zeroed::<T>(evals.len() >> lg_arity);
+    T::Repr::fri_fold(
+        device,
+        T::cast_slice_mut(&mut ret),
+        T::cast_slice(evals),
+        lg_domain_size,
+        lg_arity,
+        challenge,
+        shift,
+    )?;
+    Ok(ret)
+}
+
+/// Same as [`try_fri_fold`], but with evaluations and challenge in the
+/// extension field, the domain being in the base one. Extension elements
+/// are folded on the host, |device| is accepted for symmetry.
+pub fn try_ext_fri_fold<E>(
+    device: Device,
+    evals: &[E],
+    lg_domain_size: u32,
+    folding_arity: usize,
+    challenge: E,
+    shift: E::Base,
+) -> Result<Vec<E>, cuda::Error>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    let lg_arity = check_fri_args(evals.len(), lg_domain_size, folding_arity, shift)?;
+
+    let _ = device;
+    let mut ret = vec![E::ZERO; evals.len() >> lg_arity];
+    sppark::ntt::fri_fold(&mut ret, evals, lg_domain_size, lg_arity, challenge, shift)?;
+    Ok(ret)
+}
+
+/// FRI folding, panic on error, see [`try_fri_fold`].
+pub fn fri_fold<T>(
+    device: Device,
+    evals: &[T],
+    lg_domain_size: u32,
+    folding_arity: usize,
+    challenge: T,
+    shift: T,
+) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_fri_fold(
+        device,
+        evals,
+        lg_domain_size,
+        folding_arity,
+        challenge,
+        shift,
+    )
+    .unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn ext_fri_fold<E>(
+    device: Device,
+    evals: &[E],
+    lg_domain_size: u32,
+    folding_arity: usize,
+    challenge: E,
+    shift: E::Base,
+) -> Vec<E>
+where
+    E: ExtensionField,
+    E::Base: NttField,
+{
+    try_ext_fri_fold(
+        device,
+        evals,
+        lg_domain_size,
+        folding_arity,
+        challenge,
+        shift,
+    )
+    .unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,47 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Batch inversion with Montgomery's trick, executed on |device|, or on
+//! the host without CUDA, see sppark::ff::batch_inverse.
+
+use crate::{cuda, Device, Field, NttField};
+use sppark::ff::HostField;
+pub use sppark::ff::Zeros;
+
+/// Invert all elements of |inout| in place. With [`Zeros::Skip`] zeros are
+/// left intact and their number is returned, with [`Zeros::Reject`] the
+/// presence of a zero is a DIVISION_BY_ZERO error naming the first one,
+/// |inout| being left intact.
+pub fn try_batch_inverse<T>(
+    device: Device,
+    inout: &mut [T],
+    zeros: Zeros,
+) -> Result<usize, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let inout = T::cast_slice_mut(inout);
+    if zeros == Zeros::Reject {
+        if let Some(i) = inout.iter().position(|x| x.is_zero()) {
+            return Err(cuda::Error::division_by_zero(&format!(
+                "element {} is zero",
+                i
+            )));
+        }
+    }
+    if inout.is_empty() {
+        return Ok(0);
+    }
+    T::Repr::batch_inverse(device, inout)
+}
+
+/// Batch inversion, panic on error, see [`try_batch_inverse`].
+pub fn batch_inverse<T>(device: Device, inout: &mut [T], zeros: Zeros) -> usize
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_batch_inverse(device, inout, zeros).unwrap_or_else(|e| panic!("{}", e))
+}
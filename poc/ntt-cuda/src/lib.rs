@@ -4,7 +4,33 @@
 
 sppark::cuda_error!();
 
+pub use sppark::device::Device;
+pub use sppark::ff::ExtensionField;
+pub use sppark::{DeviceVec, Field};
+
+use sppark::ntt::{Direction, InputOutputOrder, Type};
+
+mod bit_rev;
+pub mod dev;
+mod domain;
+mod ext;
+mod four_step;
+mod fri;
+mod inverse;
+pub mod poly;
+mod product;
+pub mod sumcheck;
+pub use bit_rev::*;
+pub use domain::*;
+pub use ext::*;
+pub use four_step::*;
+pub use fri::*;
+pub use inverse::*;
+pub use product::*;
+pub use sppark::ntt::Domain;
+
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum NTTInputOutputOrder {
     NN = 0,
     NR = 1,
@@ -13,126 +39,1577 @@ pub enum NTTInputOutputOrder {
 }
 
 #[repr(C)]
-enum NTTDirection {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NTTDirection {
     Forward = 0,
     Inverse = 1,
 }
 
 #[repr(C)]
-enum NTTType {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NTTType {
     Standard = 0,
     Coset = 1,
 }
 
-extern "C" {
+/// Layout of a batch of polynomials, see [`NTT_batch`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NTTLayout {
+    /// Polynomials one after another.
+    ColumnMajor = 0,
+    /// Polynomials interleaved, i.e. element i of polynomial j is at
+    /// i * ncolumns + j.
+    RowMajor = 1,
+}
+
+/// Fields selectable with the cargo feature of the same name, i.e. scalar
+/// fields of the curves, Goldilocks and BabyBear. More than one can be
+/// enabled at a time. The Goldilocks and BabyBear extensions come with
+/// their base fields, see [`try_ext_NTT`].
+pub mod fields {
+    #[cfg(feature = "bls12_377")]
+    pub type Bls12_377 = sppark::ff::bls12_377::Fr;
+    #[cfg(feature = "bls12_381")]
+    pub type Bls12_381 = sppark::ff::bls12_381::Fr;
+    // Fr for Pallas curve is Vesta and vice versa
+    #[cfg(feature = "pallas")]
+    pub type Pallas = sppark::ff::pasta::Vesta;
+    #[cfg(feature = "vesta")]
+    pub type Vesta = sppark::ff::pasta::Pallas;
+    #[cfg(feature = "bn254")]
+    pub type Bn254 = sppark::ff::alt_bn128::Fr;
+    #[cfg(feature = "gl64")]
+    pub type Goldilocks = sppark::ff::goldilocks::Goldilocks;
+    #[cfg(feature = "gl64")]
+    pub type GoldilocksExt2 = sppark::ff::goldilocks::GoldilocksExt2;
+    #[cfg(feature = "bb31")]
+    pub type BabyBear = sppark::ff::baby_bear::BabyBear;
+    #[cfg(feature = "bb31")]
+    pub type BabyBearExt4 = sppark::ff::baby_bear::BabyBearExt4;
+}
+
+/// A field the NTT was compiled for, see [`fields`]. Entry points accept
+/// slices of any [`Field`] that is represented as one.
+pub trait NttField: sppark::ntt::NTTParameters + Field<Repr = Self> {
+    /// log2 of the largest domain the library was compiled for, larger ones
+    /// are rejected with DOMAIN_TOO_LARGE. It's the MAX_LG_DOMAIN_SIZE that
+    /// build.rs passes to nvcc, and applies to the host implementation too.
+    const MAX_LG_DOMAIN_SIZE: u32;
+    /// log2 of the largest power-of-2 multiplicative subgroup of the field.
+    const TWO_ADICITY: u32 = <Self as sppark::ntt::NTTParameters>::S;
+
+    #[doc(hidden)]
     fn compute_ntt(
-        device_id: usize,
-        inout: *mut core::ffi::c_void,
+        device: Device,
+        inout: &mut [Self],
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+        type_: NTTType,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    fn compute_coset_ntt(
+        device: Device,
+        inout: &mut [Self],
+        shift: Self,
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    #[allow(clippy::too_many_arguments)]
+    fn compute_ntt_batch(
+        device: Device,
+        inout: &mut [Self],
         lg_domain_size: u32,
-        ntt_order: NTTInputOutputOrder,
-        ntt_direction: NTTDirection,
-        ntt_type: NTTType,
-    ) -> cuda::Error;
+        ncolumns: usize,
+        layout: NTTLayout,
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+        type_: NTTType,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    fn compute_lde(
+        device: Device,
+        inout: &mut [Self],
+        lg_blowup: u32,
+        ext_pow: bool,
+    ) -> Result<(), cuda::Error>;
+
+    /// |inp| of None means in place.
+    #[doc(hidden)]
+    fn bit_rev(
+        device: Device,
+        out: &mut [Self],
+        inp: Option<&[Self]>,
+        lg_domain_size: u32,
+        ncolumns: usize,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    fn compute_ntt_dev<T: Field<Repr = Self>>(
+        inout: &mut DeviceVec<T>,
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+        type_: NTTType,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    fn compute_lde_dev<T: Field<Repr = Self>>(
+        inout: &mut DeviceVec<T>,
+        lg_blowup: u32,
+        ext_pow: bool,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    fn bit_rev_dev<T: Field<Repr = Self>>(
+        out: &mut DeviceVec<T>,
+        inp: Option<&DeviceVec<T>>,
+        lg_domain_size: u32,
+        ncolumns: usize,
+    ) -> Result<(), cuda::Error>;
+
+    #[doc(hidden)]
+    fn fri_fold(
+        device: Device,
+        out: &mut [Self],
+        inp: &[Self],
+        lg_domain_size: u32,
+        lg_arity: u32,
+        challenge: Self,
+        shift: Self,
+    ) -> Result<(), cuda::Error>;
+
+    /// Zeros are left intact, their number is returned.
+    #[doc(hidden)]
+    fn batch_inverse(device: Device, inout: &mut [Self]) -> Result<usize, cuda::Error>;
+
+    /// |den| is expected to be as long as |inout|, returns the product of
+    /// all factors.
+    #[doc(hidden)]
+    fn grand_product(
+        device: Device,
+        inout: &mut [Self],
+        den: Option<&[Self]>,
+        exclusive: bool,
+    ) -> Result<Self, cuda::Error>;
+
+    /// |eq| is expected to be of 2^r.len() elements.
+    #[doc(hidden)]
+    fn sumcheck_eq_table(device: Device, eq: &mut [Self], r: &[Self]) -> Result<(), cuda::Error>;
+
+    /// |inp| is expected to be a power of 2 of at least 2 elements, and
+    /// |out| half as long.
+    #[doc(hidden)]
+    fn sumcheck_fold(
+        device: Device,
+        out: &mut [Self],
+        inp: &[Self],
+        challenge: Self,
+    ) -> Result<(), cuda::Error>;
+
+    /// |tables| are expected to be |ntables| power-of-2 tables of at least
+    /// 2 elements, and |evals| of ntables + 1 elements.
+    #[doc(hidden)]
+    fn sumcheck_round_poly(
+        device: Device,
+        evals: &mut [Self],
+        tables: &[Self],
+        ntables: usize,
+    ) -> Result<(), cuda::Error>;
 }
 
-/// Compute an in-place NTT on the input data.
-#[allow(non_snake_case)]
-pub fn NTT<T>(device_id: usize, inout: &mut [T], order: NTTInputOutputOrder) {
-    let len = inout.len();
-    if (len & (len - 1)) != 0 {
-        panic!("inout.len() is not power of 2");
+// Tie a field to its {compute_ntt,compute_coset_ntt,compute_ntt_batch,
+// compute_lde,bit_rev}_<feature> symbols and their DeviceVec counterparts,
+// to fri_fold_<feature>, batch_inverse_<feature> and grand_product_<feature>,
+// and to the sumcheck_{eq_table,fold,round_poly} ones, see cuda/ntt_api.cu.
+macro_rules! ntt_field {
+    (
+        $feature:literal,
+        $field:ty,
+        $compute_ntt:ident,
+        $compute_coset_ntt:ident,
+        $compute_ntt_batch:ident,
+        $compute_lde:ident,
+        $bit_rev:ident,
+        $compute_ntt_dev:ident,
+        $compute_lde_dev:ident,
+        $bit_rev_dev:ident,
+        $batch_inverse:ident,
+        $grand_product:ident,
+        $sumcheck_eq_table:ident,
+        $sumcheck_fold:ident,
+        $sumcheck_round_poly:ident,
+        $fri_fold:ident
+    ) => {
+        #[cfg(feature = $feature)]
+        impl NttField for $field {
+            const MAX_LG_DOMAIN_SIZE: u32 =
+                parse_lg(env!(concat!("MAX_LG_DOMAIN_SIZE_", $feature)));
+
+            fn compute_ntt(
+                device: Device,
+                inout: &mut [Self],
+                order: NTTInputOutputOrder,
+                direction: NTTDirection,
+                type_: NTTType,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $compute_ntt(
+                            device_id: i32,
+                            inout: *mut core::ffi::c_void,
+                            lg_domain_size: u32,
+                            ntt_order: NTTInputOutputOrder,
+                            ntt_direction: NTTDirection,
+                            ntt_type: NTTType,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $compute_ntt(
+                            device.as_raw(),
+                            inout.as_mut_ptr() as *mut core::ffi::c_void,
+                            inout.len().trailing_zeros(),
+                            order,
+                            direction,
+                            type_,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::Base"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    cpu::compute_ntt(inout, order, direction, type_)
+                }
+            }
+
+            fn compute_coset_ntt(
+                device: Device,
+                inout: &mut [Self],
+                shift: Self,
+                order: NTTInputOutputOrder,
+                direction: NTTDirection,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $compute_coset_ntt(
+                            device_id: i32,
+                            inout: *mut core::ffi::c_void,
+                            lg_domain_size: u32,
+                            shift: *const core::ffi::c_void,
+                            ntt_order: NTTInputOutputOrder,
+                            ntt_direction: NTTDirection,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $compute_coset_ntt(
+                            device.as_raw(),
+                            inout.as_mut_ptr() as *mut core::ffi::c_void,
+                            inout.len().trailing_zeros(),
+                            &shift as *const Self as *const core::ffi::c_void,
+                            order,
+                            direction,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::Coset"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    cpu::compute_coset_ntt(inout, shift, order, direction)
+                }
+            }
+
+            fn compute_ntt_batch(
+                device: Device,
+                inout: &mut [Self],
+                lg_domain_size: u32,
+                ncolumns: usize,
+                layout: NTTLayout,
+                order: NTTInputOutputOrder,
+                direction: NTTDirection,
+                type_: NTTType,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $compute_ntt_batch(
+                            device_id: i32,
+                            inout: *mut core::ffi::c_void,
+                            lg_domain_size: u32,
+                            ncolumns: usize,
+                            layout: NTTLayout,
+                            ntt_order: NTTInputOutputOrder,
+                            ntt_direction: NTTDirection,
+                            ntt_type: NTTType,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $compute_ntt_batch(
+                            device.as_raw(),
+                            inout.as_mut_ptr() as *mut core::ffi::c_void,
+                            lg_domain_size,
+                            ncolumns,
+                            layout,
+                            order,
+                            direction,
+                            type_,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::Batch"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    cpu::compute_ntt_batch(
+                        inout,
+                        lg_domain_size,
+                        ncolumns,
+                        layout,
+                        order,
+                        direction,
+                        type_,
+                    )
+                }
+            }
+
+            fn compute_lde(
+                device: Device,
+                inout: &mut [Self],
+                lg_blowup: u32,
+                ext_pow: bool,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $compute_lde(
+                            device_id: i32,
+                            inout: *mut core::ffi::c_void,
+                            lg_domain_size: u32,
+                            lg_blowup: u32,
+                            ext_pow: bool,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $compute_lde(
+                            device.as_raw(),
+                            inout.as_mut_ptr() as *mut core::ffi::c_void,
+                            inout.len().trailing_zeros() - lg_blowup,
+                            lg_blowup,
+                            ext_pow,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::LDE"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    sppark::ntt::lde(inout, lg_blowup, ext_pow)
+                }
+            }
+
+            fn bit_rev(
+                device: Device,
+                out: &mut [Self],
+                inp: Option<&[Self]>,
+                lg_domain_size: u32,
+                ncolumns: usize,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $bit_rev(
+                            device_id: i32,
+                            out: *mut core::ffi::c_void,
+                            inp: *const core::ffi::c_void,
+                            lg_domain_size: u32,
+                            ncolumns: usize,
+                        ) -> cuda::Error;
+                    }
+
+                    let out = out.as_mut_ptr() as *mut core::ffi::c_void;
+                    let inp = inp.map_or(out as *const _, |inp| inp.as_ptr() as *const _);
+                    let err =
+                        unsafe { $bit_rev(device.as_raw(), out, inp, lg_domain_size, ncolumns) };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::BitRev"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    match inp {
+                        None => sppark::ntt::bit_rev_batch(out, lg_domain_size, ncolumns),
+                        Some(inp) => {
+                            sppark::ntt::bit_rev_batch_into(out, inp, lg_domain_size, ncolumns)
+                        }
+                    }
+                }
+            }
+
+            fn compute_ntt_dev<T: Field<Repr = Self>>(
+                inout: &mut DeviceVec<T>,
+                order: NTTInputOutputOrder,
+                direction: NTTDirection,
+                type_: NTTType,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $compute_ntt_dev(
+                            device_id: i32,
+                            inout: *const core::ffi::c_void,
+                            lg_domain_size: u32,
+                            ntt_order: NTTInputOutputOrder,
+                            ntt_direction: NTTDirection,
+                            ntt_type: NTTType,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $compute_ntt_dev(
+                            inout.device().as_raw(),
+                            inout.as_gpu_ptr() as *const _ as *const core::ffi::c_void,
+                            inout.len().trailing_zeros(),
+                            order,
+                            direction,
+                            type_,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::Base_dev_ptr"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let inout = T::cast_slice_mut(inout.as_host_mut());
+                    cpu::compute_ntt(inout, order, direction, type_)
+                }
+            }
+
+            fn compute_lde_dev<T: Field<Repr = Self>>(
+                inout: &mut DeviceVec<T>,
+                lg_blowup: u32,
+                ext_pow: bool,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $compute_lde_dev(
+                            device_id: i32,
+                            inout: *const core::ffi::c_void,
+                            lg_domain_size: u32,
+                            lg_blowup: u32,
+                            ext_pow: bool,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $compute_lde_dev(
+                            inout.device().as_raw(),
+                            inout.as_gpu_ptr() as *const _ as *const core::ffi::c_void,
+                            inout.len().trailing_zeros() - lg_blowup,
+                            lg_blowup,
+                            ext_pow,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::LDE_dev_ptr"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let inout = T::cast_slice_mut(inout.as_host_mut());
+                    sppark::ntt::lde(inout, lg_blowup, ext_pow)
+                }
+            }
+
+            fn bit_rev_dev<T: Field<Repr = Self>>(
+                out: &mut DeviceVec<T>,
+                inp: Option<&DeviceVec<T>>,
+                lg_domain_size: u32,
+                ncolumns: usize,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $bit_rev_dev(
+                            device_id: i32,
+                            out: *const core::ffi::c_void,
+                            inp: *const core::ffi::c_void,
+                            lg_domain_size: u32,
+                            ncolumns: usize,
+                        ) -> cuda::Error;
+                    }
+
+                    let inp = inp.unwrap_or(out).as_gpu_ptr() as *const _;
+                    let err = unsafe {
+                        $bit_rev_dev(
+                            out.device().as_raw(),
+                            out.as_gpu_ptr() as *const _ as *const core::ffi::c_void,
+                            inp as *const core::ffi::c_void,
+                            lg_domain_size,
+                            ncolumns,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::BitRev_dev_ptr"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let out = T::cast_slice_mut(out.as_host_mut());
+                    match inp {
+                        None => sppark::ntt::bit_rev_batch(out, lg_domain_size, ncolumns),
+                        Some(inp) => {
+                            let inp = T::cast_slice(inp.as_host());
+                            sppark::ntt::bit_rev_batch_into(out, inp, lg_domain_size, ncolumns)
+                        }
+                    }
+                }
+            }
+
+            fn fri_fold(
+                device: Device,
+                out: &mut [Self],
+                inp: &[Self],
+                lg_domain_size: u32,
+                lg_arity: u32,
+                challenge: Self,
+                shift: Self,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $fri_fold(
+                            device_id: i32,
+                            out: *mut core::ffi::c_void,
+                            inp: *const core::ffi::c_void,
+                            lg_domain_size: u32,
+                            lg_arity: u32,
+                            challenge: *const core::ffi::c_void,
+                            shift: *const core::ffi::c_void,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $fri_fold(
+                            device.as_raw(),
+                            out.as_mut_ptr() as *mut core::ffi::c_void,
+                            inp.as_ptr() as *const core::ffi::c_void,
+                            lg_domain_size,
+                            lg_arity,
+                            &challenge as *const Self as *const core::ffi::c_void,
+                            &shift as *const Self as *const core::ffi::c_void,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("NTT::FriFold"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    sppark::ntt::fri_fold(out, inp, lg_domain_size, lg_arity, challenge, shift)
+                }
+            }
+
+            fn batch_inverse(device: Device, inout: &mut [Self]) -> Result<usize, cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $batch_inverse(
+                            device_id: i32,
+                            inout: *mut core::ffi::c_void,
+                            len: usize,
+                            nzeros: *mut usize,
+                        ) -> cuda::Error;
+                    }
+
+                    let mut nzeros = 0usize;
+                    let err = unsafe {
+                        $batch_inverse(
+                            device.as_raw(),
+                            inout.as_mut_ptr() as *mut core::ffi::c_void,
+                            inout.len(),
+                            &mut nzeros,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("batch_inverse"));
+                    }
+                    Ok(nzeros)
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    sppark::ff::batch_inverse(inout, sppark::ff::Zeros::Skip)
+                }
+            }
+
+            fn grand_product(
+                device: Device,
+                inout: &mut [Self],
+                den: Option<&[Self]>,
+                exclusive: bool,
+            ) -> Result<Self, cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $grand_product(
+                            device_id: i32,
+                            inout: *mut core::ffi::c_void,
+                            den: *const core::ffi::c_void,
+                            len: usize,
+                            exclusive: bool,
+                            total: *mut core::ffi::c_void,
+                        ) -> cuda::Error;
+                    }
+
+                    let mut total = <Self as sppark::ff::HostField>::ONE;
+                    let err = unsafe {
+                        $grand_product(
+                            device.as_raw(),
+                            inout.as_mut_ptr() as *mut core::ffi::c_void,
+                            den.map_or(core::ptr::null(), |d| d.as_ptr() as *const core::ffi::c_void),
+                            inout.len(),
+                            exclusive,
+                            &mut total as *mut Self as *mut core::ffi::c_void,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("grand_product"));
+                    }
+                    Ok(total)
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    let scan = match exclusive {
+                        true => sppark::poly::Scan::Exclusive,
+                        false => sppark::poly::Scan::Inclusive,
+                    };
+                    sppark::poly::grand_product(inout, den, scan)
+                }
+            }
+
+            fn sumcheck_eq_table(
+                device: Device,
+                eq: &mut [Self],
+                r: &[Self],
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $sumcheck_eq_table(
+                            device_id: i32,
+                            eq: *mut core::ffi::c_void,
+                            r: *const core::ffi::c_void,
+                            nvars: u32,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $sumcheck_eq_table(
+                            device.as_raw(),
+                            eq.as_mut_ptr() as *mut core::ffi::c_void,
+                            r.as_ptr() as *const core::ffi::c_void,
+                            r.len() as u32,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("Sumcheck::EqTable"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    eq.copy_from_slice(&sppark::sumcheck::eq_table(r));
+                    Ok(())
+                }
+            }
+
+            fn sumcheck_fold(
+                device: Device,
+                out: &mut [Self],
+                inp: &[Self],
+                challenge: Self,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $sumcheck_fold(
+                            device_id: i32,
+                            out: *mut core::ffi::c_void,
+                            inp: *const core::ffi::c_void,
+                            lg_size: u32,
+                            challenge: *const core::ffi::c_void,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $sumcheck_fold(
+                            device.as_raw(),
+                            out.as_mut_ptr() as *mut core::ffi::c_void,
+                            inp.as_ptr() as *const core::ffi::c_void,
+                            inp.len().trailing_zeros(),
+                            &challenge as *const Self as *const core::ffi::c_void,
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("Sumcheck::Fold"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    sppark::sumcheck::fold(out, inp, challenge)
+                }
+            }
+
+            fn sumcheck_round_poly(
+                device: Device,
+                evals: &mut [Self],
+                tables: &[Self],
+                ntables: usize,
+            ) -> Result<(), cuda::Error> {
+                #[cfg(feature = "cuda")]
+                {
+                    extern "C" {
+                        fn $sumcheck_round_poly(
+                            device_id: i32,
+                            evals: *mut core::ffi::c_void,
+                            tables: *const core::ffi::c_void,
+                            ntables: u32,
+                            lg_size: u32,
+                        ) -> cuda::Error;
+                    }
+
+                    let err = unsafe {
+                        $sumcheck_round_poly(
+                            device.as_raw(),
+                            evals.as_mut_ptr() as *mut core::ffi::c_void,
+                            tables.as_ptr() as *const core::ffi::c_void,
+                            ntables as u32,
+                            (tables.len() / ntables).trailing_zeros(),
+                        )
+                    };
+
+                    if err.code != 0 {
+                        return Err(err.with_op("Sumcheck::RoundPoly"));
+                    }
+                    Ok(())
+                }
+
+                #[cfg(not(feature = "cuda"))]
+                {
+                    let _ = device;
+                    evals.copy_from_slice(&sppark::sumcheck::round_poly(tables, ntables)?);
+                    Ok(())
+                }
+            }
+        }
+    };
+}
+
+ntt_field!(
+    "bls12_377",
+    fields::Bls12_377,
+    compute_ntt_bls12_377,
+    compute_coset_ntt_bls12_377,
+    compute_ntt_batch_bls12_377,
+    compute_lde_bls12_377,
+    bit_rev_bls12_377,
+    compute_ntt_dev_bls12_377,
+    compute_lde_dev_bls12_377,
+    bit_rev_dev_bls12_377,
+    batch_inverse_bls12_377,
+    grand_product_bls12_377,
+    sumcheck_eq_table_bls12_377,
+    sumcheck_fold_bls12_377,
+    sumcheck_round_poly_bls12_377,
+    fri_fold_bls12_377
+);
+ntt_field!(
+    "bls12_381",
+    fields::Bls12_381,
+    compute_ntt_bls12_381,
+    compute_coset_ntt_bls12_381,
+    compute_ntt_batch_bls12_381,
+    compute_lde_bls12_381,
+    bit_rev_bls12_381,
+    compute_ntt_dev_bls12_381,
+    compute_lde_dev_bls12_381,
+    bit_rev_dev_bls12_381,
+    batch_inverse_bls12_381,
+    grand_product_bls12_381,
+    sumcheck_eq_table_bls12_381,
+    sumcheck_fold_bls12_381,
+    sumcheck_round_poly_bls12_381,
+    fri_fold_bls12_381
+);
+ntt_field!(
+    "pallas",
+    fields::Pallas,
+    compute_ntt_pallas,
+    compute_coset_ntt_pallas,
+    compute_ntt_batch_pallas,
+    compute_lde_pallas,
+    bit_rev_pallas,
+    compute_ntt_dev_pallas,
+    compute_lde_dev_pallas,
+    bit_rev_dev_pallas,
+    batch_inverse_pallas,
+    grand_product_pallas,
+    sumcheck_eq_table_pallas,
+    sumcheck_fold_pallas,
+    sumcheck_round_poly_pallas,
+    fri_fold_pallas
+);
+ntt_field!(
+    "vesta",
+    fields::Vesta,
+    compute_ntt_vesta,
+    compute_coset_ntt_vesta,
+    compute_ntt_batch_vesta,
+    compute_lde_vesta,
+    bit_rev_vesta,
+    compute_ntt_dev_vesta,
+    compute_lde_dev_vesta,
+    bit_rev_dev_vesta,
+    batch_inverse_vesta,
+    grand_product_vesta,
+    sumcheck_eq_table_vesta,
+    sumcheck_fold_vesta,
+    sumcheck_round_poly_vesta,
+    fri_fold_vesta
+);
+ntt_field!(
+    "bn254",
+    fields::Bn254,
+    compute_ntt_bn254,
+    compute_coset_ntt_bn254,
+    compute_ntt_batch_bn254,
+    compute_lde_bn254,
+    bit_rev_bn254,
+    compute_ntt_dev_bn254,
+    compute_lde_dev_bn254,
+    bit_rev_dev_bn254,
+    batch_inverse_bn254,
+    grand_product_bn254,
+    sumcheck_eq_table_bn254,
+    sumcheck_fold_bn254,
+    sumcheck_round_poly_bn254,
+    fri_fold_bn254
+);
+ntt_field!(
+    "gl64",
+    fields::Goldilocks,
+    compute_ntt_gl64,
+    compute_coset_ntt_gl64,
+    compute_ntt_batch_gl64,
+    compute_lde_gl64,
+    bit_rev_gl64,
+    compute_ntt_dev_gl64,
+    compute_lde_dev_gl64,
+    bit_rev_dev_gl64,
+    batch_inverse_gl64,
+    grand_product_gl64,
+    sumcheck_eq_table_gl64,
+    sumcheck_fold_gl64,
+    sumcheck_round_poly_gl64,
+    fri_fold_gl64
+);
+ntt_field!(
+    "bb31",
+    fields::BabyBear,
+    compute_ntt_bb31,
+    compute_coset_ntt_bb31,
+    compute_ntt_batch_bb31,
+    compute_lde_bb31,
+    bit_rev_bb31,
+    compute_ntt_dev_bb31,
+    compute_lde_dev_bb31,
+    bit_rev_dev_bb31,
+    batch_inverse_bb31,
+    grand_product_bb31,
+    sumcheck_eq_table_bb31,
+    sumcheck_fold_bb31,
+    sumcheck_round_poly_bb31,
+    fri_fold_bb31
+);
+
+// MAX_LG_DOMAIN_SIZE_<feature> as set by build.rs.
+const fn parse_lg(s: &str) -> u32 {
+    let s = s.as_bytes();
+    let mut ret = 0;
+    let mut i = 0;
+    while i < s.len() {
+        assert!(s[i].is_ascii_digit());
+        ret = ret * 10 + (s[i] - b'0') as u32;
+        i += 1;
+    }
+    ret
+}
+
+fn check_domain_size<F: NttField>(lg_domain_size: u32) -> Result<(), cuda::Error> {
+    if lg_domain_size > F::MAX_LG_DOMAIN_SIZE {
+        return Err(cuda::Error::domain_too_large(
+            lg_domain_size,
+            F::MAX_LG_DOMAIN_SIZE,
+        ));
     }
+    Ok(())
+}
 
-    let err = unsafe {
-        compute_ntt(
-            device_id,
-            inout.as_mut_ptr() as *mut core::ffi::c_void,
-            len.trailing_zeros(),
-            order,
-            NTTDirection::Forward,
-            NTTType::Standard,
-        )
+// Validate the length of a single-domain buffer.
+fn check_len<F: NttField>(len: usize) -> Result<(), cuda::Error> {
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    check_domain_size::<F>(len.trailing_zeros())
+}
+
+fn ntt_internal<T>(
+    device: Device,
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    type_: NTTType,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_len::<T::Repr>(inout.len())?;
+
+    T::Repr::compute_ntt(device, T::cast_slice_mut(inout), order, direction, type_)
+}
+
+// Counterparts of the arguments in sppark's host implementation.
+fn convert(
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    type_: NTTType,
+) -> (InputOutputOrder, Direction, Type) {
+    let order = match order {
+        NTTInputOutputOrder::NN => InputOutputOrder::NN,
+        NTTInputOutputOrder::NR => InputOutputOrder::NR,
+        NTTInputOutputOrder::RN => InputOutputOrder::RN,
+        NTTInputOutputOrder::RR => InputOutputOrder::RR,
+    };
+    let direction = match direction {
+        NTTDirection::Forward => Direction::Forward,
+        NTTDirection::Inverse => Direction::Inverse,
     };
+    let type_ = match type_ {
+        NTTType::Standard => Type::Standard,
+        NTTType::Coset => Type::Coset,
+    };
+    (order, direction, type_)
+}
+
+// Host fallback for systems without CUDA compiler, bit-identical to the GPU
+// implementation.
+#[cfg(not(feature = "cuda"))]
+mod cpu {
+    use super::{convert, NTTDirection, NTTInputOutputOrder, NTTLayout, NTTType};
+    use sppark::ntt::{self, Layout, NTTParameters};
+
+    pub(crate) fn compute_ntt<F: NTTParameters>(
+        inout: &mut [F],
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+        type_: NTTType,
+    ) -> Result<(), sppark::Error> {
+        let (order, direction, type_) = convert(order, direction, type_);
+
+        ntt::compute_ntt(inout, order, direction, type_)
+    }
+
+    pub(crate) fn compute_coset_ntt<F: NTTParameters>(
+        inout: &mut [F],
+        shift: F,
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+    ) -> Result<(), sppark::Error> {
+        let (order, direction, _) = convert(order, direction, NTTType::Coset);
 
-    if err.code != 0 {
-        panic!("{}", String::from(err));
+        ntt::compute_coset_ntt(inout, shift, order, direction)
     }
+
+    pub(crate) fn compute_ntt_batch<F: NTTParameters>(
+        inout: &mut [F],
+        lg_domain_size: u32,
+        ncolumns: usize,
+        layout: NTTLayout,
+        order: NTTInputOutputOrder,
+        direction: NTTDirection,
+        type_: NTTType,
+    ) -> Result<(), sppark::Error> {
+        let (order, direction, type_) = convert(order, direction, type_);
+        let layout = match layout {
+            NTTLayout::ColumnMajor => Layout::ColumnMajor,
+            NTTLayout::RowMajor => Layout::RowMajor,
+        };
+
+        ntt::compute_ntt_batch(
+            inout,
+            lg_domain_size,
+            ncolumns,
+            layout,
+            order,
+            direction,
+            type_,
+        )
+    }
+}
+
+/// Compute an in-place NTT on the input data. Elements can be of any type
+/// sppark can vouch for, e.g. arkworks' Fr, see [`Field`].
+#[allow(non_snake_case)]
+pub fn try_NTT<T>(
+    device: Device,
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(
+        device,
+        inout,
+        order,
+        NTTDirection::Forward,
+        NTTType::Standard,
+    )
 }
 
 /// Compute an in-place iNTT on the input data.
 #[allow(non_snake_case)]
-pub fn iNTT<T>(device_id: usize, inout: &mut [T], order: NTTInputOutputOrder) {
-    let len = inout.len();
-    if (len & (len - 1)) != 0 {
-        panic!("inout.len() is not power of 2");
+pub fn try_iNTT<T>(
+    device: Device,
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(
+        device,
+        inout,
+        order,
+        NTTDirection::Inverse,
+        NTTType::Standard,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_NTT<T>(
+    device: Device,
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(device, inout, order, NTTDirection::Forward, NTTType::Coset)
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_iNTT<T>(
+    device: Device,
+    inout: &mut [T],
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_internal(device, inout, order, NTTDirection::Inverse, NTTType::Coset)
+}
+
+/// Compute an in-place NTT on the input data, panic on error.
+#[allow(non_snake_case)]
+pub fn NTT<T>(device: Device, inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_NTT(device, inout, order) {
+        panic!("{}", e);
     }
+}
 
-    let err = unsafe {
-        compute_ntt(
-            device_id,
-            inout.as_mut_ptr() as *mut core::ffi::c_void,
-            len.trailing_zeros(),
-            order,
-            NTTDirection::Inverse,
-            NTTType::Standard,
-        )
-    };
+/// Compute an in-place iNTT on the input data, panic on error.
+#[allow(non_snake_case)]
+pub fn iNTT<T>(device: Device, inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_iNTT(device, inout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn coset_NTT<T>(device: Device, inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_NTT(device, inout, order) {
+        panic!("{}", e);
+    }
+}
 
-    if err.code != 0 {
-        panic!("{}", String::from(err));
+#[allow(non_snake_case)]
+pub fn coset_iNTT<T>(device: Device, inout: &mut [T], order: NTTInputOutputOrder)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_iNTT(device, inout, order) {
+        panic!("{}", e);
     }
 }
 
+fn coset_ntt_internal<T>(
+    device: Device,
+    inout: &mut [T],
+    shift: T,
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_len::<T::Repr>(inout.len())?;
+
+    let shift = T::cast_slice(core::slice::from_ref(&shift))[0];
+    T::Repr::compute_coset_ntt(device, T::cast_slice_mut(inout), shift, order, direction)
+}
+
+/// Compute an in-place NTT over the coset |shift|·H of the input data's
+/// domain H, as opposed to [`try_coset_NTT`], which is fixed to the field's
+/// group generator. Powers of |shift| are computed on the device with each
+/// call, which is cheap compared to the transform itself.
 #[allow(non_snake_case)]
-pub fn coset_NTT<T>(
-    device_id: usize,
+pub fn try_coset_NTT_with_shift<T>(
+    device: Device,
     inout: &mut [T],
+    shift: T,
     order: NTTInputOutputOrder,
-) {
-    let len = inout.len();
-    if (len & (len - 1)) != 0 {
-        panic!("inout.len() is not power of 2");
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    coset_ntt_internal(device, inout, shift, order, NTTDirection::Forward)
+}
+
+/// Compute an in-place iNTT from the coset |shift|·H, the inverse of
+/// [`try_coset_NTT_with_shift`].
+#[allow(non_snake_case)]
+pub fn try_coset_iNTT_with_shift<T>(
+    device: Device,
+    inout: &mut [T],
+    shift: T,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    coset_ntt_internal(device, inout, shift, order, NTTDirection::Inverse)
+}
+
+#[allow(non_snake_case)]
+pub fn coset_NTT_with_shift<T>(
+    device: Device,
+    inout: &mut [T],
+    shift: T,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_NTT_with_shift(device, inout, shift, order) {
+        panic!("{}", e);
     }
+}
 
-    let err = unsafe {
-        compute_ntt(
-            device_id,
-            inout.as_mut_ptr() as *mut core::ffi::c_void,
-            len.trailing_zeros(),
-            order,
-            NTTDirection::Forward,
-            NTTType::Coset,
-        )
-    };
+#[allow(non_snake_case)]
+pub fn coset_iNTT_with_shift<T>(
+    device: Device,
+    inout: &mut [T],
+    shift: T,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_iNTT_with_shift(device, inout, shift, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn ntt_batch_internal<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+    direction: NTTDirection,
+    type_: NTTType,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_domain_size::<T::Repr>(lg_domain_size)?;
+    let expected = ncolumns.checked_mul(1 << lg_domain_size);
+    if Some(inout.len()) != expected {
+        return Err(cuda::Error::length_mismatch(
+            inout.len(),
+            expected.unwrap_or(usize::MAX),
+        ));
+    }
+
+    T::Repr::compute_ntt_batch(
+        device,
+        T::cast_slice_mut(inout),
+        lg_domain_size,
+        ncolumns,
+        layout,
+        order,
+        direction,
+        type_,
+    )
+}
+
+/// Compute in-place NTTs of |ncolumns| polynomials of 2^|lg_domain_size|
+/// elements each, laid out as specified by |layout|. Transfers to and from
+/// the device are done for as many polynomials at a time as fit.
+#[allow(non_snake_case)]
+pub fn try_NTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_batch_internal(
+        device,
+        inout,
+        lg_domain_size,
+        ncolumns,
+        layout,
+        order,
+        NTTDirection::Forward,
+        NTTType::Standard,
+    )
+}
 
-    if err.code != 0 {
-        panic!("{}", String::from(err));
+#[allow(non_snake_case)]
+pub fn try_iNTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_batch_internal(
+        device,
+        inout,
+        lg_domain_size,
+        ncolumns,
+        layout,
+        order,
+        NTTDirection::Inverse,
+        NTTType::Standard,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_NTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_batch_internal(
+        device,
+        inout,
+        lg_domain_size,
+        ncolumns,
+        layout,
+        order,
+        NTTDirection::Forward,
+        NTTType::Coset,
+    )
+}
+
+#[allow(non_snake_case)]
+pub fn try_coset_iNTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    ntt_batch_internal(
+        device,
+        inout,
+        lg_domain_size,
+        ncolumns,
+        layout,
+        order,
+        NTTDirection::Inverse,
+        NTTType::Coset,
+    )
+}
+
+/// Compute in-place NTTs of a batch of polynomials, panic on error, see
+/// [`try_NTT_batch`].
+#[allow(non_snake_case)]
+pub fn NTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_NTT_batch(device, inout, lg_domain_size, ncolumns, layout, order) {
+        panic!("{}", e);
     }
 }
 
 #[allow(non_snake_case)]
-pub fn coset_iNTT<T>(
-    device_id: usize,
+pub fn iNTT_batch<T>(
+    device: Device,
     inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
     order: NTTInputOutputOrder,
-) {
-    let len = inout.len();
-    if (len & (len - 1)) != 0 {
-        panic!("inout.len() is not power of 2");
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_iNTT_batch(device, inout, lg_domain_size, ncolumns, layout, order) {
+        panic!("{}", e);
     }
+}
 
-    let err = unsafe {
-        compute_ntt(
-            device_id,
-            inout.as_mut_ptr() as *mut core::ffi::c_void,
-            len.trailing_zeros(),
-            order,
-            NTTDirection::Inverse,
-            NTTType::Coset,
-        )
-    };
+#[allow(non_snake_case)]
+pub fn coset_NTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_NTT_batch(device, inout, lg_domain_size, ncolumns, layout, order) {
+        panic!("{}", e);
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn coset_iNTT_batch<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: NTTLayout,
+    order: NTTInputOutputOrder,
+) where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_coset_iNTT_batch(device, inout, lg_domain_size, ncolumns, layout, order) {
+        panic!("{}", e);
+    }
+}
+
+// Validate the length of an in-place LDE's buffer.
+fn check_lde_len<F: NttField>(len: usize, lg_blowup: u32) -> Result<(), cuda::Error> {
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    let lg_ext_domain_size = len.trailing_zeros();
+    if lg_ext_domain_size < lg_blowup {
+        return Err(cuda::Error::new(
+            cuda::Error::LENGTH_MISMATCH,
+            &format!("inout.len() {} is less than 2^{}", len, lg_blowup),
+        ));
+    }
+    check_domain_size::<F>(lg_ext_domain_size)
+}
+
+fn lde_internal<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_blowup: u32,
+    ext_pow: bool,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_lde_len::<T::Repr>(inout.len(), lg_blowup)?;
+
+    T::Repr::compute_lde(device, T::cast_slice_mut(inout), lg_blowup, ext_pow)
+}
+
+/// Low-degree extension. |input| is taken for evaluations of a polynomial
+/// over the subgroup of its size, and evaluations over the coset of the
+/// subgroup 2^|lg_blowup| times larger are returned, in natural order.
+/// The coset is shifted by the field's group generator g, or by
+/// g^(2^|lg_blowup|) if |ext_pow| is set.
+pub fn try_lde<T>(
+    device: Device,
+    input: &[T],
+    lg_blowup: u32,
+    ext_pow: bool,
+) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let len = input.len();
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    // before allocating the extended domain
+    check_domain_size::<T::Repr>(len.trailing_zeros().saturating_add(lg_blowup))?;
+
+    let mut ret = crate::poly::zeroed::<T>(len << lg_blowup);
+    ret[..len].copy_from_slice(input);
+    lde_internal(device, &mut ret, lg_blowup, ext_pow)?;
+    Ok(ret)
+}
+
+/// Same as [`try_lde`], but in place. The input is the first
+/// `inout.len() >> lg_blowup` elements of |inout|.
+pub fn try_lde_in_place<T>(
+    device: Device,
+    inout: &mut [T],
+    lg_blowup: u32,
+    ext_pow: bool,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    lde_internal(device, inout, lg_blowup, ext_pow)
+}
+
+/// Low-degree extension, panic on error, see [`try_lde`].
+pub fn lde<T>(device: Device, input: &[T], lg_blowup: u32, ext_pow: bool) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_lde(device, input, lg_blowup, ext_pow).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    if err.code != 0 {
-        panic!("{}", String::from(err));
+pub fn lde_in_place<T>(device: Device, inout: &mut [T], lg_blowup: u32, ext_pow: bool)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_lde_in_place(device, inout, lg_blowup, ext_pow) {
+        panic!("{}", e);
     }
 }
@@ -0,0 +1,313 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Polynomial arithmetic on top of the transforms. Polynomials are vectors
+//! of coefficients, lowest degree first, evaluations are in natural order.
+//! Transforms are executed on |device|, or on the host without CUDA, while
+//! element-wise operations are always performed on the host.
+
+use crate::{cuda, Device, Field, NTTInputOutputOrder, NttField};
+use sppark::ff::HostField;
+use sppark::ntt::NTTParameters;
+
+pub use sppark::ntt::Domain;
+
+// All Field implementors are plain arrays of integers, and all-zero is
+// the field's zero in every representation.
+pub(crate) fn zeroed<T: Field>(len: usize) -> Vec<T> {
+    vec![unsafe { core::mem::zeroed::<T>() }; len]
+}
+
+pub(crate) fn from_repr<T: Field>(repr: &[T::Repr]) -> Vec<T> {
+    let mut ret = zeroed::<T>(repr.len());
+    T::cast_slice_mut(&mut ret).copy_from_slice(repr);
+    ret
+}
+
+fn check_same_len(a: usize, b: usize) -> Result<(), cuda::Error> {
+    if a != b {
+        return Err(cuda::Error::length_mismatch(a, b));
+    }
+    Ok(())
+}
+
+/// Product of |a| and |b|, of `a.len() + b.len() - 1` coefficients, or
+/// none if either is empty. Computed with transforms over the smallest
+/// power-of-2 domain that accommodates the result.
+pub fn try_mul<T>(device: Device, a: &[T], b: &[T]) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if a.is_empty() || b.is_empty() {
+        return Ok(vec![]);
+    }
+    let len = a.len() + b.len() - 1;
+    let domain_size = len.next_power_of_two();
+
+    let mut ret = zeroed::<T>(domain_size);
+    ret[..a.len()].copy_from_slice(a);
+    crate::try_NTT(device, &mut ret, NTTInputOutputOrder::NR)?;
+
+    let mut other = zeroed::<T>(domain_size);
+    other[..b.len()].copy_from_slice(b);
+    crate::try_NTT(device, &mut other, NTTInputOutputOrder::NR)?;
+
+    // the order of evaluations doesn't matter as long as it's the same
+    try_pointwise_mul(&mut ret, &other)?;
+    drop(other);
+
+    crate::try_iNTT(device, &mut ret, NTTInputOutputOrder::RN)?;
+    ret.truncate(len);
+    Ok(ret)
+}
+
+/// Square of |a|, same as `try_mul(device, a, a)`, but with one forward
+/// transform.
+pub fn try_square<T>(device: Device, a: &[T]) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if a.is_empty() {
+        return Ok(vec![]);
+    }
+    let len = 2 * a.len() - 1;
+
+    let mut ret = zeroed::<T>(len.next_power_of_two());
+    ret[..a.len()].copy_from_slice(a);
+    crate::try_NTT(device, &mut ret, NTTInputOutputOrder::NR)?;
+    for x in T::cast_slice_mut(&mut ret) {
+        *x = x.sqr();
+    }
+    crate::try_iNTT(device, &mut ret, NTTInputOutputOrder::RN)?;
+    ret.truncate(len);
+    Ok(ret)
+}
+
+/// Divide evaluations over the coset |shift|·H' by the vanishing polynomial
+/// X^n - 1 of the subgroup H of n = 2^|lg_domain_size| elements, H' being
+/// the subgroup of `evals.len()` elements, a multiple of n. Transforms of
+/// the coset type are over the coset shifted by [`NttField::GROUP_GEN`],
+/// see [`try_lde`](crate::try_lde) for ones shifted by its powers.
+///
+/// X^n - 1 takes only `evals.len() / n` distinct values over the coset, and
+/// the division fails with DIVISION_BY_ZERO if either of them is zero,
+/// i.e. if the coset intersects H.
+pub fn try_divide_by_vanishing<T>(
+    evals: &mut [T],
+    lg_domain_size: u32,
+    shift: T,
+) -> Result<(), cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let len = evals.len();
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    let lg_ext_domain_size = len.trailing_zeros();
+    if lg_ext_domain_size < lg_domain_size {
+        return Err(cuda::Error::new(
+            cuda::Error::LENGTH_MISMATCH,
+            &format!("evals.len() {} is less than 2^{}", len, lg_domain_size),
+        ));
+    }
+    if lg_ext_domain_size > T::Repr::TWO_ADICITY {
+        return Err(cuda::Error::domain_too_large(
+            lg_ext_domain_size,
+            T::Repr::TWO_ADICITY,
+        ));
+    }
+
+    // (shift·w^i)^n - 1 = shift^n·(w^n)^i - 1, w^n being a primitive
+    // root of unity of order evals.len() / n
+    let lg_blowup = lg_ext_domain_size - lg_domain_size;
+    let shift = T::cast_slice(core::slice::from_ref(&shift))[0];
+    let shift_n = shift.pow(1 << lg_domain_size);
+    let root = T::Repr::root_of_unity(lg_blowup, false);
+
+    let mut acc = shift_n;
+    let mut denominators = Vec::with_capacity(1 << lg_blowup);
+    for i in 0..1usize << lg_blowup {
+        let z = acc - T::Repr::ONE;
+        if z.is_zero() {
+            return Err(cuda::Error::division_by_zero(&format!(
+                "X^{} - 1 vanishes at coset point {}",
+                1u64 << lg_domain_size,
+                i
+            )));
+        }
+        denominators.push(z.reciprocal());
+        acc *= root;
+    }
+
+    let mask = denominators.len() - 1;
+    for (i, x) in T::cast_slice_mut(evals).iter_mut().enumerate() {
+        *x *= denominators[i & mask];
+    }
+    Ok(())
+}
+
+/// Evaluations over the subgroup 2^|lg_blowup| times larger than that of
+/// |evals|, the latter being evaluations over the subgroup of their size.
+/// Both are in natural order. For evaluations over a coset, see
+/// [`try_lde`](crate::try_lde).
+pub fn try_extend<T>(device: Device, evals: &[T], lg_blowup: u32) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let len = evals.len();
+    if !len.is_power_of_two() {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    // before allocating the extended domain
+    crate::check_domain_size::<T::Repr>(len.trailing_zeros().saturating_add(lg_blowup))?;
+
+    let mut ret = zeroed::<T>(len << lg_blowup);
+    ret[..len].copy_from_slice(evals);
+    crate::try_iNTT(device, &mut ret[..len], NTTInputOutputOrder::NN)?;
+    crate::try_NTT(device, &mut ret, NTTInputOutputOrder::NN)?;
+    Ok(ret)
+}
+
+/// Evaluate the polynomial given by its |evals| over |domain|, in natural
+/// order, at an arbitrary |point|, see
+/// [`sppark::poly::evaluate_lagrange`]. Evaluations by a transform of the
+/// coset type are over [`Domain::standard_coset`].
+pub fn try_evaluate_lagrange<T>(
+    evals: &[T],
+    domain: &Domain<T::Repr>,
+    point: T,
+) -> Result<T, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    Ok(try_evaluate_lagrange_batch(evals, domain, 1, point)?[0])
+}
+
+/// Evaluate |ncolumns| polynomials laid out one after another at the same
+/// |point|, sharing the inversions between them.
+pub fn try_evaluate_lagrange_batch<T>(
+    evals: &[T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    point: T,
+) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    let ret = sppark::poly::evaluate_lagrange_batch(
+        T::cast_slice(evals),
+        domain,
+        ncolumns,
+        T::cast_slice(core::slice::from_ref(&point))[0],
+    )?;
+    Ok(from_repr(&ret))
+}
+
+fn pointwise<T, F>(a: &mut [T], b: &[T], f: F) -> Result<(), cuda::Error>
+where
+    T: Field,
+    F: Fn(&mut T::Repr, T::Repr),
+{
+    check_same_len(a.len(), b.len())?;
+    for (x, y) in T::cast_slice_mut(a).iter_mut().zip(T::cast_slice(b)) {
+        f(x, *y);
+    }
+    Ok(())
+}
+
+/// a[i] *= b[i]
+pub fn try_pointwise_mul<T: Field>(a: &mut [T], b: &[T]) -> Result<(), cuda::Error> {
+    pointwise(a, b, |x, y| *x *= y)
+}
+
+/// a[i] += b[i]
+pub fn try_pointwise_add<T: Field>(a: &mut [T], b: &[T]) -> Result<(), cuda::Error> {
+    pointwise(a, b, |x, y| *x += y)
+}
+
+/// a[i] -= b[i]
+pub fn try_pointwise_sub<T: Field>(a: &mut [T], b: &[T]) -> Result<(), cuda::Error> {
+    pointwise(a, b, |x, y| *x -= y)
+}
+
+/// Product of |a| and |b|, panic on error, see [`try_mul`].
+pub fn mul<T>(device: Device, a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_mul(device, a, b).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn square<T>(device: Device, a: &[T]) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_square(device, a).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn divide_by_vanishing<T>(evals: &mut [T], lg_domain_size: u32, shift: T)
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Err(e) = try_divide_by_vanishing(evals, lg_domain_size, shift) {
+        panic!("{}", e);
+    }
+}
+
+pub fn extend<T>(device: Device, evals: &[T], lg_blowup: u32) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_extend(device, evals, lg_blowup).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn evaluate_lagrange<T>(evals: &[T], domain: &Domain<T::Repr>, point: T) -> T
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_evaluate_lagrange(evals, domain, point).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn evaluate_lagrange_batch<T>(
+    evals: &[T],
+    domain: &Domain<T::Repr>,
+    ncolumns: usize,
+    point: T,
+) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_evaluate_lagrange_batch(evals, domain, ncolumns, point).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn pointwise_mul<T: Field>(a: &mut [T], b: &[T]) {
+    if let Err(e) = try_pointwise_mul(a, b) {
+        panic!("{}", e);
+    }
+}
+
+pub fn pointwise_add<T: Field>(a: &mut [T], b: &[T]) {
+    if let Err(e) = try_pointwise_add(a, b) {
+        panic!("{}", e);
+    }
+}
+
+pub fn pointwise_sub<T: Field>(a: &mut [T], b: &[T]) {
+    if let Err(e) = try_pointwise_sub(a, b) {
+        panic!("{}", e);
+    }
+}
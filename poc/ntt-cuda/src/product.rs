@@ -0,0 +1,48 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Grand products for permutation and lookup arguments, executed on
+//! |device|, or on the host without CUDA, see sppark::poly::grand_product.
+
+use crate::poly::from_repr;
+use crate::{cuda, Device, Field, NttField};
+pub use sppark::poly::Scan;
+
+/// Replace the factors in |inout| with their running products z_i, divided
+/// by the running products of |den| if it's given, i.e. z_i = Π num_j/den_j
+/// over j <= i or j < i depending on |scan|. Returns the product of all
+/// factors. A zero denominator is a DIVISION_BY_ZERO error.
+pub fn try_grand_product<T>(
+    device: Device,
+    inout: &mut [T],
+    den: Option<&[T]>,
+    scan: Scan,
+) -> Result<T, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if let Some(den) = den {
+        if den.len() != inout.len() {
+            return Err(cuda::Error::length_mismatch(den.len(), inout.len()));
+        }
+    }
+
+    let total = T::Repr::grand_product(
+        device,
+        T::cast_slice_mut(inout),
+        den.map(T::cast_slice),
+        scan == Scan::Exclusive,
+    )?;
+    Ok(from_repr::<T>(&[total])[0])
+}
+
+/// Grand product, panic on error, see [`try_grand_product`].
+pub fn grand_product<T>(device: Device, inout: &mut [T], den: Option<&[T]>, scan: Scan) -> T
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_grand_product(device, inout, den, scan).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,124 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Multilinear primitives of sumcheck provers. A multilinear polynomial in
+//! n variables is given by its 2^n evaluations over the boolean hypercube,
+//! the first variable being the most significant bit of the index. The
+//! primitives are executed on |device|, or on the host without CUDA, see
+//! sppark::sumcheck.
+
+use crate::poly::zeroed;
+use crate::{cuda, Device, Field, NttField};
+pub use sppark::sumcheck::MAX_TABLES;
+
+// A table of at least one variable.
+fn check_table_len(len: usize) -> Result<(), cuda::Error> {
+    if !len.is_power_of_two() || len < 2 {
+        return Err(cuda::Error::not_power_of_two(len));
+    }
+    Ok(())
+}
+
+/// Evaluations of eq(r, x) = Π (r_k·x_k + (1 - r_k)·(1 - x_k)) over the
+/// hypercube of |r.len()| dimensions.
+pub fn try_eq_table<T>(device: Device, r: &[T]) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if r.len() >= usize::BITS as usize {
+        return Err(cuda::Error::new(
+            cuda::Error::LENGTH_MISMATCH,
+            &format!("{} variables are too many", r.len()),
+        ));
+    }
+
+    let mut ret = zeroed::<T>(1 << r.len());
+    T::Repr::sumcheck_eq_table(device, T::cast_slice_mut(&mut ret), T::cast_slice(r))?;
+    Ok(ret)
+}
+
+/// Fix the first variable of the multilinear polynomial given by |table|
+/// at |challenge| r, i.e. f(r, x') = f(0, x') + r·(f(1, x') - f(0, x')),
+/// and return the table of half the size.
+pub fn try_fold<T>(device: Device, table: &[T], challenge: T) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    check_table_len(table.len())?;
+
+    let mut ret = zeroed::<T>(table.len() / 2);
+    T::Repr::sumcheck_fold(
+        device,
+        T::cast_slice_mut(&mut ret),
+        T::cast_slice(table),
+        T::cast_slice(core::slice::from_ref(&challenge))[0],
+    )?;
+    Ok(ret)
+}
+
+/// Round polynomial g(X) = Σ Π f_i(X, x') over the hypercube of x', of the
+/// product of |ntables| multilinear polynomials, up to [`MAX_TABLES`],
+/// whose tables are laid out one after another in |tables|. Returns g's
+/// evaluations at X = 0, 1, ..., ntables.
+pub fn try_round_poly<T>(
+    device: Device,
+    tables: &[T],
+    ntables: usize,
+) -> Result<Vec<T>, cuda::Error>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    if ntables == 0 || ntables > MAX_TABLES {
+        return Err(cuda::Error::new(
+            cuda::Error::LENGTH_MISMATCH,
+            &format!("number of tables {} is not in 1..={}", ntables, MAX_TABLES),
+        ));
+    }
+    if !tables.len().is_multiple_of(ntables) {
+        return Err(cuda::Error::length_mismatch(
+            tables.len(),
+            tables.len() / ntables * ntables,
+        ));
+    }
+    check_table_len(tables.len() / ntables)?;
+
+    let mut ret = zeroed::<T>(ntables + 1);
+    T::Repr::sumcheck_round_poly(
+        device,
+        T::cast_slice_mut(&mut ret),
+        T::cast_slice(tables),
+        ntables,
+    )?;
+    Ok(ret)
+}
+
+/// eq table, panic on error, see [`try_eq_table`].
+pub fn eq_table<T>(device: Device, r: &[T]) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_eq_table(device, r).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Folding, panic on error, see [`try_fold`].
+pub fn fold<T>(device: Device, table: &[T], challenge: T) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_fold(device, table, challenge).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Round polynomial, panic on error, see [`try_round_poly`].
+pub fn round_poly<T>(device: Device, tables: &[T], ntables: usize) -> Vec<T>
+where
+    T: Field,
+    T::Repr: NttField,
+{
+    try_round_poly(device, tables, ntables).unwrap_or_else(|e| panic!("{}", e))
+}
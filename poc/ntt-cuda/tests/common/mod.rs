@@ -0,0 +1,35 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+// Helpers shared by the integration tests, not every test uses all of them.
+#![allow(dead_code)]
+
+use ntt_cuda::NttField;
+
+// Deterministic pseudo-random elements, distinct for distinct seeds.
+pub fn sample<F: NttField>(len: usize, seed: u64) -> Vec<F> {
+    (0..len as u64)
+        .map(|i| F::from_u64((i + seed).wrapping_mul(0x9e3779b97f4a7c15) >> 8))
+        .collect()
+}
+
+// Call $check::<F>() for each field enabled by the crate's features.
+macro_rules! for_each_field {
+    ($check:ident) => {
+        #[cfg(feature = "bls12_377")]
+        $check::<ntt_cuda::fields::Bls12_377>();
+        #[cfg(feature = "bls12_381")]
+        $check::<ntt_cuda::fields::Bls12_381>();
+        #[cfg(feature = "pallas")]
+        $check::<ntt_cuda::fields::Pallas>();
+        #[cfg(feature = "vesta")]
+        $check::<ntt_cuda::fields::Vesta>();
+        #[cfg(feature = "bn254")]
+        $check::<ntt_cuda::fields::Bn254>();
+        #[cfg(feature = "gl64")]
+        $check::<ntt_cuda::fields::Goldilocks>();
+        #[cfg(feature = "bb31")]
+        $check::<ntt_cuda::fields::BabyBear>();
+    };
+}
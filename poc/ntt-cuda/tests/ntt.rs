@@ -2,42 +2,44 @@
 // Licensed under the Apache License, Version 2.0, see LICENSE for details.
 // SPDX-License-Identifier: Apache-2.0
 
-use ntt_cuda::NTTInputOutputOrder;
+#[macro_use]
+mod common;
 
-const DEFAULT_GPU: usize = 0;
+use ntt_cuda::{Device, NTTInputOutputOrder};
 
 #[test]
 #[cfg(feature = "gl64")]
 fn gl64_self_consistency() {
+    use ntt_cuda::fields;
     use rand::random;
 
-    fn random_fr() -> u64 {
+    fn random_fr() -> fields::Goldilocks {
         let fr: u64 = random();
-        fr % 0xffffffff00000001
+        fields::Goldilocks::from_raw(fr % 0xffffffff00000001)
     }
 
     for lg_domain_size in 1..28 {
         let domain_size = 1usize << lg_domain_size;
 
-        let v: Vec<u64> = (0..domain_size).map(|_| random_fr()).collect();
+        let v: Vec<_> = (0..domain_size).map(|_| random_fr()).collect();
 
         let mut vtest1 = v.clone();
         let mut vtest2 = v.clone();
 
-        ntt_cuda::NTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::NN);
+        ntt_cuda::NTT(Device::default(), &mut vtest1, NTTInputOutputOrder::NN);
 
-        ntt_cuda::NTT(DEFAULT_GPU, &mut vtest2, NTTInputOutputOrder::RR);
+        ntt_cuda::NTT(Device::default(), &mut vtest2, NTTInputOutputOrder::RR);
         assert!(vtest1 == vtest2);
 
-        ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::NN);
+        ntt_cuda::iNTT(Device::default(), &mut vtest1, NTTInputOutputOrder::NN);
 
-        ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest2, NTTInputOutputOrder::RR);
+        ntt_cuda::iNTT(Device::default(), &mut vtest2, NTTInputOutputOrder::RR);
         assert!(v == vtest1);
         assert!(vtest1 == vtest2);
 
-        ntt_cuda::NTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::NR);
+        ntt_cuda::NTT(Device::default(), &mut vtest1, NTTInputOutputOrder::NR);
 
-        ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::RN);
+        ntt_cuda::iNTT(Device::default(), &mut vtest1, NTTInputOutputOrder::RN);
         assert!(v == vtest1);
     }
 }
@@ -45,35 +47,36 @@ fn gl64_self_consistency() {
 #[test]
 #[cfg(feature = "bb31")]
 fn bb31_self_consistency() {
+    use ntt_cuda::fields;
     use rand::random;
 
-    fn random_fr() -> u32 {
+    fn random_fr() -> fields::BabyBear {
         let fr: u32 = random();
-        fr % 0x78000001
+        fields::BabyBear::from_raw(fr % 0x78000001)
     }
 
     for lg_domain_size in 1..27 {
         let domain_size = 1usize << lg_domain_size;
 
-        let v: Vec<u32> = (0..domain_size).map(|_| random_fr()).collect();
+        let v: Vec<_> = (0..domain_size).map(|_| random_fr()).collect();
 
         let mut vtest1 = v.clone();
         let mut vtest2 = v.clone();
 
-        ntt_cuda::NTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::NN);
+        ntt_cuda::NTT(Device::default(), &mut vtest1, NTTInputOutputOrder::NN);
 
-        ntt_cuda::NTT(DEFAULT_GPU, &mut vtest2, NTTInputOutputOrder::RR);
+        ntt_cuda::NTT(Device::default(), &mut vtest2, NTTInputOutputOrder::RR);
         assert!(vtest1 == vtest2);
 
-        ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::NN);
+        ntt_cuda::iNTT(Device::default(), &mut vtest1, NTTInputOutputOrder::NN);
 
-        ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest2, NTTInputOutputOrder::RR);
+        ntt_cuda::iNTT(Device::default(), &mut vtest2, NTTInputOutputOrder::RR);
         assert!(v == vtest1);
         assert!(vtest1 == vtest2);
 
-        ntt_cuda::NTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::NR);
+        ntt_cuda::NTT(Device::default(), &mut vtest1, NTTInputOutputOrder::NR);
 
-        ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest1, NTTInputOutputOrder::RN);
+        ntt_cuda::iNTT(Device::default(), &mut vtest1, NTTInputOutputOrder::RN);
         assert!(v == vtest1);
     }
 }
@@ -87,35 +90,55 @@ fn bb31_self_consistency() {
 ))]
 #[test]
 fn test_against_arkworks() {
-    #[cfg(feature = "bls12_377")]
-    use ark_bls12_377::Fr;
-    #[cfg(feature = "bls12_381")]
-    use ark_bls12_381::Fr;
-    #[cfg(feature = "bn254")]
-    use ark_bn254::Fr;
-    #[cfg(feature = "pallas")]
-    use ark_pallas::Fr;
-    #[cfg(feature = "vesta")]
-    use ark_vesta::Fr;
     use ark_ff::{PrimeField, UniformRand};
-    use ark_poly::{
-        domain::DomainCoeff, EvaluationDomain, GeneralEvaluationDomain,
-    };
+    use ark_poly::{domain::DomainCoeff, EvaluationDomain, GeneralEvaluationDomain};
     use ark_std::test_rng;
 
     fn test_ntt<
-        F: PrimeField,
-        T: DomainCoeff<F> + UniformRand + core::fmt::Debug + Eq,
+        F: PrimeField + ntt_cuda::Field<Repr = T::Repr>,
+        T: DomainCoeff<F> + UniformRand + core::fmt::Debug + Eq + ntt_cuda::Field,
         R: ark_std::rand::Rng,
         D: EvaluationDomain<F>,
     >(
         rng: &mut R,
-    ) {
+    ) where
+        T::Repr: ntt_cuda::NttField,
+    {
+        use ntt_cuda::Domain;
+        use sppark::ff::HostField;
+        use NTTInputOutputOrder::{NN, NR, RN};
+
+        // arkworks derives both roots of unity and the coset shift from its
+        // own multiplicative generator. It is GROUP_GEN for all curves but
+        // BN254, where arkworks settled on 5 and ntt/parameters/alt_bn128.h
+        // on 7. Hence BN254 is compared through a Domain with arkworks' root
+        // and shift, the other curves through the compiled conventions.
+        let gen = F::cast_slice(&[F::multiplicative_generator()])[0];
+
         for lg_domain_size in 1..20 + 4 * !cfg!(debug_assertions) as i32 {
             let domain_size = 1usize << lg_domain_size;
 
             let domain = D::new(domain_size).unwrap();
 
+            let lg = lg_domain_size as u32;
+            let root = F::cast_slice(&[domain.element(1)])[0];
+            let one = <T::Repr as HostField>::ONE;
+            let subgroup = Domain::new(lg, root, one).unwrap();
+            let coset = Domain::new(lg, root, gen).unwrap();
+            let native = subgroup == Domain::subgroup(lg).unwrap()
+                && coset == Domain::standard_coset(lg).unwrap();
+
+            let ntt = |v: &mut [T], d: &Domain<T::Repr>, order| match native {
+                false => ntt_cuda::domain_NTT(Device::default(), v, d, order),
+                true if d.shift() == one => ntt_cuda::NTT(Device::default(), v, order),
+                true => ntt_cuda::coset_NTT(Device::default(), v, order),
+            };
+            let intt = |v: &mut [T], d: &Domain<T::Repr>, order| match native {
+                false => ntt_cuda::domain_iNTT(Device::default(), v, d, order),
+                true if d.shift() == one => ntt_cuda::iNTT(Device::default(), v, order),
+                true => ntt_cuda::coset_iNTT(Device::default(), v, order),
+            };
+
             let mut v = vec![];
             for _ in 0..domain_size {
                 v.push(T::rand(rng));
@@ -125,28 +148,913 @@ fn test_against_arkworks() {
             let mut vtest = v.clone();
 
             domain.fft_in_place(&mut v);
-            ntt_cuda::NTT(DEFAULT_GPU, &mut vtest, NTTInputOutputOrder::NN);
+            ntt(&mut vtest, &subgroup, NN);
             assert!(vtest == v);
 
             domain.ifft_in_place(&mut v);
-            ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest, NTTInputOutputOrder::NN);
+            intt(&mut vtest, &subgroup, NN);
             assert!(vtest == v);
 
-            ntt_cuda::NTT(DEFAULT_GPU, &mut vtest, NTTInputOutputOrder::NR);
-            ntt_cuda::iNTT(DEFAULT_GPU, &mut vtest, NTTInputOutputOrder::RN);
+            ntt(&mut vtest, &subgroup, NR);
+            intt(&mut vtest, &subgroup, RN);
             assert!(vtest == v);
 
             domain.coset_fft_in_place(&mut v);
-            ntt_cuda::coset_NTT(DEFAULT_GPU, &mut vtest, NTTInputOutputOrder::NN);
+            ntt(&mut vtest, &coset, NN);
             assert!(vtest == v);
 
             domain.coset_ifft_in_place(&mut v);
-            ntt_cuda::coset_iNTT(DEFAULT_GPU, &mut vtest, NTTInputOutputOrder::NN);
+            intt(&mut vtest, &coset, NN);
             assert!(vtest == v);
         }
     }
 
-    let rng = &mut test_rng();
+    macro_rules! test_ntt {
+        ($fr:ty) => {
+            test_ntt::<$fr, $fr, _, GeneralEvaluationDomain<$fr>>(&mut test_rng())
+        };
+    }
+
+    #[cfg(feature = "bls12_377")]
+    test_ntt!(ark_bls12_377::Fr);
+    #[cfg(feature = "bls12_381")]
+    test_ntt!(ark_bls12_381::Fr);
+    #[cfg(feature = "bn254")]
+    test_ntt!(ark_bn254::Fr);
+    #[cfg(feature = "pallas")]
+    test_ntt!(ark_pallas::Fr);
+    #[cfg(feature = "vesta")]
+    test_ntt!(ark_vesta::Fr);
+}
+
+// More than one field in the same process, as in a recursive proof over a
+// cycle of curves.
+#[cfg(all(feature = "gl64", feature = "bb31"))]
+#[test]
+fn multiple_fields() {
+    use ntt_cuda::{fields, NttField};
+
+    fn round_trip<F: NttField>() -> Vec<F> {
+        let v: Vec<F> = (0..1u64 << 10).map(|i| F::from_u64(i * i + 1)).collect();
+        let mut vtest = v.clone();
+
+        ntt_cuda::NTT(Device::default(), &mut vtest, NTTInputOutputOrder::NN);
+        assert!(vtest != v);
+        ntt_cuda::iNTT(Device::default(), &mut vtest, NTTInputOutputOrder::NN);
+        assert!(vtest == v);
+
+        ntt_cuda::NTT(Device::default(), &mut vtest, NTTInputOutputOrder::NN);
+        vtest
+    }
+
+    let gl64 = round_trip::<fields::Goldilocks>();
+    let bb31 = round_trip::<fields::BabyBear>();
+    // DC term is the sum of the inputs, small enough for either field
+    let sum = (0..1u64 << 10).map(|i| i * i + 1).sum::<u64>();
+    assert_eq!(gl64[0].to_raw(), sum);
+    assert_eq!(bb31[0].to_canonical() as u64, sum);
+}
+
+#[test]
+fn invalid_length() {
+    use ntt_cuda::NttField;
+
+    fn invalid_length<F: NttField>() {
+        let mut v = vec![F::default(); 3];
+
+        let err =
+            ntt_cuda::try_NTT(Device::default(), &mut v, NTTInputOutputOrder::NN).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        assert!(err.is_invalid_argument());
+
+        let mut v: Vec<F> = vec![];
+        let err =
+            ntt_cuda::try_iNTT(Device::default(), &mut v, NTTInputOutputOrder::NN).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+    }
+
+    for_each_field!(invalid_length);
+}
+
+#[test]
+fn lde() {
+    use ntt_cuda::NttField;
+
+    fn check_lde<F: NttField>() {
+        for (lg_domain_size, lg_blowup) in [(0, 2), (3, 0), (5, 1), (10, 3)] {
+            let len = 1usize << lg_domain_size;
+            let input: Vec<F> = (0..len as u64).map(|i| F::from_u64(i * i + 7)).collect();
+
+            for ext_pow in [false, true] {
+                let ext = ntt_cuda::lde(Device::default(), &input, lg_blowup, ext_pow);
+                assert_eq!(ext.len(), len << lg_blowup);
+
+                // CPU reference
+                let mut expected = input.repeat(1 << lg_blowup);
+                sppark::ntt::lde(&mut expected, lg_blowup, ext_pow).unwrap();
+                assert!(ext == expected);
+
+                let mut inout = vec![F::default(); len << lg_blowup];
+                inout[..len].copy_from_slice(&input);
+                ntt_cuda::lde_in_place(Device::default(), &mut inout, lg_blowup, ext_pow);
+                assert!(inout == ext);
+
+                // the original subgroup's coset is a subset of the extended one's
+                if !ext_pow {
+                    let mut coset = input.clone();
+                    ntt_cuda::iNTT(Device::default(), &mut coset, NTTInputOutputOrder::NN);
+                    ntt_cuda::coset_NTT(Device::default(), &mut coset, NTTInputOutputOrder::NN);
+                    let every_nth = ext.iter().step_by(1 << lg_blowup).copied();
+                    assert!(every_nth.eq(coset));
+                }
+            }
+        }
+
+        let mut v = vec![F::default(); 8];
+        let err = ntt_cuda::try_lde_in_place(Device::default(), &mut v, 4, false).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err = ntt_cuda::try_lde(Device::default(), &v[..3], 1, false).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let err = ntt_cuda::try_lde(Device::default(), &v, F::S, false).unwrap_err();
+        assert_eq!(err.code, sppark::Error::DOMAIN_TOO_LARGE);
+    }
+
+    for_each_field!(check_lde);
+}
+
+// NTT::LDE used to take |ext_pow| for whether to shift at all, and with
+// ext_pow = false returned evaluations over the extended subgroup itself.
+#[test]
+fn lde_shifts_by_group_gen() {
+    use ntt_cuda::NttField;
+
+    fn check_lde_shift<F: NttField>() {
+        let (lg_domain_size, lg_blowup) = (4, 2);
+        let len = 1usize << lg_domain_size;
+        let input: Vec<F> = common::sample(len, 11);
+
+        let mut coeffs = vec![F::default(); len << lg_blowup];
+        coeffs[..len].copy_from_slice(&input);
+        ntt_cuda::iNTT(
+            Device::default(),
+            &mut coeffs[..len],
+            NTTInputOutputOrder::NN,
+        );
+
+        let mut unshifted = coeffs.clone();
+        ntt_cuda::NTT(Device::default(), &mut unshifted, NTTInputOutputOrder::NN);
+
+        let ext = ntt_cuda::lde(Device::default(), &input, lg_blowup, false);
+        let mut expected = coeffs.clone();
+        ntt_cuda::coset_NTT(Device::default(), &mut expected, NTTInputOutputOrder::NN);
+        assert!(ext == expected);
+        assert!(ext != unshifted);
+
+        let ext = ntt_cuda::lde(Device::default(), &input, lg_blowup, true);
+        let shift = F::GROUP_GEN.pow(1 << lg_blowup);
+        let mut expected = coeffs;
+        ntt_cuda::coset_NTT_with_shift(
+            Device::default(),
+            &mut expected,
+            shift,
+            NTTInputOutputOrder::NN,
+        );
+        assert!(ext == expected);
+    }
+
+    for_each_field!(check_lde_shift);
+}
+
+#[test]
+fn batch() {
+    use ntt_cuda::{NTTLayout, NttField};
+    use sppark::ntt::{Direction, InputOutputOrder, Layout, Type};
+
+    fn check_batch<F: NttField>() {
+        let lg_domain_size = 8;
+        let domain_size = 1usize << lg_domain_size;
+        let ncolumns = 5;
+        let columns: Vec<Vec<F>> = (0..ncolumns as u64)
+            .map(|j| {
+                (0..domain_size as u64)
+                    .map(|i| F::from_u64(i * j + i + 3))
+                    .collect()
+            })
+            .collect();
+
+        let mut expected = columns.clone();
+        for column in expected.iter_mut() {
+            ntt_cuda::NTT(Device::default(), column, NTTInputOutputOrder::NR);
+        }
+
+        for row_major in [false, true] {
+            let layout = || match row_major {
+                false => NTTLayout::ColumnMajor,
+                true => NTTLayout::RowMajor,
+            };
+            let interleave = |cols: &[Vec<F>]| -> Vec<F> {
+                match row_major {
+                    false => cols.concat(),
+                    true => (0..domain_size * ncolumns)
+                        .map(|k| cols[k % ncolumns][k / ncolumns])
+                        .collect(),
+                }
+            };
+
+            let input = interleave(&columns);
+            let mut inout = input.clone();
+            ntt_cuda::NTT_batch(
+                Device::default(),
+                &mut inout,
+                lg_domain_size,
+                ncolumns,
+                layout(),
+                NTTInputOutputOrder::NR,
+            );
+            assert!(inout == interleave(&expected));
+
+            // CPU reference
+            let mut reference = input.clone();
+            sppark::ntt::compute_ntt_batch(
+                &mut reference,
+                lg_domain_size,
+                ncolumns,
+                if row_major {
+                    Layout::RowMajor
+                } else {
+                    Layout::ColumnMajor
+                },
+                InputOutputOrder::NR,
+                Direction::Forward,
+                Type::Standard,
+            )
+            .unwrap();
+            assert!(inout == reference);
+
+            ntt_cuda::iNTT_batch(
+                Device::default(),
+                &mut inout,
+                lg_domain_size,
+                ncolumns,
+                layout(),
+                NTTInputOutputOrder::RN,
+            );
+            assert!(inout == input);
+
+            ntt_cuda::coset_NTT_batch(
+                Device::default(),
+                &mut inout,
+                lg_domain_size,
+                ncolumns,
+                layout(),
+                NTTInputOutputOrder::NN,
+            );
+            ntt_cuda::coset_iNTT_batch(
+                Device::default(),
+                &mut inout,
+                lg_domain_size,
+                ncolumns,
+                layout(),
+                NTTInputOutputOrder::NN,
+            );
+            assert!(inout == input);
+        }
+
+        let mut v = vec![F::default(); 24];
+        let err = ntt_cuda::try_NTT_batch(
+            Device::default(),
+            &mut v,
+            3,
+            4,
+            NTTLayout::ColumnMajor,
+            NTTInputOutputOrder::NN,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+    }
+
+    for_each_field!(check_batch);
+}
+
+#[test]
+fn coset_with_shift() {
+    use ntt_cuda::NttField;
+    use sppark::ntt::{Direction, InputOutputOrder};
+
+    fn check_coset<F: NttField>() {
+        let input: Vec<F> = (0..1u64 << 9).map(|i| F::from_u64(i * 3 + 1)).collect();
+
+        // alternate between a few shifts to exercise the cache
+        for shift in [3u64, 5, 3, 1 << 20, 5] {
+            let shift = F::from_u64(shift);
+
+            let mut inout = input.clone();
+            ntt_cuda::coset_NTT_with_shift(
+                Device::default(),
+                &mut inout,
+                shift,
+                NTTInputOutputOrder::NR,
+            );
+
+            // CPU reference
+            let mut expected = input.clone();
+            sppark::ntt::compute_coset_ntt(
+                &mut expected,
+                shift,
+                InputOutputOrder::NR,
+                Direction::Forward,
+            )
+            .unwrap();
+            assert!(inout == expected);
+
+            ntt_cuda::coset_iNTT_with_shift(
+                Device::default(),
+                &mut inout,
+                shift,
+                NTTInputOutputOrder::RN,
+            );
+            assert!(inout == input);
+        }
+
+        // the group generator is just another shift
+        let mut a = input.clone();
+        ntt_cuda::coset_NTT(Device::default(), &mut a, NTTInputOutputOrder::NN);
+        let mut b = input.clone();
+        ntt_cuda::coset_NTT_with_shift(
+            Device::default(),
+            &mut b,
+            F::GROUP_GEN,
+            NTTInputOutputOrder::NN,
+        );
+        assert!(a == b);
+
+        let mut v = vec![F::default(); 3];
+        let err = ntt_cuda::try_coset_NTT_with_shift(
+            Device::default(),
+            &mut v,
+            F::ONE,
+            NTTInputOutputOrder::NN,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+    }
+
+    for_each_field!(check_coset);
+}
+
+#[test]
+fn device_vec() {
+    use ntt_cuda::{dev, DeviceVec, NttField};
+
+    fn check_dev<F: NttField>() {
+        let input: Vec<F> = (0..1u64 << 8).map(|i| F::from_u64(i * 5 + 2)).collect();
+
+        // chain of transforms without leaving the device...
+        let mut v = DeviceVec::from_host(Device::default(), &input).unwrap();
+        dev::NTT(&mut v, NTTInputOutputOrder::NR);
+        dev::bit_reverse(&mut v);
+        dev::coset_iNTT(&mut v, NTTInputOutputOrder::NN);
+        dev::coset_NTT(&mut v, NTTInputOutputOrder::NR);
+        dev::iNTT(&mut v, NTTInputOutputOrder::RN);
+        let chained = v.to_host().unwrap();
+
+        // ... matches the same steps through host memory
+        let mut expected = input.clone();
+        ntt_cuda::NTT(Device::default(), &mut expected, NTTInputOutputOrder::NN);
+        ntt_cuda::coset_iNTT(Device::default(), &mut expected, NTTInputOutputOrder::NN);
+        ntt_cuda::coset_NTT(Device::default(), &mut expected, NTTInputOutputOrder::NR);
+        ntt_cuda::iNTT(Device::default(), &mut expected, NTTInputOutputOrder::RN);
+        assert!(chained == expected);
+
+        for (lg_blowup, ext_pow) in [(0, false), (2, false), (3, true)] {
+            let mut v = DeviceVec::zeroed(Device::default(), input.len() << lg_blowup).unwrap();
+            let mut host = v.to_host().unwrap();
+            host[..input.len()].copy_from_slice(&input);
+            v.upload(&host).unwrap();
+            dev::lde(&mut v, lg_blowup, ext_pow);
+            assert!(
+                v.to_host().unwrap()
+                    == ntt_cuda::lde(Device::default(), &input, lg_blowup, ext_pow)
+            );
+        }
+
+        let mut v = DeviceVec::<F>::zeroed(Device::default(), 12).unwrap();
+        let err = dev::try_NTT(&mut v, NTTInputOutputOrder::NN).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let err = dev::try_bit_reverse(&mut v).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let mut v = DeviceVec::<F>::zeroed(Device::default(), 4).unwrap();
+        let err = dev::try_lde(&mut v, 3, false).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+    }
+
+    for_each_field!(check_dev);
+}
+
+#[test]
+fn domain_too_large() {
+    use ntt_cuda::{NTTLayout, NttField};
+
+    fn check_limits<F: NttField>() {
+        assert!(F::MAX_LG_DOMAIN_SIZE <= F::TWO_ADICITY);
+
+        // rejected before anything is allocated
+        let v = vec![F::default(); 8];
+        let err =
+            ntt_cuda::try_lde(Device::default(), &v, F::MAX_LG_DOMAIN_SIZE, false).unwrap_err();
+        let details = err.as_domain_too_large().unwrap();
+        assert_eq!(details.requested, F::MAX_LG_DOMAIN_SIZE + 3);
+        assert_eq!(details.max, F::MAX_LG_DOMAIN_SIZE);
+
+        let err = ntt_cuda::try_NTT_batch(
+            Device::default(),
+            &mut Vec::<F>::new(),
+            F::MAX_LG_DOMAIN_SIZE + 1,
+            0,
+            NTTLayout::ColumnMajor,
+            NTTInputOutputOrder::NN,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, sppark::Error::DOMAIN_TOO_LARGE);
+        assert_eq!(
+            err.as_domain_too_large().unwrap().requested,
+            F::MAX_LG_DOMAIN_SIZE + 1
+        );
+    }
+
+    for_each_field!(check_limits);
+}
+
+#[test]
+fn four_step() {
+    use ntt_cuda::NttField;
+
+    fn check_four_step<F: NttField>() {
+        for lg_domain_size in [1, 6, 11] {
+            let input: Vec<F> = (0..1u64 << lg_domain_size)
+                .map(|i| F::from_u64(i * i + 7))
+                .collect();
+
+            for order in [NTTInputOutputOrder::NN, NTTInputOutputOrder::NR] {
+                let mut expected = input.clone();
+                let mut inout = input.clone();
+                match order {
+                    NTTInputOutputOrder::NN => {
+                        ntt_cuda::NTT(Device::default(), &mut expected, NTTInputOutputOrder::NN);
+                        ntt_cuda::NTT_four_step(&[], &mut inout, NTTInputOutputOrder::NN);
+                    }
+                    _ => {
+                        ntt_cuda::coset_NTT(
+                            Device::default(),
+                            &mut expected,
+                            NTTInputOutputOrder::NR,
+                        );
+                        ntt_cuda::coset_NTT_four_step(&[], &mut inout, NTTInputOutputOrder::NR);
+                    }
+                }
+                assert!(inout == expected);
+            }
+
+            let mut inout = input.clone();
+            let devices = [Device::default(); 3];
+            ntt_cuda::NTT_four_step(&devices, &mut inout, NTTInputOutputOrder::RN);
+            ntt_cuda::iNTT_four_step(&devices, &mut inout, NTTInputOutputOrder::NR);
+            assert!(inout == input);
+            ntt_cuda::coset_NTT_four_step(&devices, &mut inout, NTTInputOutputOrder::NN);
+            ntt_cuda::coset_iNTT_four_step(&devices, &mut inout, NTTInputOutputOrder::NN);
+            assert!(inout == input);
+        }
+
+        let mut v = vec![F::default(); 24];
+        let err = ntt_cuda::try_NTT_four_step(&[], &mut v, NTTInputOutputOrder::NN).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+    }
+
+    for_each_field!(check_four_step);
+}
+
+#[test]
+fn bit_reverse() {
+    use ntt_cuda::{dev, DeviceVec, NttField};
+
+    fn check_bit_reverse<F: NttField>() {
+        for (lg_domain_size, ncolumns) in [(0, 3), (1, 1), (6, 4), (12, 2)] {
+            let input: Vec<F> = (0..ncolumns << lg_domain_size)
+                .map(|i| F::from_u64(i as u64 * 11 + 3))
+                .collect();
+
+            // CPU reference
+            let mut expected = input.clone();
+            sppark::ntt::bit_rev_batch(&mut expected, lg_domain_size, ncolumns).unwrap();
+
+            let mut inout = input.clone();
+            ntt_cuda::bit_reverse_batch(Device::default(), &mut inout, lg_domain_size, ncolumns);
+            assert!(inout == expected);
+
+            let mut out = vec![F::default(); input.len()];
+            ntt_cuda::bit_reverse_batch_into(
+                Device::default(),
+                &mut out,
+                &input,
+                lg_domain_size,
+                ncolumns,
+            );
+            assert!(out == expected);
+
+            let domain = &input[..1 << lg_domain_size];
+            let mut single = domain.to_vec();
+            ntt_cuda::bit_reverse(Device::default(), &mut single);
+            assert!(single == expected[..domain.len()]);
+            ntt_cuda::bit_reverse_into(Device::default(), &mut out[..domain.len()], &single);
+            assert!(out[..domain.len()] == *domain);
+
+            // same on device-resident data
+            let mut v = DeviceVec::from_host(Device::default(), &input).unwrap();
+            dev::bit_reverse_batch(&mut v, lg_domain_size, ncolumns);
+            assert!(v.to_host().unwrap() == expected);
+            let mut w = DeviceVec::zeroed(Device::default(), input.len()).unwrap();
+            dev::bit_reverse_batch_into(&mut w, &v, lg_domain_size, ncolumns);
+            assert!(w.to_host().unwrap() == input);
+        }
+
+        let mut v = vec![F::default(); 12];
+        let err = ntt_cuda::try_bit_reverse(Device::default(), &mut v).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let err = ntt_cuda::try_bit_reverse_batch(Device::default(), &mut v, 2, 4).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err =
+            ntt_cuda::try_bit_reverse_batch_into(Device::default(), &mut v, &[], 2, 3).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+    }
+
+    for_each_field!(check_bit_reverse);
+}
+
+#[test]
+fn custom_domain() {
+    use ntt_cuda::{Domain, NTTLayout, NttField};
+
+    // Horner's rule at each point of the domain
+    fn evaluate<F: NttField>(coeffs: &[F], domain: &Domain<F>) -> Vec<F> {
+        (0..domain.size())
+            .map(|j| {
+                let x = domain.element(j);
+                coeffs.iter().rev().fold(F::ZERO, |acc, c| acc * x + *c)
+            })
+            .collect()
+    }
+
+    fn reversed<F: NttField>(v: &[F]) -> Vec<F> {
+        let mut v = v.to_vec();
+        sppark::ntt::bit_rev(&mut v);
+        v
+    }
+
+    fn check_domain<F: NttField>() {
+        for lg in [1, 4, 8] {
+            let len = 1usize << lg;
+            let coeffs: Vec<F> = (0..3 * len as u64)
+                .map(|i| F::from_u64(i * i + 7))
+                .collect();
+            let root = F::root_of_unity(lg, false);
+
+            for k in [1, 3, len - 1] {
+                for shift in [F::ONE, F::GROUP_GEN, F::from_u64(7)] {
+                    let domain = Domain::new(lg, root.pow(k as u64), shift).unwrap();
+                    let input = &coeffs[..len];
+                    let expected = evaluate(input, &domain);
+
+                    let mut inout = input.to_vec();
+                    ntt_cuda::domain_NTT(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        NTTInputOutputOrder::NN,
+                    );
+                    assert!(inout == expected);
+                    ntt_cuda::domain_iNTT(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        NTTInputOutputOrder::NN,
+                    );
+                    assert!(inout == input);
+
+                    ntt_cuda::domain_NTT(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        NTTInputOutputOrder::NR,
+                    );
+                    assert!(inout == reversed(&expected));
+                    ntt_cuda::domain_iNTT(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        NTTInputOutputOrder::RN,
+                    );
+                    assert!(inout == input);
+
+                    let mut inout = reversed(input);
+                    ntt_cuda::domain_NTT(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        NTTInputOutputOrder::RN,
+                    );
+                    assert!(inout == expected);
+                    ntt_cuda::domain_iNTT(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        NTTInputOutputOrder::NR,
+                    );
+                    assert!(inout == reversed(input));
+
+                    // batches, polynomials one after another and interleaved
+                    let expected: Vec<F> = coeffs
+                        .chunks(len)
+                        .flat_map(|c| evaluate(c, &domain))
+                        .collect();
+                    let mut inout = coeffs.clone();
+                    ntt_cuda::domain_NTT_batch(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        3,
+                        NTTLayout::ColumnMajor,
+                        NTTInputOutputOrder::NN,
+                    );
+                    assert!(inout == expected);
+                    ntt_cuda::domain_iNTT_batch(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        3,
+                        NTTLayout::ColumnMajor,
+                        NTTInputOutputOrder::NN,
+                    );
+                    assert!(inout == coeffs);
+
+                    let interleave = |v: &[F]| -> Vec<F> {
+                        (0..v.len()).map(|i| v[(i % 3) * len + i / 3]).collect()
+                    };
+                    let mut inout = interleave(&coeffs);
+                    ntt_cuda::domain_NTT_batch(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        3,
+                        NTTLayout::RowMajor,
+                        NTTInputOutputOrder::NR,
+                    );
+                    let rows: Vec<F> = expected.chunks(len).flat_map(reversed).collect();
+                    assert!(inout == interleave(&rows));
+                    ntt_cuda::domain_iNTT_batch(
+                        Device::default(),
+                        &mut inout,
+                        &domain,
+                        3,
+                        NTTLayout::RowMajor,
+                        NTTInputOutputOrder::RN,
+                    );
+                    assert!(inout == interleave(&coeffs));
+                }
+
+                // extension from the subgroup of the domain's root squared,
+                // or raised to the 4th power
+                for lg_blowup in (1..=2).filter(|&b| b <= lg) {
+                    let input = &coeffs[..len >> lg_blowup];
+                    let subroot = root.pow((k << lg_blowup) as u64);
+                    let subgroup = Domain::new(lg - lg_blowup, subroot, F::ONE).unwrap();
+                    let gen = F::GROUP_GEN;
+                    for shift in [gen, gen.pow(1 << lg_blowup), F::from_u64(7)] {
+                        let domain = Domain::new(lg, root.pow(k as u64), shift).unwrap();
+                        let mut inout = evaluate(input, &subgroup);
+                        inout.resize(len, F::ZERO);
+                        ntt_cuda::domain_lde(Device::default(), &mut inout, &domain, lg_blowup);
+                        assert!(inout == evaluate(input, &domain));
+                    }
+                }
+            }
+        }
+
+        let domain = Domain::standard_coset(4).unwrap();
+        let mut v = vec![F::ZERO; 8];
+        let err =
+            ntt_cuda::try_domain_NTT(Device::default(), &mut v, &domain, NTTInputOutputOrder::NN)
+                .unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err = ntt_cuda::try_domain_lde(Device::default(), &mut v, &domain, 1).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let root = F::root_of_unity(4, false);
+        let err = Domain::new(4, root.sqr(), F::ONE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::BAD_ROOT_OF_UNITY);
+    }
+
+    for_each_field!(check_domain);
+}
+
+#[cfg(any(feature = "gl64", feature = "bb31"))]
+#[test]
+fn extension_fields() {
+    use ntt_cuda::{ExtensionField, NttField};
+    use sppark::ff::HostField;
+    use sppark::ntt::NTTParameters;
+
+    // Horner's rule at base-field points shift·ω^j
+    fn evaluate<E>(coeffs: &[E], lg_domain_size: u32, shift: E::Base) -> Vec<E>
+    where
+        E: ExtensionField,
+        E::Base: NttField,
+    {
+        let root = E::Base::root_of_unity(lg_domain_size, false);
+        (0..1u64 << lg_domain_size)
+            .map(|j| {
+                let x = E::from_base(shift * root.pow(j));
+                coeffs.iter().rev().fold(E::ZERO, |acc, c| acc * x + *c)
+            })
+            .collect()
+    }
+
+    fn check_ext<E>()
+    where
+        E: ExtensionField,
+        E::Base: NttField,
+    {
+        let gen = E::Base::GROUP_GEN;
+        for lg in [0, 1, 5, 10] {
+            let len = 1usize << lg;
+            let coeffs: Vec<E> = (0..len as u64)
+                .map(|i| E::from_u64(i * i + 7) * E::from_u64(i + 3).reciprocal())
+                .collect();
+            let expected = evaluate(&coeffs, lg, E::Base::ONE);
+
+            let mut inout = coeffs.clone();
+            ntt_cuda::ext_NTT(Device::default(), &mut inout, NTTInputOutputOrder::NN);
+            assert!(inout == expected);
+            ntt_cuda::ext_iNTT(Device::default(), &mut inout, NTTInputOutputOrder::NN);
+            assert!(inout == coeffs);
+
+            // same as transforming each coefficient on its own
+            let mut inout = coeffs.clone();
+            ntt_cuda::ext_NTT(Device::default(), &mut inout, NTTInputOutputOrder::NR);
+            for d in 0..E::DEGREE {
+                let mut column: Vec<E::Base> = E::as_base_slice(&coeffs)
+                    .iter()
+                    .skip(d)
+                    .step_by(E::DEGREE)
+                    .copied()
+                    .collect();
+                ntt_cuda::NTT(Device::default(), &mut column, NTTInputOutputOrder::NR);
+                let got = E::as_base_slice(&inout).iter().skip(d).step_by(E::DEGREE);
+                assert!(got.eq(column.iter()));
+            }
+            ntt_cuda::ext_iNTT(Device::default(), &mut inout, NTTInputOutputOrder::RN);
+            assert!(inout == coeffs);
+
+            let mut inout = coeffs.clone();
+            ntt_cuda::ext_coset_NTT(Device::default(), &mut inout, NTTInputOutputOrder::NN);
+            assert!(inout == evaluate(&coeffs, lg, gen));
+            ntt_cuda::ext_coset_iNTT(Device::default(), &mut inout, NTTInputOutputOrder::NN);
+            assert!(inout == coeffs);
+
+            for lg_blowup in [0, 2] {
+                for ext_pow in [false, true] {
+                    let ext = ntt_cuda::ext_lde(Device::default(), &expected, lg_blowup, ext_pow);
+                    let shift = if ext_pow {
+                        gen.pow(1 << lg_blowup)
+                    } else {
+                        gen
+                    };
+                    assert!(ext == evaluate(&coeffs, lg + lg_blowup, shift));
+                }
+            }
+        }
+
+        let mut v = vec![E::ZERO; 3];
+        let err =
+            ntt_cuda::try_ext_NTT(Device::default(), &mut v, NTTInputOutputOrder::NN).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let err = ntt_cuda::try_ext_lde(Device::default(), &v[..2], E::Base::S, false).unwrap_err();
+        assert_eq!(err.code, sppark::Error::DOMAIN_TOO_LARGE);
+    }
+
+    #[cfg(feature = "gl64")]
+    check_ext::<ntt_cuda::fields::GoldilocksExt2>();
+    #[cfg(feature = "bb31")]
+    check_ext::<ntt_cuda::fields::BabyBearExt4>();
+}
+
+#[test]
+fn fri_fold() {
+    use ntt_cuda::NttField;
+    use sppark::ff::HostField;
+
+    // Σ β^r·f_r, f_r's coefficients being every arity-th one of f's
+    fn fold_coeffs<F: HostField>(coeffs: &[F], arity: usize, challenge: F) -> Vec<F> {
+        coeffs
+            .chunks(arity)
+            .map(|c| c.iter().rev().fold(F::ZERO, |acc, x| acc * challenge + *x))
+            .collect()
+    }
+
+    fn check_fri<F: NttField>() {
+        let challenge = F::from_u64(0x1234_5678_9abc);
+        for lg in [1, 5, 10] {
+            let len = 1usize << lg;
+            let coeffs: Vec<F> = (0..len as u64).map(|i| F::from_u64(i * i + 7)).collect();
+
+            for shift in [F::ONE, F::GROUP_GEN, F::from_u64(5)] {
+                let mut evals = coeffs.clone();
+                ntt_cuda::coset_NTT_with_shift(
+                    Device::default(),
+                    &mut evals,
+                    shift,
+                    NTTInputOutputOrder::NR,
+                );
+
+                for arity in [1, 2, 4, 16].into_iter().filter(|&a| a <= len) {
+                    let mut folded =
+                        ntt_cuda::fri_fold(Device::default(), &evals, lg, arity, challenge, shift);
+                    assert_eq!(folded.len(), len / arity);
+
+                    // interpolate the next layer over its coset
+                    ntt_cuda::coset_iNTT_with_shift(
+                        Device::default(),
+                        &mut folded,
+                        shift.pow(arity as u64),
+                        NTTInputOutputOrder::RN,
+                    );
+                    assert!(folded == fold_coeffs(&coeffs, arity, challenge));
+                }
+            }
+        }
+
+        let evals = vec![F::ONE; 8];
+        let err =
+            ntt_cuda::try_fri_fold(Device::default(), &evals, 3, 3, F::ONE, F::ONE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        assert_eq!(String::from(&err), "folding_arity is not power of 2: 3");
+        let err =
+            ntt_cuda::try_fri_fold(Device::default(), &evals, 3, 16, F::ONE, F::ONE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err =
+            ntt_cuda::try_fri_fold(Device::default(), &evals, 4, 2, F::ONE, F::ONE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err =
+            ntt_cuda::try_fri_fold(Device::default(), &evals, 3, 2, F::ONE, F::ZERO).unwrap_err();
+        assert_eq!(err.code, sppark::Error::DIVISION_BY_ZERO);
+    }
+
+    #[cfg(any(feature = "gl64", feature = "bb31"))]
+    fn check_ext_fri<E>()
+    where
+        E: ntt_cuda::ExtensionField,
+        E::Base: NttField,
+    {
+        use sppark::ntt::NTTParameters;
+
+        let challenge = E::from_u64(3).reciprocal() + E::from_u64(0x1234_5678_9abc);
+        let gen = E::Base::GROUP_GEN;
+        for lg in [1, 6] {
+            let len = 1usize << lg;
+            let coeffs: Vec<E> = (0..len as u64)
+                .map(|i| E::from_u64(i + 7) * E::from_u64(i * i + 1).reciprocal())
+                .collect();
+            let mut evals = coeffs.clone();
+            ntt_cuda::ext_coset_NTT(Device::default(), &mut evals, NTTInputOutputOrder::NR);
+
+            for arity in [1, 2, 8].into_iter().filter(|&a| a <= len) {
+                let folded =
+                    ntt_cuda::ext_fri_fold(Device::default(), &evals, lg, arity, challenge, gen);
+
+                // Horner's rule at gen^arity·ω^bit_reverse(i)
+                let expected_coeffs = fold_coeffs(&coeffs, arity, challenge);
+                let lg_next = lg - arity.trailing_zeros();
+                let root = E::Base::root_of_unity(lg_next, false);
+                let expected: Vec<E> = (0..len / arity)
+                    .map(|i| {
+                        let rev = match lg_next {
+                            0 => 0,
+                            _ => i.reverse_bits() >> (usize::BITS - lg_next),
+                        };
+                        let x = E::from_base(gen.pow(arity as u64) * root.pow(rev as u64));
+                        expected_coeffs
+                            .iter()
+                            .rev()
+                            .fold(E::ZERO, |acc, c| acc * x + *c)
+                    })
+                    .collect();
+                assert!(folded == expected);
+            }
+        }
+    }
+
+    for_each_field!(check_fri);
 
-    test_ntt::<Fr, Fr, _, GeneralEvaluationDomain<Fr>>(rng);
+    #[cfg(feature = "gl64")]
+    check_ext_fri::<ntt_cuda::fields::GoldilocksExt2>();
+    #[cfg(feature = "bb31")]
+    check_ext_fri::<ntt_cuda::fields::BabyBearExt4>();
 }
@@ -0,0 +1,239 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+#[macro_use]
+mod common;
+
+use common::sample;
+use ntt_cuda::{poly, Device, NTTInputOutputOrder, NttField};
+
+fn schoolbook<F: NttField>(a: &[F], b: &[F]) -> Vec<F> {
+    let mut ret = vec![F::ZERO; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            ret[i + j] += *x * *y;
+        }
+    }
+    ret
+}
+
+#[test]
+fn mul_and_square() {
+    fn check_mul<F: NttField>() {
+        for (m, n) in [(1, 1), (1, 7), (5, 12), (64, 65), (100, 28)] {
+            let a = sample::<F>(m, 1);
+            let b = sample::<F>(n, 2);
+            let expected = schoolbook(&a, &b);
+            assert!(poly::mul(Device::default(), &a, &b) == expected);
+            assert!(poly::mul(Device::default(), &b, &a) == expected);
+            assert!(poly::square(Device::default(), &a) == schoolbook(&a, &a));
+        }
+        assert!(poly::mul::<F>(Device::default(), &[], &[F::ONE]).is_empty());
+        assert!(poly::square::<F>(Device::default(), &[]).is_empty());
+    }
+
+    for_each_field!(check_mul);
+}
+
+#[test]
+fn divide_by_vanishing() {
+    fn check_divide<F: NttField>() {
+        let (lg_domain_size, lg_blowup) = (5, 2);
+        let (n, len) = (1 << lg_domain_size, 1 << (lg_domain_size + lg_blowup));
+
+        // p = q * (X^n - 1)
+        let q = sample::<F>(len - n, 3);
+        let mut p = vec![F::ZERO; len];
+        for (i, x) in q.iter().enumerate() {
+            p[i] -= *x;
+            p[i + n] += *x;
+        }
+
+        ntt_cuda::coset_NTT(Device::default(), &mut p, NTTInputOutputOrder::NN);
+        poly::divide_by_vanishing(&mut p, lg_domain_size, F::GROUP_GEN);
+        ntt_cuda::coset_iNTT(Device::default(), &mut p, NTTInputOutputOrder::NN);
+        assert!(p[..q.len()] == q[..]);
+        assert!(p[q.len()..].iter().all(|x| *x == F::ZERO));
+
+        // X^n - 1 vanishes over H itself
+        let err = poly::try_divide_by_vanishing(&mut p, lg_domain_size, F::ONE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::DIVISION_BY_ZERO);
+        let err =
+            poly::try_divide_by_vanishing(&mut p[..16], lg_domain_size, F::GROUP_GEN).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+    }
+
+    for_each_field!(check_divide);
+}
+
+#[test]
+fn pointwise_and_extend() {
+    fn check_pointwise<F: NttField>() {
+        let a = sample::<F>(64, 4);
+        let b = sample::<F>(64, 5);
+
+        // evaluations of a product are products of evaluations
+        let mut ea = poly::extend(Device::default(), &a, 1);
+        let eb = poly::extend(Device::default(), &b, 1);
+        poly::pointwise_mul(&mut ea, &eb);
+        ntt_cuda::iNTT(Device::default(), &mut ea, NTTInputOutputOrder::NN);
+        let mut ab = a.clone();
+        ntt_cuda::iNTT(Device::default(), &mut ab, NTTInputOutputOrder::NN);
+        let mut bb = b.clone();
+        ntt_cuda::iNTT(Device::default(), &mut bb, NTTInputOutputOrder::NN);
+        assert!(ea[..127] == schoolbook(&ab, &bb)[..]);
+
+        // extended evaluations agree with the original ones at even points
+        assert!(eb.iter().step_by(2).eq(b.iter()));
+
+        let mut c = a.clone();
+        poly::pointwise_add(&mut c, &b);
+        poly::pointwise_sub(&mut c, &b);
+        assert!(c == a);
+
+        let err = poly::try_pointwise_add(&mut c, &b[1..]).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err = poly::try_extend(Device::default(), &b[1..], 1).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let err = poly::try_extend(Device::default(), &b, F::MAX_LG_DOMAIN_SIZE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::DOMAIN_TOO_LARGE);
+    }
+
+    for_each_field!(check_pointwise);
+}
+
+#[test]
+fn evaluate_lagrange() {
+    fn horner<F: NttField>(coeffs: &[F], point: F) -> F {
+        coeffs.iter().rev().fold(F::ZERO, |acc, c| acc * point + *c)
+    }
+
+    fn check_evaluate<F: NttField>() {
+        let (lg_domain_size, ncolumns) = (7, 3);
+        let coeffs = sample::<F>(ncolumns << lg_domain_size, 6);
+        let domain = poly::Domain::standard_coset(lg_domain_size).unwrap();
+
+        let mut evals = coeffs.clone();
+        for column in evals.chunks_mut(1 << lg_domain_size) {
+            ntt_cuda::coset_NTT(Device::default(), column, NTTInputOutputOrder::NN);
+        }
+
+        let point = F::from_u64(0xdeadbeef);
+        let expected: Vec<F> = coeffs
+            .chunks(1 << lg_domain_size)
+            .map(|c| horner(c, point))
+            .collect();
+        assert!(poly::evaluate_lagrange_batch(&evals, &domain, ncolumns, point) == expected);
+        assert!(
+            poly::evaluate_lagrange(&evals[..1 << lg_domain_size], &domain, point) == expected[0]
+        );
+
+        // in-domain points
+        let point = domain.element(3);
+        assert!(poly::evaluate_lagrange(&evals[..1 << lg_domain_size], &domain, point) == evals[3]);
+
+        let err = poly::try_evaluate_lagrange_batch(&evals, &domain, 2, point).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+    }
+
+    for_each_field!(check_evaluate);
+}
+
+#[test]
+fn batch_inverse() {
+    use ntt_cuda::Zeros;
+
+    fn check_inverse<F: NttField>() {
+        for len in [0, 1, 31, 1000, 5000] {
+            let mut v = sample::<F>(len, 3);
+            for i in (0..len).step_by(7) {
+                v[i] = F::ZERO;
+            }
+            let expected: Vec<F> = v.iter().map(|x| x.reciprocal()).collect();
+            let orig = v.clone();
+
+            if len != 0 {
+                let err = ntt_cuda::try_batch_inverse(Device::default(), &mut v, Zeros::Reject)
+                    .unwrap_err();
+                assert_eq!(err.code, sppark::Error::DIVISION_BY_ZERO);
+                assert!(v == orig);
+            }
+            let nzeros = ntt_cuda::batch_inverse(Device::default(), &mut v, Zeros::Skip);
+            assert_eq!(nzeros, len.div_ceil(7));
+            assert!(v == expected);
+        }
+
+        let mut v = sample::<F>(100, 4);
+        let expected: Vec<F> = v.iter().map(|x| x.reciprocal()).collect();
+        assert_eq!(
+            ntt_cuda::batch_inverse(Device::default(), &mut v, Zeros::Reject),
+            0
+        );
+        assert!(v == expected);
+    }
+
+    for_each_field!(check_inverse);
+}
+
+#[test]
+fn grand_product() {
+    use ntt_cuda::Scan;
+
+    fn check_product<F: NttField>() {
+        for len in [0, 1, 31, 33, 1000, 40000] {
+            let num = sample::<F>(len, 5);
+            let mut den = sample::<F>(len, 6);
+            for d in den.iter_mut().filter(|d| d.is_zero()) {
+                *d = F::ONE;
+            }
+
+            let mut expected = Vec::with_capacity(len);
+            let mut acc = F::ONE;
+            for (n, d) in num.iter().zip(&den) {
+                expected.push(acc);
+                acc *= *n * d.reciprocal();
+            }
+
+            let mut z = num.clone();
+            let total =
+                ntt_cuda::grand_product(Device::default(), &mut z, Some(&den), Scan::Exclusive);
+            assert!(total == acc);
+            assert!(z == expected);
+
+            let mut z = num.clone();
+            let total = ntt_cuda::grand_product(Device::default(), &mut z, None, Scan::Inclusive);
+            let mut acc = F::ONE;
+            for (z, n) in z.iter().zip(&num) {
+                acc *= *n;
+                assert!(*z == acc);
+            }
+            assert!(total == acc);
+        }
+
+        // a permutation closes with 1
+        let num = sample::<F>(100, 7);
+        let den: Vec<F> = num.iter().rev().copied().collect();
+        let mut z = num.clone();
+        let total = ntt_cuda::grand_product(Device::default(), &mut z, Some(&den), Scan::Exclusive);
+        assert!(total == F::ONE);
+
+        let mut z = vec![F::ONE; 8];
+        let mut den = vec![F::ONE; 8];
+        let err = ntt_cuda::try_grand_product(
+            Device::default(),
+            &mut z,
+            Some(&den[1..]),
+            Scan::Inclusive,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        den[3] = F::ZERO;
+        let err =
+            ntt_cuda::try_grand_product(Device::default(), &mut z, Some(&den), Scan::Inclusive)
+                .unwrap_err();
+        assert_eq!(err.code, sppark::Error::DIVISION_BY_ZERO);
+    }
+
+    for_each_field!(check_product);
+}
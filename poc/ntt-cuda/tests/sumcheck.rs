@@ -0,0 +1,102 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+#[macro_use]
+mod common;
+
+use common::sample;
+use ntt_cuda::{sumcheck, Device, NttField};
+
+// Σ f(x)·eq(point, x), i.e. the multilinear extension of |table| at |point|
+fn evaluate<F: NttField>(table: &[F], point: &[F]) -> F {
+    table
+        .iter()
+        .zip(sumcheck::eq_table(Device::default(), point))
+        .fold(F::ZERO, |acc, (f, e)| acc + *f * e)
+}
+
+#[test]
+fn eq_table() {
+    fn check_eq<F: NttField>() {
+        for nvars in [0, 1, 3, 12] {
+            let r = sample::<F>(nvars, 1);
+            let eq = sumcheck::eq_table(Device::default(), &r);
+            assert_eq!(eq.len(), 1 << nvars);
+
+            // the first variable is the most significant bit
+            let mask = (1 << nvars) - 1;
+            for x in [0, 1 & mask, mask, 0x5a5 & mask] {
+                let expected = r.iter().enumerate().fold(F::ONE, |acc, (k, r_k)| {
+                    match (x >> (nvars - 1 - k)) & 1 {
+                        1 => acc * *r_k,
+                        _ => acc * (F::ONE - *r_k),
+                    }
+                });
+                assert!(eq[x] == expected);
+            }
+            assert!(eq.iter().fold(F::ZERO, |acc, e| acc + *e) == F::ONE);
+        }
+    }
+
+    for_each_field!(check_eq);
+}
+
+#[test]
+fn fold_and_round_poly() {
+    fn check_sumcheck<F: NttField>() {
+        // a sumcheck of Σ f·g·h, the last claim being checked by evaluation
+        let nvars = 10;
+        let size = 1 << nvars;
+        let mut tables = sample::<F>(3 * size, 2);
+        let mut claim = tables[..size]
+            .iter()
+            .zip(&tables[size..2 * size])
+            .zip(&tables[2 * size..])
+            .fold(F::ZERO, |acc, ((f, g), h)| acc + *f * *g * *h);
+        let point = sample::<F>(nvars, 3);
+
+        for r in &point {
+            let evals = sumcheck::round_poly(Device::default(), &tables, 3);
+            assert_eq!(evals.len(), 4);
+            assert!(evals[0] + evals[1] == claim);
+
+            // Lagrange interpolation over 0..=3 at r
+            claim = F::ZERO;
+            for (i, e) in evals.iter().enumerate() {
+                let (mut num, mut den) = (F::ONE, F::ONE);
+                for j in (0..4).filter(|&j| j != i) {
+                    num *= *r - F::from_u64(j as u64);
+                    den *= F::from_u64(i as u64) - F::from_u64(j as u64);
+                }
+                claim += *e * num * den.reciprocal();
+            }
+
+            let half = tables.len() / 6;
+            tables = tables
+                .chunks(2 * half)
+                .flat_map(|table| sumcheck::fold(Device::default(), table, *r))
+                .collect();
+        }
+
+        let original = sample::<F>(3 * size, 2);
+        let expected: F = original
+            .chunks(size)
+            .map(|table| evaluate(table, &point))
+            .fold(F::ONE, |acc, v| acc * v);
+        assert!(tables[0] * tables[1] * tables[2] == expected);
+        assert!(claim == expected);
+
+        let table = vec![F::ONE; 8];
+        let err = sumcheck::try_fold(Device::default(), &table[..1], F::ONE).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+        let err = sumcheck::try_round_poly(Device::default(), &table, 3).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err = sumcheck::try_round_poly(Device::default(), &table, 9).unwrap_err();
+        assert_eq!(err.code, sppark::Error::LENGTH_MISMATCH);
+        let err = sumcheck::try_round_poly(Device::default(), &table[..6], 2).unwrap_err();
+        assert_eq!(err.code, sppark::Error::NOT_POWER_OF_TWO);
+    }
+
+    for_each_field!(check_sumcheck);
+}
@@ -33,6 +33,10 @@ fn main() {
             "cargo:rerun-if-changed={}",
             base_dir.join("msm").to_string_lossy()
         );
+        println!(
+            "cargo:rerun-if-changed={}",
+            base_dir.join("sumcheck").to_string_lossy()
+        );
         println!(
             "cargo:rerun-if-changed={}",
             base_dir.join("util").to_string_lossy()
@@ -46,8 +50,8 @@ fn main() {
         Ok(var) => which::which(var),
         Err(_) => which::which("nvcc"),
     };
-    if nvcc.is_ok() {
-        let cuda_version = std::process::Command::new(nvcc.unwrap())
+    if let Ok(nvcc) = nvcc {
+        let cuda_version = std::process::Command::new(nvcc)
             .arg("--version")
             .output()
             .expect("impossible");
@@ -77,5 +81,6 @@ fn main() {
         println!("cargo:rerun-if-changed=src/lib.cpp");
         println!("cargo:rustc-cfg=feature=\"cuda\"");
     }
+    println!("cargo:rustc-check-cfg=cfg(feature, values(\"cuda\"))");
     println!("cargo:rerun-if-env-changed=NVCC");
 }
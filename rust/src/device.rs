@@ -0,0 +1,202 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enumeration of the GPUs sppark is prepared to use, i.e. the ones with
+//! compute capability 7.0 or higher, in the order of util/all_gpus.cpp.
+
+use std::cell::RefCell;
+
+use crate::Error;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    /// sppark's own index, see [`Device::id`].
+    pub id: usize,
+    /// The CUDA runtime's ordinal, which counts unsupported devices too.
+    pub cuda_id: i32,
+    pub name: String,
+    pub sm_count: u32,
+    /// Global memory size in bytes.
+    pub total_mem: usize,
+    /// (major, minor)
+    pub compute_capability: (u32, u32),
+}
+
+impl DeviceInfo {
+    pub fn device(&self) -> Device {
+        Device(self.id as i32)
+    }
+}
+
+/// Handle to a device to run an operation on. Obtained either from
+/// [`devices`], in which case it's known to be valid, or by default, in
+/// which case it refers to the device current on the calling thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Device(i32);
+
+impl Device {
+    /// Validate |id| against [`devices`].
+    pub fn new(id: usize) -> Result<Self, Error> {
+        let available = devices().len();
+        if id >= available {
+            return Err(Error::invalid_device(id, available));
+        }
+        Ok(Self(id as i32))
+    }
+
+    pub fn all() -> Vec<Self> {
+        devices().iter().map(DeviceInfo::device).collect()
+    }
+
+    /// sppark's index of the device, None for the current one.
+    pub fn id(&self) -> Option<usize> {
+        usize::try_from(self.0).ok()
+    }
+
+    /// The value to pass as `int device_id` to C++, with -1 denoting the
+    /// current device.
+    pub fn as_raw(&self) -> i32 {
+        self.0
+    }
+
+    pub fn info(&self) -> Option<DeviceInfo> {
+        let devices = devices();
+        match self.id() {
+            Some(id) => devices.get(id).cloned(),
+            None => devices.into_iter().next(),
+        }
+    }
+}
+
+impl Default for Device {
+    fn default() -> Self {
+        Self(-1)
+    }
+}
+
+thread_local! {
+    static HOST_TABLE: RefCell<Option<Vec<DeviceInfo>>> = const { RefCell::new(None) };
+}
+
+/// List the available devices. The list is empty if the crate was built
+/// without CUDA, or there is no driver or no supported device.
+pub fn devices() -> Vec<DeviceInfo> {
+    if let Some(table) = HOST_TABLE.with(|t| t.borrow().clone()) {
+        return table;
+    }
+    cuda_devices()
+}
+
+/// Make [`devices`] return |table| instead of querying the driver, for the
+/// duration of |f| and on the calling thread only. Meant for testing code
+/// that distributes work among devices on machines without any.
+pub fn with_host_table<R>(table: &[DeviceInfo], f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Vec<DeviceInfo>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            HOST_TABLE.with(|t| *t.borrow_mut() = prev);
+        }
+    }
+
+    let prev = HOST_TABLE.with(|t| t.borrow_mut().replace(table.to_vec()));
+    let _restore = Restore(prev);
+    f()
+}
+
+#[cfg(feature = "cuda")]
+fn cuda_devices() -> Vec<DeviceInfo> {
+    // Keep in sync with src/lib.cpp.
+    #[repr(C)]
+    struct device_info_t {
+        cuda_id: i32,
+        sm_count: i32,
+        total_mem: usize,
+        major: i32,
+        minor: i32,
+        name: [u8; 256],
+    }
+
+    extern "C" {
+        fn sppark_ngpus() -> usize;
+        fn sppark_device_info(id: usize, out: *mut device_info_t) -> bool;
+    }
+
+    let mut ret = Vec::new();
+    for id in 0..unsafe { sppark_ngpus() } {
+        let mut info = core::mem::MaybeUninit::<device_info_t>::uninit();
+        if !unsafe { sppark_device_info(id, info.as_mut_ptr()) } {
+            break;
+        }
+        let info = unsafe { info.assume_init() };
+        let len = info.name.iter().position(|&c| c == 0).unwrap_or(0);
+        ret.push(DeviceInfo {
+            id,
+            cuda_id: info.cuda_id,
+            name: String::from_utf8_lossy(&info.name[..len]).into_owned(),
+            sm_count: info.sm_count as u32,
+            total_mem: info.total_mem,
+            compute_capability: (info.major as u32, info.minor as u32),
+        });
+    }
+    ret
+}
+
+#[cfg(not(feature = "cuda"))]
+fn cuda_devices() -> Vec<DeviceInfo> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake(id: usize, sm_count: u32) -> DeviceInfo {
+        DeviceInfo {
+            id,
+            cuda_id: id as i32 + 1,
+            name: format!("Fake GPU #{}", id),
+            sm_count,
+            total_mem: 16 << 30,
+            compute_capability: (8, 0),
+        }
+    }
+
+    #[test]
+    fn host_table() {
+        let before = devices();
+        let table = [fake(0, 108), fake(1, 80)];
+
+        let sms = with_host_table(&table, || devices().iter().map(|d| d.sm_count).sum::<u32>());
+        assert_eq!(sms, 188);
+        with_host_table(&table, || {
+            assert_eq!(devices(), table);
+            with_host_table(&[], || assert!(devices().is_empty()));
+            assert_eq!(devices().len(), 2);
+        });
+        assert_eq!(devices(), before);
+
+        with_host_table(&table[..1], || {
+            let dev = Device::new(0).unwrap();
+            assert_eq!(dev.id(), Some(0));
+            assert_eq!(dev.as_raw(), 0);
+            assert_eq!(dev.info(), Some(table[0].clone()));
+            assert_eq!(Device::all(), [dev]);
+
+            let err = Device::new(3).unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::InvalidDevice);
+            assert_eq!(String::from(err), "invalid device id 3, 1 available");
+        });
+        with_host_table(&[], || {
+            assert!(Device::new(0).is_err());
+            assert!(Device::all().is_empty());
+            assert_eq!(Device::default().id(), None);
+            assert_eq!(Device::default().info(), None);
+        });
+
+        // the table is restored even if |f| panics
+        let _ = std::panic::catch_unwind(|| with_host_table(&table, || panic!()));
+        assert_eq!(devices(), before);
+    }
+}
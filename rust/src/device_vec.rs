@@ -0,0 +1,238 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device-resident vectors, for chaining operations on the same data
+//! without round trips to host memory. Operations themselves are provided
+//! by the crates that compile the kernels, e.g. ntt-cuda.
+//!
+//! Without CUDA the storage is emulated in host memory, so that code
+//! built around [`DeviceVec`] can be exercised on any machine.
+
+use crate::device::Device;
+use crate::{Error, Field};
+
+/// |len| elements of |T| allocated on |device|, zeroed upon creation.
+/// Not [`Clone`], since clones of the underlying [`Gpu_Ptr`](crate::Gpu_Ptr)
+/// share the memory.
+pub struct DeviceVec<T> {
+    #[cfg(feature = "cuda")]
+    ptr: crate::Gpu_Ptr<T>,
+    #[cfg(not(feature = "cuda"))]
+    data: Vec<T>,
+    len: usize,
+    device: Device,
+}
+
+// Device memory is not tied to the thread that allocated it, and the only
+// access through a shared reference is a read.
+unsafe impl<T: Send> Send for DeviceVec<T> {}
+unsafe impl<T: Sync> Sync for DeviceVec<T> {}
+
+impl<T: Field> DeviceVec<T> {
+    pub fn zeroed(device: Device, len: usize) -> Result<Self, Error> {
+        #[cfg(feature = "cuda")]
+        {
+            use core::ffi::c_void;
+            use core::mem::size_of;
+
+            extern "C" {
+                fn sppark_dev_alloc(
+                    out: &mut crate::Gpu_Ptr<c_void>,
+                    device_id: i32,
+                    sz: usize,
+                ) -> Error;
+            }
+
+            let mut ptr = crate::Gpu_Ptr::<T>::default();
+            if len != 0 {
+                let err = unsafe {
+                    sppark_dev_alloc(
+                        core::mem::transmute::<&mut _, &mut _>(&mut ptr),
+                        device.as_raw(),
+                        len * size_of::<T>(),
+                    )
+                };
+                if err.code != 0 {
+                    return Err(err.with_op("DeviceVec::zeroed"));
+                }
+            }
+            Ok(Self { ptr, len, device })
+        }
+
+        #[cfg(not(feature = "cuda"))]
+        {
+            // all Field implementors are plain arrays of integers
+            let zero = unsafe { core::mem::zeroed::<T>() };
+            Ok(Self {
+                data: vec![zero; len],
+                len,
+                device,
+            })
+        }
+    }
+
+    /// Allocate on |device| and upload |data|.
+    pub fn from_host(device: Device, data: &[T]) -> Result<Self, Error> {
+        let mut ret = Self::zeroed(device, data.len())?;
+        ret.upload(data)?;
+        Ok(ret)
+    }
+
+    /// Overwrite the contents with |data| of the same length.
+    pub fn upload(&mut self, data: &[T]) -> Result<(), Error> {
+        if data.len() != self.len {
+            return Err(Error::length_mismatch(data.len(), self.len));
+        }
+
+        #[cfg(feature = "cuda")]
+        {
+            self.copy(
+                self.as_raw_ptr(),
+                data.as_ptr() as *const _,
+                "DeviceVec::upload",
+            )
+        }
+
+        #[cfg(not(feature = "cuda"))]
+        {
+            self.data.copy_from_slice(data);
+            Ok(())
+        }
+    }
+
+    /// Copy the contents to |out| of the same length.
+    pub fn download(&self, out: &mut [T]) -> Result<(), Error> {
+        if out.len() != self.len {
+            return Err(Error::length_mismatch(out.len(), self.len));
+        }
+
+        #[cfg(feature = "cuda")]
+        {
+            self.copy(
+                out.as_mut_ptr() as *mut _,
+                self.as_raw_ptr(),
+                "DeviceVec::download",
+            )
+        }
+
+        #[cfg(not(feature = "cuda"))]
+        {
+            out.copy_from_slice(&self.data);
+            Ok(())
+        }
+    }
+
+    pub fn to_host(&self) -> Result<Vec<T>, Error> {
+        let mut ret = vec![unsafe { core::mem::zeroed::<T>() }; self.len];
+        self.download(&mut ret)?;
+        Ok(ret)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The device the memory was allocated on, and operations on it are
+    /// to be executed on.
+    pub fn device(&self) -> Device {
+        self.device
+    }
+
+    /// The handle to pass to C++ as `const gpu_ptr_t<T>&`.
+    #[cfg(feature = "cuda")]
+    pub fn as_gpu_ptr(&self) -> &crate::Gpu_Ptr<T> {
+        &self.ptr
+    }
+
+    /// The emulated device memory, for host fallbacks of operations.
+    #[cfg(not(feature = "cuda"))]
+    pub fn as_host(&self) -> &[T] {
+        &self.data
+    }
+
+    #[cfg(not(feature = "cuda"))]
+    pub fn as_host_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    #[cfg(feature = "cuda")]
+    fn as_raw_ptr(&self) -> *mut core::ffi::c_void {
+        extern "C" {
+            fn sppark_dev_ptr(by_ref: &crate::Gpu_Ptr<core::ffi::c_void>)
+                -> *mut core::ffi::c_void;
+        }
+        if self.len == 0 {
+            return core::ptr::null_mut();
+        }
+        unsafe { sppark_dev_ptr(core::mem::transmute::<&_, &_>(&self.ptr)) }
+    }
+
+    #[cfg(feature = "cuda")]
+    fn copy(
+        &self,
+        dst: *mut core::ffi::c_void,
+        src: *const core::ffi::c_void,
+        op: &str,
+    ) -> Result<(), Error> {
+        extern "C" {
+            fn sppark_dev_copy(
+                device_id: i32,
+                dst: *mut core::ffi::c_void,
+                src: *const core::ffi::c_void,
+                sz: usize,
+            ) -> Error;
+        }
+        if self.len == 0 {
+            return Ok(());
+        }
+        let sz = self.len * core::mem::size_of::<T>();
+        let err = unsafe { sppark_dev_copy(self.device.as_raw(), dst, src, sz) };
+        if err.code != 0 {
+            return Err(err.with_op(op));
+        }
+        Ok(())
+    }
+}
+
+impl<T> core::fmt::Debug for DeviceVec<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("DeviceVec")
+            .field("len", &self.len)
+            .field("device", &self.device)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{goldilocks::Goldilocks, HostField};
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<Goldilocks> = (0..100).map(Goldilocks::from_u64).collect();
+
+        let mut v = DeviceVec::zeroed(Device::default(), data.len()).unwrap();
+        assert_eq!(v.len(), 100);
+        assert_eq!(v.to_host().unwrap(), vec![Goldilocks::ZERO; 100]);
+
+        v.upload(&data).unwrap();
+        let mut out = vec![Goldilocks::ZERO; 100];
+        v.download(&mut out).unwrap();
+        assert_eq!(out, data);
+
+        let v = DeviceVec::from_host(Device::default(), &data[..10]).unwrap();
+        assert_eq!(v.to_host().unwrap(), &data[..10]);
+
+        let err = v.download(&mut out).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+
+        let v = DeviceVec::<Goldilocks>::zeroed(Device::default(), 0).unwrap();
+        assert!(v.is_empty() && v.to_host().unwrap().is_empty());
+    }
+}
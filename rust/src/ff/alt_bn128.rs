@@ -0,0 +1,33 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{Mont256, Mont256Params};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct FrParameters;
+
+impl Mont256Params for FrParameters {
+    const P: [u64; 4] = [
+        0x43e1f593f0000001,
+        0x2833e84879b97091,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ];
+    const M0: u64 = 0xc2e1f593efffffff;
+    const ONE: [u64; 4] = [
+        0xac96341c4ffffffb,
+        0x36fc76959f60cd29,
+        0x666ea36f7879462e,
+        0x0e0a77c19a07df2f,
+    ];
+    const RR: [u64; 4] = [
+        0x1bb8e645ae216da7,
+        0x53fe3ab1e35c59e3,
+        0x8c49833d53bb8085,
+        0x0216d0b17f4e44a5,
+    ];
+}
+
+/// BN254 scalar field, fr_t in ff/alt_bn128.hpp.
+pub type Fr = Mont256<FrParameters>;
@@ -0,0 +1,123 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::{impl_assign_ops, impl_binomial_extension, ExtensionField, HostField};
+
+/// BabyBear field element, bb31_t in ff/bb31_t.cuh, held in Montgomery
+/// representation with R = 2^32.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct BabyBear(u32);
+
+impl BabyBear {
+    pub const P: u32 = 0x78000001;
+    // -1/P mod 2^32
+    const M: u32 = 0x77ffffff;
+    // 2^64 mod P
+    const RR: u32 = 0x45dddde3;
+
+    /// Wrap a value that is already in Montgomery representation.
+    pub const fn from_raw(val: u32) -> Self {
+        Self(val)
+    }
+
+    /// Montgomery representation.
+    pub const fn to_raw(&self) -> u32 {
+        self.0
+    }
+
+    pub fn from_canonical(val: u32) -> Self {
+        Self(mont_reduce(val as u64 * Self::RR as u64))
+    }
+
+    pub fn to_canonical(&self) -> u32 {
+        mont_reduce(self.0 as u64)
+    }
+}
+
+// x / 2^32 mod P for x < P*2^32
+#[inline]
+fn mont_reduce(x: u64) -> u32 {
+    let m = (x as u32).wrapping_mul(BabyBear::M);
+    let t = ((x + m as u64 * BabyBear::P as u64) >> 32) as u32;
+    if t >= BabyBear::P {
+        t - BabyBear::P
+    } else {
+        t
+    }
+}
+
+impl Add for BabyBear {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let ret = self.0 + rhs.0;
+        Self(if ret >= Self::P { ret - Self::P } else { ret })
+    }
+}
+
+impl Sub for BabyBear {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (ret, borrow) = self.0.overflowing_sub(rhs.0);
+        Self(if borrow {
+            ret.wrapping_add(Self::P)
+        } else {
+            ret
+        })
+    }
+}
+
+impl Mul for BabyBear {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(mont_reduce(self.0 as u64 * rhs.0 as u64))
+    }
+}
+
+impl Neg for BabyBear {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::default() - self
+    }
+}
+
+impl_assign_ops!(BabyBear);
+
+impl HostField for BabyBear {
+    const ZERO: Self = Self(0);
+    // 2^32 mod P
+    const ONE: Self = Self(0x0ffffffe);
+
+    fn from_u64(val: u64) -> Self {
+        Self::from_canonical((val % Self::P as u64) as u32)
+    }
+
+    fn reciprocal(&self) -> Self {
+        self.pow((Self::P - 2) as u64)
+    }
+}
+
+impl core::fmt::Debug for BabyBear {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "0x{:08x}", self.to_canonical())
+    }
+}
+
+/// Quartic extension of the BabyBear field by u^4 = 11.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct BabyBearExt4([BabyBear; 4]);
+
+// 11 in Montgomery representation
+impl_binomial_extension!(BabyBearExt4, BabyBear, 4, BabyBear(0x37ffffe9));
@@ -0,0 +1,33 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{Mont256, Mont256Params};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct FrParameters;
+
+impl Mont256Params for FrParameters {
+    const P: [u64; 4] = [
+        0x0a11800000000001,
+        0x59aa76fed0000001,
+        0x60b44d1e5c37b001,
+        0x12ab655e9a2ca556,
+    ];
+    const M0: u64 = 0x0a117fffffffffff;
+    const ONE: [u64; 4] = [
+        0x7d1c7ffffffffff3,
+        0x7257f50f6ffffff2,
+        0x16d81575512c0fee,
+        0x0d4bda322bbb9a9d,
+    ];
+    const RR: [u64; 4] = [
+        0x25d577bab861857b,
+        0xcc2c27b58860591f,
+        0xa7cc008fe5dc8593,
+        0x011fdae7eff1c939,
+    ];
+}
+
+/// BLS12-377 scalar field, fr_t in ff/bls12-377.hpp.
+pub type Fr = Mont256<FrParameters>;
@@ -0,0 +1,33 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{Mont256, Mont256Params};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct FrParameters;
+
+impl Mont256Params for FrParameters {
+    const P: [u64; 4] = [
+        0xffffffff00000001,
+        0x53bda402fffe5bfe,
+        0x3339d80809a1d805,
+        0x73eda753299d7d48,
+    ];
+    const M0: u64 = 0xfffffffeffffffff;
+    const ONE: [u64; 4] = [
+        0x00000001fffffffe,
+        0x5884b7fa00034802,
+        0x998c4fefecbc4ff5,
+        0x1824b159acc5056f,
+    ];
+    const RR: [u64; 4] = [
+        0xc999e990f3f29c6d,
+        0x2b6cedcb87925c23,
+        0x05d314967254398f,
+        0x0748d9d99f59ff11,
+    ];
+}
+
+/// BLS12-381 scalar field, fr_t in ff/bls12-381.hpp.
+pub type Fr = Mont256<FrParameters>;
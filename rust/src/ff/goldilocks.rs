@@ -0,0 +1,125 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::{impl_assign_ops, impl_binomial_extension, ExtensionField, HostField};
+
+/// Goldilocks field element, gl64_t in ff/gl64_t.cuh. Unlike 256-bit
+/// fields, values are held in canonical form.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Goldilocks(u64);
+
+impl Goldilocks {
+    pub const P: u64 = 0xffffffff00000001;
+
+    pub const fn from_raw(val: u64) -> Self {
+        Self(val)
+    }
+
+    pub const fn to_raw(&self) -> u64 {
+        self.0
+    }
+}
+
+// 2^64 mod P
+const EPSILON: u64 = 0xffffffff;
+
+// Reduce a 128-bit value taking into account that 2^64 = 2^32-1 and
+// 2^96 = -1 modulo P.
+#[inline]
+fn reduce128(x: u128) -> u64 {
+    let lo = x as u64;
+    let hi = (x >> 64) as u64;
+    let (hi_hi, hi_lo) = (hi >> 32, hi & EPSILON);
+
+    let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+    if borrow {
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+    let (mut t1, carry) = t0.overflowing_add(hi_lo * EPSILON);
+    if carry {
+        t1 = t1.wrapping_add(EPSILON);
+    }
+    if t1 >= Goldilocks::P {
+        t1 -= Goldilocks::P;
+    }
+    t1
+}
+
+impl Add for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let (mut ret, carry) = self.0.overflowing_add(rhs.0);
+        if carry {
+            ret = ret.wrapping_add(EPSILON);
+        }
+        if ret >= Self::P {
+            ret -= Self::P;
+        }
+        Self(ret)
+    }
+}
+
+impl Sub for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (mut ret, borrow) = self.0.overflowing_sub(rhs.0);
+        if borrow {
+            ret = ret.wrapping_sub(EPSILON);
+        }
+        Self(ret)
+    }
+}
+
+impl Mul for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(reduce128(self.0 as u128 * rhs.0 as u128))
+    }
+}
+
+impl Neg for Goldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::default() - self
+    }
+}
+
+impl_assign_ops!(Goldilocks);
+
+impl HostField for Goldilocks {
+    const ZERO: Self = Self(0);
+    const ONE: Self = Self(1);
+
+    fn from_u64(val: u64) -> Self {
+        Self(if val >= Self::P { val - Self::P } else { val })
+    }
+
+    fn reciprocal(&self) -> Self {
+        self.pow(Self::P - 2)
+    }
+}
+
+impl core::fmt::Debug for Goldilocks {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "0x{:016x}", self.0)
+    }
+}
+
+/// Quadratic extension of the Goldilocks field by u^2 = 7.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct GoldilocksExt2([Goldilocks; 2]);
+
+impl_binomial_extension!(GoldilocksExt2, Goldilocks, 2, Goldilocks(7));
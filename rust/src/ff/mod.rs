@@ -0,0 +1,435 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Host-side counterparts of the field types in ff/. The in-memory
+//! representation of each type matches the one used on the GPU, so that
+//! the same slice can be handed to either implementation.
+
+mod mont256;
+pub use mont256::{Mont256, Mont256Params};
+
+pub mod alt_bn128;
+pub mod baby_bear;
+pub mod bls12_377;
+pub mod bls12_381;
+pub mod goldilocks;
+pub mod pasta;
+
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ntt::{par_chunks, MIN_GRAIN};
+use crate::Error;
+
+pub trait HostField:
+    Copy
+    + Default
+    + Eq
+    + core::fmt::Debug
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_u64(val: u64) -> Self;
+
+    /// Multiplicative inverse, zero maps to zero.
+    fn reciprocal(&self) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    fn sqr(&self) -> Self {
+        *self * *self
+    }
+
+    fn pow(&self, exp: u64) -> Self {
+        self.pow_limbs(&[exp])
+    }
+
+    /// Raise to the power of a little-endian multi-limb exponent.
+    fn pow_limbs(&self, exp: &[u64]) -> Self {
+        let mut ret = Self::ONE;
+        for limb in exp.iter().rev() {
+            for i in (0..64).rev() {
+                ret = ret.sqr();
+                if (limb >> i) & 1 != 0 {
+                    ret *= *self;
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// Extension of a [`HostField`] by a root u of an irreducible binomial
+/// u^DEGREE - W. Elements are held as DEGREE coefficients in the base
+/// field, lowest degree first, same as on the GPU.
+pub trait ExtensionField: HostField + Mul<<Self as ExtensionField>::Base, Output = Self> {
+    type Base: HostField;
+    const DEGREE: usize;
+
+    fn from_base(val: Self::Base) -> Self;
+
+    /// View the elements as DEGREE times as many base-field ones, i.e. as
+    /// a row-major matrix of DEGREE columns.
+    fn as_base_slice(slice: &[Self]) -> &[Self::Base];
+    fn as_base_slice_mut(slice: &mut [Self]) -> &mut [Self::Base];
+}
+
+/// How [`batch_inverse`] treats zero elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Zeros {
+    /// Leave zeros intact, same as [`HostField::reciprocal`] maps zero to
+    /// zero, and report their number.
+    Skip,
+    /// Fail with DIVISION_BY_ZERO naming the first zero, leaving the input
+    /// intact.
+    Reject,
+}
+
+/// Invert all elements with Montgomery's trick, i.e. with a single
+/// reciprocal per chunk of elements processed by a thread and three
+/// multiplications per element. Returns the number of zeros, which can be
+/// non-zero only with [`Zeros::Skip`].
+pub fn batch_inverse<F: HostField>(inout: &mut [F], zeros: Zeros) -> Result<usize, Error> {
+    if zeros == Zeros::Reject {
+        if let Some(i) = inout.iter().position(|x| x.is_zero()) {
+            return Err(Error::division_by_zero(&format!("element {} is zero", i)));
+        }
+    }
+
+    let nzeros = AtomicUsize::new(0);
+    par_chunks(inout, MIN_GRAIN, |_, chunk| {
+        nzeros.fetch_add(montgomery_inverse(chunk), Ordering::Relaxed);
+    });
+    Ok(nzeros.into_inner())
+}
+
+// Sequential Montgomery's trick skipping zeros, return their number.
+fn montgomery_inverse<F: HostField>(inout: &mut [F]) -> usize {
+    let mut prefix = Vec::with_capacity(inout.len());
+    let mut acc = F::ONE;
+    let mut nzeros = 0;
+    for x in inout.iter() {
+        prefix.push(acc);
+        if x.is_zero() {
+            nzeros += 1;
+        } else {
+            acc *= *x;
+        }
+    }
+
+    let mut inv = acc.reciprocal();
+    for (x, p) in inout.iter_mut().zip(prefix).rev() {
+        if !x.is_zero() {
+            let t = inv * p;
+            inv *= *x;
+            *x = t;
+        }
+    }
+    nzeros
+}
+
+// Implement the compound assignment operators in terms of the binary ones.
+macro_rules! impl_assign_ops {
+    ($t:ty $(, $g:ident: $b:path)?) => {
+        impl$(<$g: $b>)? core::ops::AddAssign for $t {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl$(<$g: $b>)? core::ops::SubAssign for $t {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl$(<$g: $b>)? core::ops::MulAssign for $t {
+            #[inline]
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+    };
+}
+use impl_assign_ops;
+
+// Implement arithmetic of |$t|, a newtype over [$base; $n], as the binomial
+// extension $base[u]/(u^$n - $w), $n being a power of 2.
+macro_rules! impl_binomial_extension {
+    ($t:ident, $base:ty, $n:literal, $w:expr) => {
+        impl $t {
+            const W: $base = $w;
+
+            pub const fn from_coeffs(coeffs: [$base; $n]) -> Self {
+                Self(coeffs)
+            }
+
+            pub const fn coeffs(&self) -> [$base; $n] {
+                self.0
+            }
+        }
+
+        impl core::ops::Add for $t {
+            type Output = Self;
+
+            #[inline]
+            fn add(mut self, rhs: Self) -> Self {
+                for (a, b) in self.0.iter_mut().zip(rhs.0) {
+                    *a += b;
+                }
+                self
+            }
+        }
+
+        impl core::ops::Sub for $t {
+            type Output = Self;
+
+            #[inline]
+            fn sub(mut self, rhs: Self) -> Self {
+                for (a, b) in self.0.iter_mut().zip(rhs.0) {
+                    *a -= b;
+                }
+                self
+            }
+        }
+
+        impl core::ops::Neg for $t {
+            type Output = Self;
+
+            #[inline]
+            fn neg(self) -> Self {
+                Self(self.0.map(|a| -a))
+            }
+        }
+
+        impl core::ops::Mul for $t {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self {
+                // schoolbook, folding u^(n+k) into W·u^k
+                let mut lo = [<$base as HostField>::ZERO; $n];
+                let mut hi = [<$base as HostField>::ZERO; $n];
+                for (i, a) in self.0.iter().enumerate() {
+                    for (j, b) in rhs.0.iter().enumerate() {
+                        if i + j < $n {
+                            lo[i + j] += *a * *b;
+                        } else {
+                            hi[i + j - $n] += *a * *b;
+                        }
+                    }
+                }
+                for (l, h) in lo.iter_mut().zip(hi) {
+                    *l += h * Self::W;
+                }
+                Self(lo)
+            }
+        }
+
+        // multiplication by a base-field element
+        impl core::ops::Mul<$base> for $t {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, rhs: $base) -> Self {
+                Self(self.0.map(|a| a * rhs))
+            }
+        }
+
+        super::impl_assign_ops!($t);
+
+        impl HostField for $t {
+            const ZERO: Self = Self([<$base as HostField>::ZERO; $n]);
+            const ONE: Self = {
+                let mut coeffs = [<$base as HostField>::ZERO; $n];
+                coeffs[0] = <$base as HostField>::ONE;
+                Self(coeffs)
+            };
+
+            fn from_u64(val: u64) -> Self {
+                Self::from_base(<$base>::from_u64(val))
+            }
+
+            fn reciprocal(&self) -> Self {
+                // a(u)·a(-u) has only even powers of u, hence multiplying
+                // by such conjugates halves the degree until the product
+                // is in the base field
+                let mut num = Self::ONE;
+                let mut den = *self;
+                let mut stride = 1;
+                while stride < $n {
+                    let mut conj = den;
+                    for (i, c) in conj.0.iter_mut().enumerate() {
+                        if (i / stride) & 1 != 0 {
+                            *c = -*c;
+                        }
+                    }
+                    num *= conj;
+                    den *= conj;
+                    stride *= 2;
+                }
+                let inv = den.0[0].reciprocal();
+                Self(num.0.map(|c| c * inv))
+            }
+        }
+
+        impl super::ExtensionField for $t {
+            type Base = $base;
+            const DEGREE: usize = $n;
+
+            fn from_base(val: $base) -> Self {
+                let mut coeffs = [<$base as HostField>::ZERO; $n];
+                coeffs[0] = val;
+                Self(coeffs)
+            }
+
+            fn as_base_slice(slice: &[Self]) -> &[$base] {
+                // #[repr(transparent)] over [$base; $n]
+                unsafe { core::slice::from_raw_parts(slice.as_ptr().cast(), slice.len() * $n) }
+            }
+
+            fn as_base_slice_mut(slice: &mut [Self]) -> &mut [$base] {
+                unsafe {
+                    core::slice::from_raw_parts_mut(slice.as_mut_ptr().cast(), slice.len() * $n)
+                }
+            }
+        }
+
+        impl core::fmt::Debug for $t {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "{:?}", self.0)
+            }
+        }
+    };
+}
+use impl_binomial_extension;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random;
+
+    // Basic field axioms on a handful of pseudo-random elements.
+    fn check_field<F: HostField>() {
+        for abc in random::<F>(3 * 32, 0xf1e1d).chunks(3) {
+            let (a, b, c) = (abc[0], abc[1], abc[2]);
+            assert_eq!(a + b, b + a);
+            assert_eq!(a * b, b * a);
+            assert_eq!((a + b) * c, a * c + b * c);
+            assert_eq!(a - b + b, a);
+            assert_eq!(-a + a, F::ZERO);
+            assert_eq!(a * F::ONE, a);
+            assert_eq!(a.sqr(), a * a);
+            assert_eq!(a * a.reciprocal(), F::ONE);
+        }
+        assert_eq!(F::ZERO.reciprocal(), F::ZERO);
+        assert_eq!(F::from_u64(6), F::from_u64(2) * F::from_u64(3));
+        assert_eq!(-F::ONE, F::ZERO - F::ONE);
+    }
+
+    #[test]
+    fn fields() {
+        check_field::<bls12_381::Fr>();
+        check_field::<bls12_377::Fr>();
+        check_field::<alt_bn128::Fr>();
+        check_field::<pasta::Pallas>();
+        check_field::<pasta::Vesta>();
+        check_field::<goldilocks::Goldilocks>();
+        check_field::<baby_bear::BabyBear>();
+    }
+
+    #[test]
+    fn extension_fields() {
+        use baby_bear::{BabyBear, BabyBearExt4};
+        use goldilocks::{Goldilocks, GoldilocksExt2};
+
+        check_field::<GoldilocksExt2>();
+        check_field::<BabyBearExt4>();
+
+        // u^DEGREE = W, W being a non-residue makes the binomial irreducible
+        let u = GoldilocksExt2::from_coeffs([Goldilocks::ZERO, Goldilocks::ONE]);
+        assert_eq!(u.sqr(), GoldilocksExt2::from_u64(7));
+        assert_eq!(
+            Goldilocks::from_u64(7).pow((Goldilocks::P - 1) / 2),
+            -Goldilocks::ONE
+        );
+        let u = BabyBearExt4::from_coeffs([
+            BabyBear::ZERO,
+            BabyBear::ONE,
+            BabyBear::ZERO,
+            BabyBear::ZERO,
+        ]);
+        assert_eq!(u.pow(4), BabyBearExt4::from_u64(11));
+        assert_eq!(
+            BabyBear::from_u64(11).pow((BabyBear::P as u64 - 1) / 2),
+            -BabyBear::ONE
+        );
+
+        let v =
+            [GoldilocksExt2::from_coeffs([Goldilocks::from_u64(1), Goldilocks::from_u64(2)]); 3];
+        let base = GoldilocksExt2::as_base_slice(&v);
+        assert_eq!(base.len(), 6);
+        assert_eq!(base[3], Goldilocks::from_u64(2));
+    }
+
+    #[test]
+    fn batch_inversion() {
+        use goldilocks::Goldilocks;
+
+        let mut v: Vec<Goldilocks> = (0..100).map(|i| Goldilocks::from_u64(i * 7 % 31)).collect();
+        let expected: Vec<_> = v.iter().map(|x| x.reciprocal()).collect();
+        let orig = v.clone();
+        let err = batch_inverse(&mut v, Zeros::Reject).unwrap_err();
+        assert_eq!(err.code, Error::DIVISION_BY_ZERO);
+        assert_eq!(v, orig);
+        assert_eq!(batch_inverse(&mut v, Zeros::Skip).unwrap(), 4);
+        assert_eq!(v, expected);
+        assert_eq!(
+            batch_inverse::<Goldilocks>(&mut [], Zeros::Reject).unwrap(),
+            0
+        );
+
+        // spans several threads' chunks
+        let mut v: Vec<baby_bear::BabyBear> = (0..5 * MIN_GRAIN as u64 + 3)
+            .map(|i| baby_bear::BabyBear::from_u64(i % 1000))
+            .collect();
+        let expected: Vec<_> = v.iter().map(|x| x.reciprocal()).collect();
+        let nzeros = v.iter().filter(|x| x.is_zero()).count();
+        assert_eq!(batch_inverse(&mut v, Zeros::Skip).unwrap(), nzeros);
+        assert_eq!(v, expected);
+
+        let mut v = [goldilocks::GoldilocksExt2::from_u64(3); 5];
+        batch_inverse(&mut v, Zeros::Reject).unwrap();
+        assert_eq!(
+            v[4] * goldilocks::GoldilocksExt2::from_u64(3),
+            HostField::ONE
+        );
+    }
+
+    #[test]
+    fn representation() {
+        let one = bls12_381::Fr::ONE;
+        assert_eq!(one.to_canonical(), [1, 0, 0, 0]);
+        assert_eq!(bls12_381::Fr::from_canonical([1, 0, 0, 0]), one);
+        assert_eq!(baby_bear::BabyBear::from_u64(7).to_canonical(), 7);
+        assert_eq!(
+            goldilocks::Goldilocks::from_u64(u64::MAX).to_raw(),
+            u64::MAX - goldilocks::Goldilocks::P
+        );
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+// Limb-wise arithmetic reads better with explicit indices.
+#![allow(clippy::needless_range_loop)]
+
+use core::marker::PhantomData;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::{impl_assign_ops, HostField};
+
+/// Modulus-specific constants for [`Mont256`], all little-endian.
+pub trait Mont256Params: Copy + Default + Eq + core::fmt::Debug + Send + Sync + 'static {
+    /// The modulus, has to be less than 2^255.
+    const P: [u64; 4];
+    /// -1/P mod 2^64
+    const M0: u64;
+    /// 2^256 mod P, i.e. one in Montgomery representation
+    const ONE: [u64; 4];
+    /// 2^512 mod P
+    const RR: [u64; 4];
+}
+
+/// Element of a prime field with a modulus of up to 255 bits, held in
+/// Montgomery representation with R = 2^256. This matches both mont_t
+/// in ff/mont_t.cuh and the arkworks' layout.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Mont256<P: Mont256Params>([u64; 4], PhantomData<P>);
+
+impl<P: Mont256Params> Mont256<P> {
+    /// Wrap a value that is already in Montgomery representation.
+    pub const fn from_raw(limbs: [u64; 4]) -> Self {
+        Self(limbs, PhantomData)
+    }
+
+    /// Montgomery representation.
+    pub const fn to_raw(&self) -> [u64; 4] {
+        self.0
+    }
+
+    /// Convert from a value less than the modulus.
+    pub fn from_canonical(limbs: [u64; 4]) -> Self {
+        Self::from_raw(mont_mul(&limbs, &P::RR, &P::P, P::M0))
+    }
+
+    pub fn to_canonical(&self) -> [u64; 4] {
+        mont_mul(&self.0, &[1, 0, 0, 0], &P::P, P::M0)
+    }
+}
+
+#[inline]
+fn adc(a: u64, b: u64, carry: &mut u64) -> u64 {
+    let t = a as u128 + b as u128 + *carry as u128;
+    *carry = (t >> 64) as u64;
+    t as u64
+}
+
+#[inline]
+fn sbb(a: u64, b: u64, borrow: &mut u64) -> u64 {
+    let t = (a as u128).wrapping_sub(b as u128 + *borrow as u128);
+    *borrow = (t >> 127) as u64;
+    t as u64
+}
+
+#[inline]
+fn mac(acc: u64, a: u64, b: u64, carry: &mut u64) -> u64 {
+    let t = acc as u128 + a as u128 * b as u128 + *carry as u128;
+    *carry = (t >> 64) as u64;
+    t as u64
+}
+
+// Subtract the modulus if |a| is not less than it.
+#[inline]
+fn final_sub(a: [u64; 4], p: &[u64; 4]) -> [u64; 4] {
+    let mut borrow = 0;
+    let mut ret = [0u64; 4];
+    for i in 0..4 {
+        ret[i] = sbb(a[i], p[i], &mut borrow);
+    }
+    if borrow != 0 {
+        a
+    } else {
+        ret
+    }
+}
+
+// Coarsely integrated operand scanning. Since the modulus is less than
+// 2^255, the intermediate result never exceeds 2*p and fits into 4 limbs.
+fn mont_mul(a: &[u64; 4], b: &[u64; 4], p: &[u64; 4], m0: u64) -> [u64; 4] {
+    let mut t = [0u64; 5];
+    for &bi in b.iter() {
+        let mut carry = 0;
+        for j in 0..4 {
+            t[j] = mac(t[j], a[j], bi, &mut carry);
+        }
+        t[4] += carry;
+
+        let m = t[0].wrapping_mul(m0);
+        let mut carry = 0;
+        mac(t[0], m, p[0], &mut carry);
+        for j in 1..4 {
+            t[j - 1] = mac(t[j], m, p[j], &mut carry);
+        }
+        t[3] = adc(t[4], 0, &mut carry);
+        t[4] = carry;
+    }
+    final_sub([t[0], t[1], t[2], t[3]], p)
+}
+
+impl<P: Mont256Params> Add for Mont256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let mut carry = 0;
+        let mut ret = [0u64; 4];
+        for i in 0..4 {
+            ret[i] = adc(self.0[i], rhs.0[i], &mut carry);
+        }
+        Self::from_raw(final_sub(ret, &P::P))
+    }
+}
+
+impl<P: Mont256Params> Sub for Mont256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let mut borrow = 0;
+        let mut ret = [0u64; 4];
+        for i in 0..4 {
+            ret[i] = sbb(self.0[i], rhs.0[i], &mut borrow);
+        }
+        if borrow != 0 {
+            let mut carry = 0;
+            for i in 0..4 {
+                ret[i] = adc(ret[i], P::P[i], &mut carry);
+            }
+        }
+        Self::from_raw(ret)
+    }
+}
+
+impl<P: Mont256Params> Mul for Mont256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_raw(mont_mul(&self.0, &rhs.0, &P::P, P::M0))
+    }
+}
+
+impl<P: Mont256Params> Neg for Mont256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::default() - self
+    }
+}
+
+impl_assign_ops!(Mont256<P>, P: Mont256Params);
+
+impl<P: Mont256Params> HostField for Mont256<P> {
+    const ZERO: Self = Self::from_raw([0; 4]);
+    const ONE: Self = Self::from_raw(P::ONE);
+
+    fn from_u64(val: u64) -> Self {
+        Self::from_canonical([val, 0, 0, 0])
+    }
+
+    fn reciprocal(&self) -> Self {
+        // Fermat's little theorem, all moduli in question are > 2
+        let mut exp = P::P;
+        exp[0] -= 2;
+        self.pow_limbs(&exp)
+    }
+}
+
+impl<P: Mont256Params> core::fmt::Debug for Mont256<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let limbs = self.to_canonical();
+        write!(
+            f,
+            "0x{:016x}{:016x}{:016x}{:016x}",
+            limbs[3], limbs[2], limbs[1], limbs[0]
+        )
+    }
+}
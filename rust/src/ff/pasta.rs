@@ -0,0 +1,61 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{Mont256, Mont256Params};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct PallasParameters;
+
+impl Mont256Params for PallasParameters {
+    const P: [u64; 4] = [
+        0x992d30ed00000001,
+        0x224698fc094cf91b,
+        0x0000000000000000,
+        0x4000000000000000,
+    ];
+    const M0: u64 = 0x992d30ecffffffff;
+    const ONE: [u64; 4] = [
+        0x34786d38fffffffd,
+        0x992c350be41914ad,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ];
+    const RR: [u64; 4] = [
+        0x8c78ecb30000000f,
+        0xd7d30dbd8b0de0e7,
+        0x7797a99bc3c95d18,
+        0x096d41af7b9cb714,
+    ];
+}
+
+/// Pallas base field, i.e. Vesta scalar field, pallas_t in ff/pasta.hpp.
+pub type Pallas = Mont256<PallasParameters>;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct VestaParameters;
+
+impl Mont256Params for VestaParameters {
+    const P: [u64; 4] = [
+        0x8c46eb2100000001,
+        0x224698fc0994a8dd,
+        0x0000000000000000,
+        0x4000000000000000,
+    ];
+    const M0: u64 = 0x8c46eb20ffffffff;
+    const ONE: [u64; 4] = [
+        0x5b2b3e9cfffffffd,
+        0x992c350be3420567,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ];
+    const RR: [u64; 4] = [
+        0xfc9678ff0000000f,
+        0x67bb433d891a16e3,
+        0x7fae231004ccf590,
+        0x096d41af7ccfdaa9,
+    ];
+}
+
+/// Vesta base field, i.e. Pallas scalar field, vesta_t in ff/pasta.hpp.
+pub type Vesta = Mont256<VestaParameters>;
@@ -0,0 +1,171 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types that can be handed to sppark in place of its own field types.
+//!
+//! Entry points reinterpret the caller's elements as the device type, so
+//! the two have to agree on size, alignment, modulus and form, Montgomery
+//! or canonical. [`Field`] is implemented only for types that were checked
+//! to agree, at compile time where the constants are reachable. Third-party
+//! implementations are ruled out by sealing the trait, e.g. one can't pass
+//! `u64` instead of Goldilocks:
+//!
+//! ```compile_fail
+//! fn takes<T: sppark::Field>(_: &mut [T]) {}
+//! takes(&mut [0u64; 4]);
+//! ```
+
+use core::mem::{align_of, size_of};
+
+use crate::ff::HostField;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A type with the same in-memory representation as [`Field::Repr`].
+pub trait Field: sealed::Sealed + Copy + Send + Sync + 'static {
+    /// sppark's host counterpart of the device field type.
+    type Repr: HostField;
+
+    fn cast_slice(slice: &[Self]) -> &[Self::Repr] {
+        // Size and alignment are asserted along with the implementation.
+        unsafe { core::slice::from_raw_parts(slice.as_ptr() as *const Self::Repr, slice.len()) }
+    }
+
+    fn cast_slice_mut(slice: &mut [Self]) -> &mut [Self::Repr] {
+        unsafe {
+            core::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut Self::Repr, slice.len())
+        }
+    }
+}
+
+#[allow(dead_code)]
+const fn limbs_eq(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    let mut i = 0;
+    while i < 4 {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// Implement Field for |$t| represented as |$repr|, optionally asserting
+// that its modulus and Montgomery one match |$params|.
+macro_rules! impl_field {
+    ($t:ty => $repr:ty $(, $modulus:expr, $one:expr, $params:ty)?) => {
+        impl sealed::Sealed for $t {}
+
+        impl Field for $t {
+            type Repr = $repr;
+        }
+
+        const _: () = {
+            assert!(size_of::<$t>() == size_of::<$repr>());
+            assert!(align_of::<$t>() >= align_of::<$repr>());
+            $(
+                assert!(limbs_eq(&$modulus, &<$params as crate::ff::Mont256Params>::P));
+                assert!(limbs_eq(&$one, &<$params as crate::ff::Mont256Params>::ONE));
+            )?
+        };
+    };
+}
+
+impl<P: crate::ff::Mont256Params> sealed::Sealed for crate::ff::Mont256<P> {}
+
+impl<P: crate::ff::Mont256Params> Field for crate::ff::Mont256<P> {
+    type Repr = Self;
+}
+
+impl_field!(crate::ff::goldilocks::Goldilocks => crate::ff::goldilocks::Goldilocks);
+impl_field!(crate::ff::baby_bear::BabyBear => crate::ff::baby_bear::BabyBear);
+
+// arkworks keeps Fp256 in Montgomery form with R = 2^256, same as Mont256.
+macro_rules! impl_ark_field {
+    ($feature:literal, $ark:ident, $params:ident, $repr:ident, $sppark_params:ty) => {
+        #[cfg(feature = $feature)]
+        impl_field!(
+            $ark::$repr => crate::ff::Mont256<$sppark_params>,
+            <$ark::$params as ark_ff::FpParameters>::MODULUS.0,
+            <$ark::$params as ark_ff::FpParameters>::R.0,
+            $sppark_params
+        );
+    };
+}
+
+impl_ark_field!(
+    "ark-bls12-377",
+    ark_bls12_377,
+    FrParameters,
+    Fr,
+    crate::ff::bls12_377::FrParameters
+);
+impl_ark_field!(
+    "ark-bls12-381",
+    ark_bls12_381,
+    FrParameters,
+    Fr,
+    crate::ff::bls12_381::FrParameters
+);
+impl_ark_field!(
+    "ark-bn254",
+    ark_bn254,
+    FrParameters,
+    Fr,
+    crate::ff::alt_bn128::FrParameters
+);
+// Fr for Pallas curve is Vesta and vice versa
+impl_ark_field!(
+    "ark-pallas",
+    ark_pallas,
+    FrParameters,
+    Fr,
+    crate::ff::pasta::VestaParameters
+);
+impl_ark_field!(
+    "ark-vesta",
+    ark_vesta,
+    FrParameters,
+    Fr,
+    crate::ff::pasta::PallasParameters
+);
+
+// blst_fr is Montgomery by definition, as opposed to blst_scalar, which
+// is deliberately left out.
+#[cfg(feature = "blst")]
+impl_field!(blst::blst_fr => crate::ff::bls12_381::Fr);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cast_slice() {
+        use crate::ff::goldilocks::Goldilocks;
+
+        let mut v = [Goldilocks::from_u64(3), Goldilocks::from_u64(5)];
+        for x in Field::cast_slice_mut(&mut v[..]) {
+            *x *= *x;
+        }
+        assert_eq!(
+            Field::cast_slice(&v[..]),
+            [Goldilocks::from_u64(9), Goldilocks::from_u64(25)]
+        );
+    }
+
+    #[cfg(feature = "ark-bls12-381")]
+    #[test]
+    fn ark_bls12_381() {
+        use ark_ff::{Field as _, One};
+
+        let mut v = [ark_bls12_381::Fr::from(3u64), ark_bls12_381::Fr::one()];
+        let repr = Field::cast_slice_mut(&mut v[..]);
+        assert_eq!(repr[0], crate::ff::bls12_381::Fr::from_u64(3));
+        assert_eq!(repr[1], crate::ff::bls12_381::Fr::ONE);
+        repr[0] = repr[0].reciprocal();
+        assert_eq!(v[0], ark_bls12_381::Fr::from(3u64).inverse().unwrap());
+    }
+}
@@ -1,5 +1,7 @@
 #include <cuda_runtime.h>
 #include <util/gpu_t.cuh>
+#include <util/rusterror.h>
+#include <cstring>
 
 extern "C" void drop_gpu_ptr_t(gpu_ptr_t<void>& ref)
 {   ref.~gpu_ptr_t();   }
@@ -15,3 +17,71 @@ extern "C" gpu_ptr_t<void>::by_value clone_gpu_ptr_t(const gpu_ptr_t<void>& rhs)
 #ifdef __clang__
 # pragma clang diagnostic pop
 #endif
+
+// Backing store for DeviceVec<T> in src/device_vec.rs. Memory is zeroed,
+// which is a valid value of any field element, and all transfers are
+// complete by the time the calls return.
+extern "C" RustError sppark_dev_alloc(gpu_ptr_t<void>& out, int device_id,
+                                      size_t sz)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+        void* d_ptr;
+        CUDA_OK(cudaMalloc(&d_ptr, sz));
+        out = gpu_ptr_t<void>{d_ptr};
+        CUDA_OK(cudaMemsetAsync(d_ptr, 0, sz, gpu));
+        gpu.sync();
+    } catch (const cuda_error& e) {
+        return RustError{e.code()};
+    }
+
+    return RustError{cudaSuccess};
+}
+
+extern "C" void* sppark_dev_ptr(const gpu_ptr_t<void>& ref)
+{   return ref;   }
+
+extern "C" RustError sppark_dev_copy(int device_id, void* dst,
+                                     const void* src, size_t sz)
+{
+    try {
+        auto& gpu = select_gpu(device_id);
+        CUDA_OK(cudaMemcpyAsync(dst, src, sz, cudaMemcpyDefault, gpu));
+        gpu.sync();
+    } catch (const cuda_error& e) {
+        return RustError{e.code()};
+    }
+
+    return RustError{cudaSuccess};
+}
+
+// Flat snapshot of gpu_t, consumed by src/device.rs. Keep in sync.
+struct device_info_t {
+    int cuda_id;
+    int sm_count;
+    size_t total_mem;
+    int major, minor;
+    char name[256];
+};
+
+extern "C" size_t sppark_ngpus()
+{   return ngpus();   }
+
+extern "C" bool sppark_device_info(size_t id, device_info_t* out)
+{
+    auto& gpus = all_gpus();
+    if (id >= gpus.size())
+        return false;
+
+    auto& gpu = *gpus[id];
+    auto& prop = gpu.props();
+    out->cuda_id = gpu.cid();
+    out->sm_count = gpu.sm_count();
+    out->total_mem = prop.totalGlobalMem;
+    out->major = prop.major;
+    out->minor = prop.minor;
+    static_assert(sizeof(out->name) == sizeof(prop.name), "name size");
+    memcpy(out->name, prop.name, sizeof(out->name));
+    out->name[sizeof(out->name)-1] = '\0';
+    return true;
+}
@@ -2,14 +2,274 @@
 // Licensed under the Apache License, Version 2.0, see LICENSE for details.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod device;
+mod device_vec;
+pub mod ff;
+mod field;
+pub mod ntt;
+pub mod poly;
+pub mod sumcheck;
+#[cfg(test)]
+mod test_util;
+
+pub use device_vec::DeviceVec;
+pub use field::Field;
+
 // Declare C/C++ counterpart as following:
 // extern "C" { fn foobar(...) -> sppark::Error; }
 #[repr(C)]
 pub struct Error {
     pub code: i32,
+    detail: u32, // code-specific payload, zero unless set on the Rust side
     str: Option<core::ptr::NonNull<i8>>, // just strdup("string") from C/C++
 }
 
+impl Error {
+    /// Construct an error on the Rust side, e.g. to report an argument
+    /// validation failure before reaching out to C/C++.
+    pub fn new(code: i32, message: &str) -> Self {
+        extern "C" {
+            fn malloc(size: usize) -> Option<core::ptr::NonNull<i8>>;
+        }
+        // The message has to be released with free() in Drop below...
+        let len = message.len();
+        let str = unsafe { malloc(len + 1) };
+        if let Some(ptr) = str {
+            unsafe {
+                let ptr = ptr.as_ptr() as *mut u8;
+                core::ptr::copy_nonoverlapping(message.as_ptr(), ptr, len);
+                *ptr.add(len) = 0;
+            }
+        }
+        Self {
+            code,
+            detail: 0,
+            str,
+        }
+    }
+
+    /// Input slices' lengths don't match.
+    pub const LENGTH_MISMATCH: i32 = SPPARK_ERROR_BASE + 1;
+    /// Input length is not a power of 2.
+    pub const NOT_POWER_OF_TWO: i32 = SPPARK_ERROR_BASE + 2;
+    /// Element type is not compatible with the compiled field.
+    pub const TYPE_MISMATCH: i32 = SPPARK_ERROR_BASE + 3;
+    /// Domain size exceeds the compiled MAX_LG_DOMAIN_SIZE.
+    pub const DOMAIN_TOO_LARGE: i32 = SPPARK_ERROR_BASE + 4;
+    /// Data layout is not supported by the operation.
+    pub const BAD_LAYOUT: i32 = SPPARK_ERROR_BASE + 5;
+    /// An element that is to be inverted is zero.
+    pub const DIVISION_BY_ZERO: i32 = SPPARK_ERROR_BASE + 6;
+    /// Supplied root of unity is not primitive of the domain's order.
+    pub const BAD_ROOT_OF_UNITY: i32 = SPPARK_ERROR_BASE + 7;
+    /// Host memory allocation failed, classified the same as a failed
+    /// device allocation.
+    pub const OUT_OF_MEMORY: i32 = SPPARK_ERROR_BASE + 8;
+    /// Device id is out of range, reported as negated cudaErrorInvalidDevice
+    /// as it would be by select_gpu.
+    pub const INVALID_DEVICE: i32 = -101;
+
+    pub fn length_mismatch(lhs: usize, rhs: usize) -> Self {
+        Self::new(
+            Self::LENGTH_MISMATCH,
+            &format!("length mismatch: {} vs. {}", lhs, rhs),
+        )
+    }
+
+    pub fn not_power_of_two(len: usize) -> Self {
+        Self::new(
+            Self::NOT_POWER_OF_TWO,
+            &format!("inout.len() is not power of 2: {}", len),
+        )
+    }
+
+    /// Keep the message in sync with NTT::check_domain_size in ntt/ntt.cuh.
+    /// The sizes are retained, see [`Error::as_domain_too_large`].
+    pub fn domain_too_large(lg_domain_size: u32, max: u32) -> Self {
+        let mut err = Self::new(
+            Self::DOMAIN_TOO_LARGE,
+            &format!("lg_domain_size {} exceeds {}", lg_domain_size, max),
+        );
+        // both are log2 of a size, hence fit in 16 bits, and the requested
+        // one exceeds max, so that the payload is never 0
+        err.detail = lg_domain_size.min(0xffff) << 16 | max.min(0xffff);
+        err
+    }
+
+    /// The requested and the maximum supported domain sizes of a
+    /// DOMAIN_TOO_LARGE error constructed with [`Error::domain_too_large`].
+    /// None for other errors, as well as for ones that come from C++, which
+    /// is why Rust bindings are expected to check domain sizes upfront.
+    pub fn as_domain_too_large(&self) -> Option<DomainTooLarge> {
+        if self.code != Self::DOMAIN_TOO_LARGE || self.detail == 0 {
+            return None;
+        }
+        Some(DomainTooLarge {
+            requested: self.detail >> 16,
+            max: self.detail & 0xffff,
+        })
+    }
+
+    pub fn type_mismatch(what: &str) -> Self {
+        Self::new(Self::TYPE_MISMATCH, what)
+    }
+
+    pub fn bad_layout(what: &str) -> Self {
+        Self::new(Self::BAD_LAYOUT, what)
+    }
+
+    pub fn division_by_zero(what: &str) -> Self {
+        Self::new(Self::DIVISION_BY_ZERO, what)
+    }
+
+    pub fn bad_root_of_unity(lg_domain_size: u32) -> Self {
+        Self::new(
+            Self::BAD_ROOT_OF_UNITY,
+            &format!("not a primitive 2^{}-th root of unity", lg_domain_size),
+        )
+    }
+
+    pub fn invalid_device(id: usize, available: usize) -> Self {
+        Self::new(
+            Self::INVALID_DEVICE,
+            &format!("invalid device id {}, {} available", id, available),
+        )
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::from_code(self.code)
+    }
+
+    /// Was the error raised by argument validation?
+    pub fn is_invalid_argument(&self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::LengthMismatch
+                | ErrorKind::NotPowerOfTwo
+                | ErrorKind::TypeMismatch
+                | ErrorKind::DomainTooLarge
+                | ErrorKind::BadLayout
+                | ErrorKind::DivisionByZero
+                | ErrorKind::BadRootOfUnity
+        )
+    }
+
+    pub fn is_oom(&self) -> bool {
+        self.kind() == ErrorKind::OutOfMemory
+    }
+
+    /// Can the operation succeed if attempted again later, possibly on
+    /// another device? Errors that leave the CUDA context in unusable
+    /// state, as well as invalid arguments, are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind(), ErrorKind::OutOfMemory | ErrorKind::DeviceBusy)
+    }
+
+    /// Attach the operation name to an error that doesn't carry a message,
+    /// e.g. because the C++ side was compiled without
+    /// TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE.
+    pub fn with_op(self, op: &str) -> Self {
+        if self.str.is_some() {
+            return self;
+        }
+        let mut err = Self::new(self.code, &format!("{}: {}", op, self.kind()));
+        err.detail = self.detail;
+        err
+    }
+}
+
+// sppark's own error codes are kept clear of errno values [reported by
+// sppark_error] and negated cudaError_t values [reported by CUDA_OK].
+// Keep in sync with util/rusterror.h.
+const SPPARK_ERROR_BASE: i32 = 0x10000;
+
+/// Details of a DOMAIN_TOO_LARGE error, both sizes are log2 of the number
+/// of elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DomainTooLarge {
+    pub requested: u32,
+    pub max: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    // CUDA runtime failures
+    OutOfMemory,
+    InvalidDevice,
+    NoDevice,
+    DeviceBusy,
+    LaunchFailure,
+    UnsupportedArchitecture,
+    // sppark-level failures
+    LengthMismatch,
+    NotPowerOfTwo,
+    TypeMismatch,
+    DomainTooLarge,
+    BadLayout,
+    DivisionByZero,
+    BadRootOfUnity,
+    /// Another cudaError_t value.
+    Cuda(i32),
+    /// Another code, most commonly errno value.
+    Other(i32),
+}
+
+impl ErrorKind {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            Error::LENGTH_MISMATCH => Self::LengthMismatch,
+            Error::NOT_POWER_OF_TWO => Self::NotPowerOfTwo,
+            Error::TYPE_MISMATCH => Self::TypeMismatch,
+            Error::DOMAIN_TOO_LARGE => Self::DomainTooLarge,
+            Error::BAD_LAYOUT => Self::BadLayout,
+            Error::DIVISION_BY_ZERO => Self::DivisionByZero,
+            Error::BAD_ROOT_OF_UNITY => Self::BadRootOfUnity,
+            Error::OUT_OF_MEMORY => Self::OutOfMemory,
+            // CUDA_OK reports negated cudaError_t values
+            code if code < 0 => match -code {
+                2 => Self::OutOfMemory,               // cudaErrorMemoryAllocation
+                35 => Self::NoDevice,                 // cudaErrorInsufficientDriver
+                46 => Self::DeviceBusy,               // cudaErrorDevicesUnavailable
+                100 => Self::NoDevice,                // cudaErrorNoDevice
+                101 => Self::InvalidDevice,           // cudaErrorInvalidDevice
+                209 => Self::UnsupportedArchitecture, // cudaErrorNoKernelImageForDevice
+                222 => Self::UnsupportedArchitecture, // cudaErrorUnsupportedPtxVersion
+                701 => Self::LaunchFailure,           // cudaErrorLaunchOutOfResources
+                702 => Self::LaunchFailure,           // cudaErrorLaunchTimeout
+                719 => Self::LaunchFailure,           // cudaErrorLaunchFailure
+                720 => Self::LaunchFailure,           // cudaErrorCooperativeLaunchTooLarge
+                code => Self::Cuda(code),
+            },
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::OutOfMemory => write!(f, "out of memory"),
+            Self::InvalidDevice => write!(f, "invalid device"),
+            Self::NoDevice => write!(f, "no CUDA-capable device"),
+            Self::DeviceBusy => write!(f, "device is busy or unavailable"),
+            Self::LaunchFailure => write!(f, "kernel launch failure"),
+            Self::UnsupportedArchitecture => {
+                write!(f, "unsupported GPU architecture")
+            }
+            Self::LengthMismatch => write!(f, "length mismatch"),
+            Self::NotPowerOfTwo => write!(f, "length is not power of 2"),
+            Self::TypeMismatch => write!(f, "type mismatch"),
+            Self::DomainTooLarge => write!(f, "domain too large"),
+            Self::BadLayout => write!(f, "bad data layout"),
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::BadRootOfUnity => write!(f, "bad root of unity"),
+            Self::Cuda(code) => write!(f, "CUDA error #{}", code),
+            Self::Other(code) => write!(f, "error #{}", code),
+        }
+    }
+}
+
 impl Drop for Error {
     fn drop(&mut self) {
         extern "C" {
@@ -26,7 +286,7 @@ impl From<&Error> for String {
             let c_str = unsafe { std::ffi::CStr::from_ptr(str.as_ptr() as *const _) };
             String::from(c_str.to_str().unwrap_or("unintelligible"))
         } else {
-            format!("sppark::Error #{}", status.code)
+            format!("sppark::Error #{} ({})", status.code, status.kind())
         }
     }
 }
@@ -43,6 +303,21 @@ impl std::fmt::Display for Error {
     }
 }
 
+impl std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Error")
+            .field("code", &self.code)
+            .field("message", &String::from(self))
+            .finish()
+    }
+}
+
+impl std::error::Error for Error {}
+
+// The message is owned exclusively and never modified after construction.
+unsafe impl Send for Error {}
+unsafe impl Sync for Error {}
+
 #[macro_export]
 macro_rules! cuda_error {
     // legacy macro, deprecated
@@ -93,3 +368,90 @@ impl<T> Clone for Gpu_Ptr<T> {
         unsafe { transmute::<_, _>(clone_gpu_ptr_t(transmute::<&_, &_>(self))) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kinds() {
+        assert_eq!(ErrorKind::from_code(-2), ErrorKind::OutOfMemory);
+        assert_eq!(ErrorKind::from_code(-101), ErrorKind::InvalidDevice);
+        assert_eq!(ErrorKind::from_code(-719), ErrorKind::LaunchFailure);
+        assert_eq!(ErrorKind::from_code(-999), ErrorKind::Cuda(999));
+        assert_eq!(ErrorKind::from_code(12), ErrorKind::Other(12));
+        assert_eq!(
+            ErrorKind::from_code(Error::OUT_OF_MEMORY),
+            ErrorKind::OutOfMemory
+        );
+
+        let err = Error::length_mismatch(1, 2);
+        assert_eq!(err.kind(), ErrorKind::LengthMismatch);
+        assert!(err.is_invalid_argument());
+        assert!(!err.is_retryable());
+        assert_eq!(String::from(&err), "length mismatch: 1 vs. 2");
+        assert_eq!(err.as_domain_too_large(), None);
+
+        let err = Error::division_by_zero("zero at 3");
+        assert_eq!(err.kind(), ErrorKind::DivisionByZero);
+        assert!(err.is_invalid_argument());
+
+        let err = Error::bad_root_of_unity(5);
+        assert_eq!(err.kind(), ErrorKind::BadRootOfUnity);
+        assert!(err.is_invalid_argument());
+    }
+
+    #[test]
+    fn domain_too_large() {
+        let err = Error::domain_too_large(30, 28);
+        assert_eq!(err.kind(), ErrorKind::DomainTooLarge);
+        assert_eq!(
+            err.as_domain_too_large(),
+            Some(DomainTooLarge {
+                requested: 30,
+                max: 28
+            })
+        );
+
+        // the sizes don't depend on the message
+        let err = Error {
+            code: Error::DOMAIN_TOO_LARGE,
+            detail: err.detail,
+            str: None,
+        }
+        .with_op("NTT::LDE");
+        assert_eq!(
+            err.as_domain_too_large().map(|d| (d.requested, d.max)),
+            Some((30, 28))
+        );
+
+        // as reported by C++, with or without a message
+        let err = Error::new(
+            Error::DOMAIN_TOO_LARGE,
+            "NTT::LDE: lg_domain_size 29 exceeds 27",
+        );
+        assert_eq!(err.as_domain_too_large(), None);
+        let err = Error {
+            code: Error::DOMAIN_TOO_LARGE,
+            detail: 0,
+            str: None,
+        };
+        assert_eq!(err.as_domain_too_large(), None);
+    }
+
+    #[test]
+    fn error_op_name() {
+        let err = Error {
+            code: -2,
+            detail: 0,
+            str: None,
+        }
+        .with_op("NTT::Base");
+        assert!(err.is_oom() && err.is_retryable());
+        assert_eq!(format!("{}", err), "NTT::Base: out of memory");
+
+        // messages from C++ are expected to carry the name already
+        let err = Error::new(-2, "NTT::LDE: oom").with_op("NTT::Base");
+        assert_eq!(format!("{}", err), "NTT::LDE: oom");
+    }
+}
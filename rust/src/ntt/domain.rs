@@ -0,0 +1,105 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::NTTParameters;
+use crate::Error;
+
+/// Evaluation domain, the coset shift·H of the multiplicative subgroup H of
+/// 2^lg_size elements. Unless specified otherwise, H is generated by the
+/// root of unity the NTT uses for the domain of the said size, so that
+/// element i of the domain is the point at which an NTT in natural order
+/// yields its output i.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Domain<F> {
+    lg_size: u32,
+    root: F,
+    shift: F,
+}
+
+impl<F: NTTParameters> Domain<F> {
+    pub fn subgroup(lg_size: u32) -> Result<Self, Error> {
+        Self::coset(lg_size, F::ONE)
+    }
+
+    /// The coset |shift|·H.
+    pub fn coset(lg_size: u32, shift: F) -> Result<Self, Error> {
+        if lg_size > F::S {
+            return Err(Error::domain_too_large(lg_size, F::S));
+        }
+        Self::new(lg_size, F::root_of_unity(lg_size, false), shift)
+    }
+
+    /// The coset that [`Type::Coset`](super::Type::Coset) transforms are
+    /// over.
+    pub fn standard_coset(lg_size: u32) -> Result<Self, Error> {
+        Self::coset(lg_size, F::GROUP_GEN)
+    }
+
+    /// The coset |shift|·H with H generated by a caller-chosen |root|, for
+    /// compatibility with libraries that settled on different conventions.
+    /// |root| has to be a primitive 2^|lg_size|-th root of unity, and
+    /// |shift| has to be non-zero.
+    pub fn new(lg_size: u32, root: F, shift: F) -> Result<Self, Error> {
+        if lg_size > F::S {
+            return Err(Error::domain_too_large(lg_size, F::S));
+        }
+        // root^(2^(lg_size-1)) = -1 iff root is of order 2^lg_size exactly
+        let primitive = match lg_size {
+            0 => root == F::ONE,
+            _ => (1..lg_size).fold(root, |acc, _| acc.sqr()) == -F::ONE,
+        };
+        if !primitive {
+            return Err(Error::bad_root_of_unity(lg_size));
+        }
+        if shift.is_zero() {
+            return Err(Error::division_by_zero("coset shift is zero"));
+        }
+        Ok(Self {
+            lg_size,
+            root,
+            shift,
+        })
+    }
+
+    pub fn lg_size(&self) -> u32 {
+        self.lg_size
+    }
+
+    pub fn size(&self) -> usize {
+        1 << self.lg_size
+    }
+
+    pub fn shift(&self) -> F {
+        self.shift
+    }
+
+    /// Generator of H.
+    pub fn root(&self) -> F {
+        self.root
+    }
+
+    /// shift·root^i
+    pub fn element(&self, i: usize) -> F {
+        self.shift * self.root.pow(i as u64)
+    }
+
+    /// The odd k such that [`root`](Self::root) is the NTT's root of unity
+    /// for the domain size raised to the power of k, 1 for domains that
+    /// don't specify the root. Transforms over the domain are those with
+    /// the NTT's root with evaluation j moved to position j·k^-1.
+    pub fn root_exponent(&self) -> usize {
+        // Pohlig-Hellman, one bit at a time, the order being a power of 2
+        let inverse = F::root_of_unity(self.lg_size, true);
+        let mut k = 0usize;
+        for bit in 0..self.lg_size {
+            // root·inverse^k is of order 2^(lg_size-bit) at most
+            let residue = self.root * inverse.pow(k as u64);
+            let order_check = (bit + 1..self.lg_size).fold(residue, |acc, _| acc.sqr());
+            if order_check != F::ONE {
+                k |= 1 << bit;
+            }
+        }
+        k.max(1)
+    }
+}
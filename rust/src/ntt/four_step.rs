@@ -0,0 +1,259 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Four-step decomposition of an NTT over a domain too large for a single
+//! device. A 2^(r+c)-point transform is carried out as 2^c transforms of
+//! 2^r points, a twiddle pass, and 2^r transforms of 2^c points, with
+//! transposes in between so that the sub-transforms always operate on
+//! contiguous polynomials. The sub-transforms are delegated to the caller,
+//! while the rest is done on the host.
+
+use super::{
+    bit_rev, lde_powers, par_chunks, Direction, InputOutputOrder, NTTParameters, Type, MIN_GRAIN,
+};
+use crate::Error;
+
+/// Compute an in-place NTT of `inout.len()` elements by decomposing the
+/// domain into 2^|lg_rows| x 2^(lg_domain_size - lg_rows). Results are
+/// identical to [`compute_ntt`](super::compute_ntt)'s for all orders,
+/// directions and types.
+///
+/// |sub_ntts| is called with a slice of consecutive polynomials of 2^lg
+/// elements each, lg being its second argument, and is expected to
+/// transform them in place in natural order, including the 1/n scaling in the inverse
+/// direction, e.g. with [`compute_ntt_batch`](super::compute_ntt_batch).
+/// It's not called for 1-element polynomials. Scratch space of the size of
+/// |inout| is allocated on the host.
+pub fn compute_ntt_four_step<F, E>(
+    inout: &mut [F],
+    lg_rows: u32,
+    order: InputOutputOrder,
+    direction: Direction,
+    type_: Type,
+    mut sub_ntts: E,
+) -> Result<(), Error>
+where
+    F: NTTParameters,
+    E: FnMut(&mut [F], u32, Direction) -> Result<(), Error>,
+{
+    let len = inout.len();
+    if !len.is_power_of_two() {
+        return Err(Error::not_power_of_two(len));
+    }
+    let lg_domain_size = len.trailing_zeros();
+    if lg_domain_size > F::S {
+        return Err(Error::domain_too_large(lg_domain_size, F::S));
+    }
+    if lg_rows > lg_domain_size {
+        return Err(Error::new(
+            Error::LENGTH_MISMATCH,
+            &format!(
+                "lg_rows {} exceeds lg_domain_size {}",
+                lg_rows, lg_domain_size
+            ),
+        ));
+    }
+    if lg_domain_size == 0 {
+        return Ok(());
+    }
+
+    // Reduce all orders to a natural-order transform. The coset powers
+    // follow NTT::Base's choice of natural vs. bit-reversed exponents.
+    let intt = direction == Direction::Inverse;
+    let coset = match (type_, direction) {
+        (Type::Standard, _) => None,
+        (Type::Coset, Direction::Forward) => Some(F::GROUP_GEN),
+        (Type::Coset, Direction::Inverse) => Some(F::GROUP_GEN_INVERSE),
+    };
+    let powers_bitrev = order == InputOutputOrder::RR;
+
+    if order == InputOutputOrder::RN {
+        bit_rev(inout);
+    }
+    if let (false, Some(gen)) = (intt, coset) {
+        lde_powers(inout, gen, powers_bitrev);
+    }
+
+    let lg_cols = lg_domain_size - lg_rows;
+    let (nrows, ncols) = (1usize << lg_rows, 1usize << lg_cols);
+    let mut scratch = vec![F::ZERO; len];
+
+    // Columns of the |nrows| x |ncols| input matrix become polynomials...
+    transpose(&mut scratch, inout, nrows, ncols);
+    if lg_rows != 0 {
+        sub_ntts(&mut scratch, lg_rows, direction)?;
+    }
+    // ... whose k-th element in column j is multiplied by omega^(j*k) ...
+    let root = F::root_of_unity(lg_domain_size, intt);
+    par_chunks(&mut scratch, nrows, |j, column| {
+        let step = root.pow(j as u64);
+        let mut acc = F::ONE;
+        for x in column.iter_mut() {
+            *x *= acc;
+            acc *= step;
+        }
+    });
+    // ... then rows are transformed, and the output is read column-wise.
+    transpose(inout, &scratch, ncols, nrows);
+    if lg_cols != 0 {
+        sub_ntts(inout, lg_cols, direction)?;
+    }
+    transpose(&mut scratch, inout, nrows, ncols);
+    inout.copy_from_slice(&scratch);
+    drop(scratch);
+
+    if let (true, Some(gen)) = (intt, coset) {
+        lde_powers(inout, gen, powers_bitrev);
+    }
+    if order == InputOutputOrder::NR {
+        bit_rev(inout);
+    }
+
+    Ok(())
+}
+
+// |out| is |inp| transposed, both are row-major, |inp| being nrows x ncols.
+// Rows of |out| are spread across threads, the reads are strided.
+fn transpose<T: Copy + Send + Sync>(out: &mut [T], inp: &[T], nrows: usize, ncols: usize) {
+    let rows_per_chunk = MIN_GRAIN.div_ceil(nrows).max(1);
+    par_chunks(out, rows_per_chunk * nrows, |i, chunk| {
+        for (j, row) in chunk.chunks_mut(nrows).enumerate() {
+            let col = i * rows_per_chunk + j;
+            for (k, x) in row.iter_mut().enumerate() {
+                *x = inp[k * ncols + col];
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{baby_bear::BabyBear, bls12_381, goldilocks::Goldilocks, HostField};
+    use crate::ntt::{compute_ntt, compute_ntt_batch, Layout};
+    use crate::test_util::random;
+
+    fn host_sub_ntts<F: NTTParameters>(
+        data: &mut [F],
+        lg_domain_size: u32,
+        direction: Direction,
+    ) -> Result<(), Error> {
+        compute_ntt_batch(
+            data,
+            lg_domain_size,
+            data.len() >> lg_domain_size,
+            Layout::ColumnMajor,
+            InputOutputOrder::NN,
+            direction,
+            Type::Standard,
+        )
+    }
+
+    fn check<F: NTTParameters>() {
+        const ORDERS: [InputOutputOrder; 4] = [
+            InputOutputOrder::NN,
+            InputOutputOrder::NR,
+            InputOutputOrder::RN,
+            InputOutputOrder::RR,
+        ];
+
+        for lg in 0..=9u32 {
+            let input: Vec<F> = random(1 << lg, 0x45 + lg as u64);
+            for lg_rows in 0..=lg {
+                for order in ORDERS {
+                    for direction in [Direction::Forward, Direction::Inverse] {
+                        for type_ in [Type::Standard, Type::Coset] {
+                            let mut expected = input.clone();
+                            compute_ntt(&mut expected, order, direction, type_).unwrap();
+
+                            let mut actual = input.clone();
+                            compute_ntt_four_step(
+                                &mut actual,
+                                lg_rows,
+                                order,
+                                direction,
+                                type_,
+                                host_sub_ntts,
+                            )
+                            .unwrap();
+                            assert!(
+                                actual == expected,
+                                "lg {} lg_rows {} {:?} {:?} {:?}",
+                                lg,
+                                lg_rows,
+                                order,
+                                direction,
+                                type_
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn four_step_vs_single_shot() {
+        check::<bls12_381::Fr>();
+        check::<Goldilocks>();
+        check::<BabyBear>();
+    }
+
+    #[test]
+    fn four_step_sub_ntts() {
+        // 2^12 = 2^5 x 2^7, sizes and counts as requested
+        let mut calls = vec![];
+        let mut data = vec![Goldilocks::ONE; 1 << 12];
+        compute_ntt_four_step(
+            &mut data,
+            5,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+            |chunk: &mut [Goldilocks], lg, direction| {
+                calls.push((chunk.len(), lg));
+                host_sub_ntts(chunk, lg, direction)
+            },
+        )
+        .unwrap();
+        assert_eq!(calls, [(1 << 12, 5), (1 << 12, 7)]);
+        // NTT of all-ones is n at 0 and zeros elsewhere
+        assert!(data[0] == Goldilocks::from_u64(1 << 12));
+        assert!(data[1..].iter().all(|x| *x == Goldilocks::ZERO));
+
+        let err = compute_ntt_four_step(
+            &mut data[..100],
+            1,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+            host_sub_ntts,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Error::NOT_POWER_OF_TWO);
+
+        let err = compute_ntt_four_step(
+            &mut data,
+            13,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+            host_sub_ntts,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+
+        // errors from the sub-transforms are passed through
+        let err = compute_ntt_four_step(
+            &mut data,
+            6,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+            |_: &mut [Goldilocks], _, _| Err(Error::new(Error::BAD_LAYOUT, "nope")),
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Error::BAD_LAYOUT);
+    }
+}
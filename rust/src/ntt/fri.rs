@@ -0,0 +1,151 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Host implementation of NTT::FriFold from ntt/ntt.cuh. Folding by
+//! 2^lg_arity is carried out as lg_arity rounds of folding by 2, each
+//! taking pairs of evaluations at ±x, which are adjacent in bit-reversed
+//! order, to v0 + v1 + β·(v0 - v1)/x, with β and the coset shift squared
+//! from round to round. The factor of 2 per round is divided out at the
+//! end, same as on the GPU.
+
+use core::ops::Mul;
+
+use super::{par_chunks, powers, NTTParameters, MIN_GRAIN};
+use crate::ff::HostField;
+use crate::Error;
+
+/// Fold 2^|lg_domain_size| evaluations of f over the coset |shift|·H, in
+/// bit-reversed order, e.g. as produced by an NR transform, by 2^|lg_arity|
+/// with |challenge| β. With f(x) = Σ x^r·f_r(x^arity) for r < arity, |out|
+/// receives evaluations of Σ β^r·f_r over the coset shift^arity·H^arity,
+/// i.e. the next FRI layer, in bit-reversed order again. Evaluations and
+/// the challenge may be in an extension of the field the domain is in.
+pub fn fri_fold<F, T>(
+    out: &mut [T],
+    inp: &[T],
+    lg_domain_size: u32,
+    lg_arity: u32,
+    challenge: T,
+    shift: F,
+) -> Result<(), Error>
+where
+    F: NTTParameters,
+    T: HostField + Mul<F, Output = T>,
+{
+    if lg_domain_size > F::S {
+        return Err(Error::domain_too_large(lg_domain_size, F::S));
+    }
+    if inp.len() != 1 << lg_domain_size {
+        return Err(Error::length_mismatch(inp.len(), 1 << lg_domain_size));
+    }
+    if lg_arity > lg_domain_size {
+        return Err(Error::new(
+            Error::LENGTH_MISMATCH,
+            &format!(
+                "folding arity 2^{} exceeds domain size 2^{}",
+                lg_arity, lg_domain_size
+            ),
+        ));
+    }
+    if out.len() != inp.len() >> lg_arity {
+        return Err(Error::length_mismatch(out.len(), inp.len() >> lg_arity));
+    }
+    if shift.is_zero() {
+        return Err(Error::division_by_zero("coset shift is zero"));
+    }
+    if lg_arity == 0 {
+        out.copy_from_slice(inp);
+        return Ok(());
+    }
+
+    let mut challenge = challenge;
+    let mut shift_inv = shift.reciprocal();
+    let mut layer = inp.to_vec();
+    for round in 0..lg_arity {
+        let lg = lg_domain_size - round;
+        let half = 1usize << (lg - 1);
+        // x^-1 for x = shift·ω^j, ω being the root for 2^lg elements
+        let inverse_roots = powers(F::root_of_unity(lg, true), half);
+
+        let mut next = vec![T::ZERO; half];
+        let prev = &layer;
+        par_chunks(&mut next, MIN_GRAIN, |i, chunk| {
+            for (j, y) in chunk.iter_mut().enumerate() {
+                let idx = i * MIN_GRAIN + j;
+                let rev = match lg {
+                    1 => 0,
+                    _ => idx.reverse_bits() >> (usize::BITS - (lg - 1)),
+                };
+                let (v0, v1) = (prev[2 * idx], prev[2 * idx + 1]);
+                *y = v0 + v1 + challenge * ((v0 - v1) * (shift_inv * inverse_roots[rev]));
+            }
+        });
+
+        layer = next;
+        challenge = challenge.sqr();
+        shift_inv = shift_inv.sqr();
+    }
+
+    let scale = F::from_u64(1 << lg_arity).reciprocal();
+    for (y, v) in out.iter_mut().zip(layer) {
+        *y = v * scale;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{baby_bear::BabyBear, bls12_381, goldilocks::Goldilocks};
+    use crate::ntt::{compute_coset_ntt, Direction, InputOutputOrder};
+    use crate::test_util::random;
+
+    fn check<F: NTTParameters>() {
+        for lg in 0..=8u32 {
+            let coeffs: Vec<F> = random(1 << lg, 0xf01d + lg as u64);
+            let challenge = random::<F>(1, lg as u64)[0];
+            let shift = F::GROUP_GEN;
+
+            let mut evals = coeffs.clone();
+            compute_coset_ntt(&mut evals, shift, InputOutputOrder::NR, Direction::Forward).unwrap();
+
+            for lg_arity in 0..=lg.min(4) {
+                let arity = 1usize << lg_arity;
+
+                // Σ β^r·f_r in coefficient form, evaluated directly
+                let mut expected: Vec<F> = coeffs
+                    .chunks(arity)
+                    .map(|c| c.iter().rev().fold(F::ZERO, |acc, x| acc * challenge + *x))
+                    .collect();
+                compute_coset_ntt(
+                    &mut expected,
+                    shift.pow(arity as u64),
+                    InputOutputOrder::NR,
+                    Direction::Forward,
+                )
+                .unwrap();
+
+                let mut folded = vec![F::ZERO; coeffs.len() >> lg_arity];
+                fri_fold(&mut folded, &evals, lg, lg_arity, challenge, shift).unwrap();
+                assert!(folded == expected, "lg {} lg_arity {}", lg, lg_arity);
+            }
+        }
+
+        let evals = vec![F::ONE; 8];
+        let mut folded = vec![F::ZERO; 2];
+        let err = fri_fold(&mut folded, &evals, 3, 4, F::ONE, F::ONE).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = fri_fold(&mut folded, &evals, 3, 1, F::ONE, F::ONE).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = fri_fold(&mut folded, &evals, 3, 2, F::ONE, F::ZERO).unwrap_err();
+        assert_eq!(err.code, Error::DIVISION_BY_ZERO);
+    }
+
+    #[test]
+    fn fold_vs_coefficients() {
+        check::<bls12_381::Fr>();
+        check::<Goldilocks>();
+        check::<BabyBear>();
+    }
+}
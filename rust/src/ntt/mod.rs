@@ -0,0 +1,834 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Host implementation of NTT::Base, NTT::Coset, NTT::Batch and NTT::LDE
+//! from ntt/ntt.cuh.
+//! It follows the same steps as the GPU one, so that results are
+//! bit-identical for all input and output orders, directions and types.
+
+mod domain;
+mod four_step;
+mod fri;
+mod parameters;
+pub use domain::Domain;
+pub use four_step::compute_ntt_four_step;
+pub use fri::fri_fold;
+pub use parameters::NTTParameters;
+
+use crate::ff::HostField;
+use crate::Error;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputOutputOrder {
+    NN = 0,
+    NR = 1,
+    RN = 2,
+    RR = 3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward = 0,
+    Inverse = 1,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Type {
+    Standard = 0,
+    Coset = 1,
+}
+
+/// Layout of a batch of polynomials, see [`compute_ntt_batch`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Polynomials one after another.
+    ColumnMajor = 0,
+    /// Polynomials interleaved, i.e. element i of polynomial j is at
+    /// i * ncolumns + j.
+    RowMajor = 1,
+}
+
+/// Compute an in-place NTT on the host, same contract as compute_ntt in
+/// poc/ntt-cuda/cuda/ntt_api.cu.
+pub fn compute_ntt<F: NTTParameters>(
+    inout: &mut [F],
+    order: InputOutputOrder,
+    direction: Direction,
+    type_: Type,
+) -> Result<(), Error> {
+    let coset = match (type_, direction) {
+        (Type::Standard, _) => None,
+        (Type::Coset, Direction::Forward) => Some(F::GROUP_GEN),
+        (Type::Coset, Direction::Inverse) => Some(F::GROUP_GEN_INVERSE),
+    };
+    ntt_internal(inout, order, direction, coset)
+}
+
+/// Coset NTT over |shift|·H on the host, same contract as NTT::Coset in
+/// ntt/ntt.cuh. Coefficient i is multiplied by shift^i before the forward
+/// transform, or by shift^-i after the inverse one.
+pub fn compute_coset_ntt<F: NTTParameters>(
+    inout: &mut [F],
+    shift: F,
+    order: InputOutputOrder,
+    direction: Direction,
+) -> Result<(), Error> {
+    let coset = match direction {
+        Direction::Forward => shift,
+        Direction::Inverse => shift.reciprocal(),
+    };
+    ntt_internal(inout, order, direction, Some(coset))
+}
+
+// |coset| is the base of the powers distributed over the coefficients,
+// i.e. the shift or its inverse, depending on |direction|.
+fn ntt_internal<F: NTTParameters>(
+    inout: &mut [F],
+    order: InputOutputOrder,
+    direction: Direction,
+    coset: Option<F>,
+) -> Result<(), Error> {
+    let len = inout.len();
+    if !len.is_power_of_two() {
+        return Err(Error::not_power_of_two(len));
+    }
+    let lg_domain_size = len.trailing_zeros();
+    if lg_domain_size > F::S {
+        return Err(Error::domain_too_large(lg_domain_size, F::S));
+    }
+    if lg_domain_size == 0 {
+        return Ok(());
+    }
+
+    let intt = direction == Direction::Inverse;
+    let (bitrev, gs) = match order {
+        InputOutputOrder::NN => {
+            bit_rev(inout);
+            (true, false)
+        }
+        InputOutputOrder::NR => (false, true),
+        InputOutputOrder::RN => (true, false),
+        InputOutputOrder::RR => (true, true),
+    };
+
+    if let (false, Some(gen)) = (intt, coset) {
+        lde_powers(inout, gen, bitrev);
+    }
+
+    let twiddles = powers(F::root_of_unity(lg_domain_size, intt), len / 2);
+    if gs {
+        gs_ntt(inout, &twiddles);
+    } else {
+        ct_ntt(inout, &twiddles);
+    }
+    if intt {
+        let scale = F::from_u64(len as u64).reciprocal();
+        par_chunks(inout, MIN_GRAIN, |_, chunk| {
+            chunk.iter_mut().for_each(|x| *x *= scale)
+        });
+    }
+
+    if let (true, Some(gen)) = (intt, coset) {
+        lde_powers(inout, gen, !bitrev);
+    }
+
+    if order == InputOutputOrder::RR {
+        bit_rev(inout);
+    }
+
+    Ok(())
+}
+
+/// Transform |ncolumns| polynomials of 2^|lg_domain_size| elements each,
+/// same contract as NTT::Batch in ntt/ntt.cuh.
+pub fn compute_ntt_batch<F: NTTParameters>(
+    inout: &mut [F],
+    lg_domain_size: u32,
+    ncolumns: usize,
+    layout: Layout,
+    order: InputOutputOrder,
+    direction: Direction,
+    type_: Type,
+) -> Result<(), Error> {
+    if lg_domain_size > F::S {
+        return Err(Error::domain_too_large(lg_domain_size, F::S));
+    }
+    let domain_size = 1usize << lg_domain_size;
+    if Some(inout.len()) != ncolumns.checked_mul(domain_size) {
+        return Err(Error::length_mismatch(
+            inout.len(),
+            ncolumns.saturating_mul(domain_size),
+        ));
+    }
+    if inout.is_empty() {
+        return Ok(());
+    }
+
+    match layout {
+        Layout::ColumnMajor => {
+            for column in inout.chunks_mut(domain_size) {
+                compute_ntt(column, order, direction, type_)?;
+            }
+        }
+        Layout::RowMajor => {
+            let mut columns = vec![F::ZERO; inout.len()];
+            transpose(&mut columns, inout, domain_size, ncolumns);
+            for column in columns.chunks_mut(domain_size) {
+                compute_ntt(column, order, direction, type_)?;
+            }
+            transpose(inout, &columns, ncolumns, domain_size);
+        }
+    }
+
+    Ok(())
+}
+
+// |out| is |inp| transposed, both are row-major, |inp| being nrows x ncols.
+fn transpose<T: Copy>(out: &mut [T], inp: &[T], nrows: usize, ncols: usize) {
+    for (i, row) in inp.chunks(ncols).enumerate() {
+        for (j, &x) in row.iter().enumerate() {
+            out[j * nrows + i] = x;
+        }
+    }
+}
+
+/// Low-degree extension on the host, same contract as NTT::LDE in
+/// ntt/ntt.cuh. The first `inout.len() >> lg_blowup` elements are taken
+/// for evaluations of a polynomial over the subgroup of that size, and are
+/// replaced with evaluations over the coset of the `inout.len()`-sized
+/// subgroup shifted by GROUP_GEN, or GROUP_GEN^(1 << lg_blowup) if
+/// |ext_pow| is set. Both are in natural order.
+pub fn lde<F: NTTParameters>(inout: &mut [F], lg_blowup: u32, ext_pow: bool) -> Result<(), Error> {
+    let ext_len = inout.len();
+    if !ext_len.is_power_of_two() {
+        return Err(Error::not_power_of_two(ext_len));
+    }
+    if ext_len.trailing_zeros() < lg_blowup {
+        return Err(Error::new(
+            Error::LENGTH_MISMATCH,
+            &format!("inout.len() {} is less than 2^{}", ext_len, lg_blowup),
+        ));
+    }
+    let lg_ext_domain_size = ext_len.trailing_zeros();
+    if lg_ext_domain_size > F::S {
+        return Err(Error::domain_too_large(lg_ext_domain_size, F::S));
+    }
+
+    let (coeffs, rest) = inout.split_at_mut(ext_len >> lg_blowup);
+    compute_ntt(
+        coeffs,
+        InputOutputOrder::NN,
+        Direction::Inverse,
+        Type::Standard,
+    )?;
+    let shift = if ext_pow {
+        F::GROUP_GEN.pow(1 << lg_blowup)
+    } else {
+        F::GROUP_GEN
+    };
+    lde_powers(coeffs, shift, false);
+    rest.fill(F::ZERO);
+
+    compute_ntt(
+        inout,
+        InputOutputOrder::NN,
+        Direction::Forward,
+        Type::Standard,
+    )
+}
+
+/// Permute the data such that inout[i] and inout[bit_reverse(i)] swap
+/// places. The length is expected to be a power of 2.
+pub fn bit_rev<T>(inout: &mut [T]) {
+    let len = inout.len();
+    debug_assert!(len.is_power_of_two());
+    if len <= 2 {
+        return;
+    }
+    let shift = usize::BITS - len.trailing_zeros();
+    for i in 0..len {
+        let rev = i.reverse_bits() >> shift;
+        if i < rev {
+            inout.swap(i, rev);
+        }
+    }
+}
+
+/// Apply [`bit_rev`] to each of |ncolumns| polynomials of 2^|lg_domain_size|
+/// elements, laid out one after another, same contract as NTT::BitRev in
+/// ntt/ntt.cuh.
+pub fn bit_rev_batch<T: Send>(
+    inout: &mut [T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), Error> {
+    let domain_size = check_batch_len(inout.len(), lg_domain_size, ncolumns)?;
+    if inout.is_empty() {
+        return Ok(());
+    }
+
+    par_chunks(inout, domain_size, |_, column| bit_rev(column));
+    Ok(())
+}
+
+/// Out-of-place [`bit_rev_batch`], |inp| is left intact.
+pub fn bit_rev_batch_into<T: Copy + Send + Sync>(
+    out: &mut [T],
+    inp: &[T],
+    lg_domain_size: u32,
+    ncolumns: usize,
+) -> Result<(), Error> {
+    if out.len() != inp.len() {
+        return Err(Error::length_mismatch(out.len(), inp.len()));
+    }
+    check_batch_len(inp.len(), lg_domain_size, ncolumns)?;
+    if inp.is_empty() {
+        return Ok(());
+    }
+
+    // gather, so that any piece of |out| can be filled independently
+    let mask = (1usize << lg_domain_size) - 1;
+    let shift = usize::BITS - lg_domain_size;
+    par_chunks(out, MIN_GRAIN, |i, chunk| {
+        for (j, x) in chunk.iter_mut().enumerate() {
+            let idx = i * MIN_GRAIN + j;
+            let rev = match lg_domain_size {
+                0 => 0,
+                _ => (idx & mask).reverse_bits() >> shift,
+            };
+            *x = inp[(idx & !mask) | rev];
+        }
+    });
+    Ok(())
+}
+
+// Validate the length of a column-major batch, return the domain size.
+fn check_batch_len(len: usize, lg_domain_size: u32, ncolumns: usize) -> Result<usize, Error> {
+    let domain_size = 1usize
+        .checked_shl(lg_domain_size)
+        .ok_or_else(|| Error::domain_too_large(lg_domain_size, usize::BITS - 1))?;
+    if Some(len) != ncolumns.checked_mul(domain_size) {
+        return Err(Error::length_mismatch(
+            len,
+            ncolumns.saturating_mul(domain_size),
+        ));
+    }
+    Ok(domain_size)
+}
+
+// Work units smaller than this are not worth a thread.
+pub(crate) const MIN_GRAIN: usize = 1 << 12;
+
+pub(crate) fn num_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Call |f| with each |chunk|-sized piece of |data| and its index, spreading
+// contiguous runs of pieces across threads.
+pub(crate) fn par_chunks<T, F>(data: &mut [T], chunk: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync,
+{
+    let nchunks = data.len().div_ceil(chunk);
+    let nthreads = num_threads().min(data.len() / MIN_GRAIN).min(nchunks);
+    if nthreads <= 1 {
+        data.chunks_mut(chunk)
+            .enumerate()
+            .for_each(|(i, c)| f(i, c));
+        return;
+    }
+    let per_thread = nchunks.div_ceil(nthreads);
+    std::thread::scope(|s| {
+        for (t, part) in data.chunks_mut(per_thread * chunk).enumerate() {
+            let f = &f;
+            s.spawn(move || {
+                for (i, c) in part.chunks_mut(chunk).enumerate() {
+                    f(t * per_thread + i, c);
+                }
+            });
+        }
+    });
+}
+
+// Same as above, but over two slices of equal length in lockstep.
+fn par_chunks2<T, F>(a: &mut [T], b: &mut [T], chunk: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T], &mut [T]) + Sync,
+{
+    debug_assert_eq!(a.len(), b.len());
+    let nchunks = a.len().div_ceil(chunk);
+    let nthreads = num_threads().min(2 * a.len() / MIN_GRAIN).min(nchunks);
+    if nthreads <= 1 {
+        for (i, (x, y)) in a.chunks_mut(chunk).zip(b.chunks_mut(chunk)).enumerate() {
+            f(i, x, y);
+        }
+        return;
+    }
+    let per_thread = nchunks.div_ceil(nthreads) * chunk;
+    std::thread::scope(|s| {
+        let parts = a.chunks_mut(per_thread).zip(b.chunks_mut(per_thread));
+        for (t, (x, y)) in parts.enumerate() {
+            let f = &f;
+            s.spawn(move || {
+                let pieces = x.chunks_mut(chunk).zip(y.chunks_mut(chunk));
+                for (i, (x, y)) in pieces.enumerate() {
+                    f(t * per_thread / chunk + i, x, y);
+                }
+            });
+        }
+    });
+}
+
+// [1, base, base^2, ..., base^(len-1)]
+fn powers<F: HostField>(base: F, len: usize) -> Vec<F> {
+    let mut ret = vec![F::ZERO; len];
+    par_chunks(&mut ret, MIN_GRAIN, |i, chunk| {
+        let mut acc = base.pow((i * MIN_GRAIN) as u64);
+        for x in chunk.iter_mut() {
+            *x = acc;
+            acc *= base;
+        }
+    });
+    ret
+}
+
+// Multiply inout[i] by gen^i, or by gen^bit_reverse(i) if |bitrev| is set.
+fn lde_powers<F: HostField>(inout: &mut [F], gen: F, bitrev: bool) {
+    if bitrev {
+        bit_rev(inout);
+    }
+    par_chunks(inout, MIN_GRAIN, |i, chunk| {
+        let mut acc = gen.pow((i * MIN_GRAIN) as u64);
+        for x in chunk.iter_mut() {
+            *x *= acc;
+            acc *= gen;
+        }
+    });
+    if bitrev {
+        bit_rev(inout);
+    }
+}
+
+// A single radix-2 layer, |twiddles| are the powers of the domain's root.
+fn layer<F: HostField>(inout: &mut [F], half: usize, twiddles: &[F], dif: bool) {
+    let stride = inout.len() / (2 * half);
+    let butterflies = |offset: usize, lo: &mut [F], hi: &mut [F]| {
+        let twiddles = twiddles[offset * stride..].iter().step_by(stride);
+        for ((x, y), w) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles) {
+            if dif {
+                let t = *x - *y;
+                *x += *y;
+                *y = t * *w;
+            } else {
+                let t = *y * *w;
+                *y = *x - t;
+                *x += t;
+            }
+        }
+    };
+
+    if half < MIN_GRAIN {
+        // many short blocks, spread them across threads
+        par_chunks(inout, 2 * MIN_GRAIN.max(half), |_, chunk| {
+            for block in chunk.chunks_mut(2 * half) {
+                let (lo, hi) = block.split_at_mut(half);
+                butterflies(0, lo, hi);
+            }
+        });
+    } else {
+        // few long blocks, split each one across threads
+        for block in inout.chunks_mut(2 * half) {
+            let (lo, hi) = block.split_at_mut(half);
+            par_chunks2(lo, hi, MIN_GRAIN, |i, lo, hi| {
+                butterflies(i * MIN_GRAIN, lo, hi)
+            });
+        }
+    }
+}
+
+// Gentleman-Sande, natural order in, bit-reversed order out.
+fn gs_ntt<F: HostField>(inout: &mut [F], twiddles: &[F]) {
+    let mut half = inout.len() / 2;
+    while half >= 1 {
+        layer(inout, half, twiddles, true);
+        half /= 2;
+    }
+}
+
+// Cooley-Tukey, bit-reversed order in, natural order out.
+fn ct_ntt<F: HostField>(inout: &mut [F], twiddles: &[F]) {
+    let mut half = 1;
+    while half < inout.len() {
+        layer(inout, half, twiddles, false);
+        half *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{
+        alt_bn128, baby_bear::BabyBear, bls12_377, bls12_381, goldilocks::Goldilocks, pasta,
+    };
+    use crate::test_util::random;
+
+    // Forward and inverse order pairs that round-trip. Note that RR treats
+    // the input as natural order when it comes to coset powers, hence it
+    // round-trips only standard transforms.
+    const ROUND_TRIPS: [(InputOutputOrder, InputOutputOrder, bool); 4] = [
+        (InputOutputOrder::NN, InputOutputOrder::NN, true),
+        (InputOutputOrder::NR, InputOutputOrder::RN, true),
+        (InputOutputOrder::RN, InputOutputOrder::NR, true),
+        (InputOutputOrder::RR, InputOutputOrder::RR, false),
+    ];
+
+    // Straightforward O(n^2) evaluation at shift*root^i by Horner's rule,
+    // natural order in and out.
+    fn naive_dft<F: HostField>(input: &[F], root: F, shift: F) -> Vec<F> {
+        let mut point = shift;
+        (0..input.len())
+            .map(|_| {
+                let eval = input.iter().rev().fold(F::ZERO, |acc, &a| acc * point + a);
+                point *= root;
+                eval
+            })
+            .collect()
+    }
+
+    fn reversed<F: Copy>(v: &[F]) -> Vec<F> {
+        let mut v = v.to_vec();
+        bit_rev(&mut v);
+        v
+    }
+
+    fn check_ntt<F: NTTParameters>() {
+        assert_eq!(F::root_of_unity(1, false), -F::ONE);
+        assert_eq!(F::GROUP_GEN * F::GROUP_GEN_INVERSE, F::ONE);
+
+        for lg in [0, 1, 2, 5, 9] {
+            let len = 1usize << lg;
+            let input = random::<F>(len, 0x5eed + lg as u64);
+            let root = F::root_of_unity(lg, false);
+            let evals = naive_dft(&input, root, F::ONE);
+            let coset = naive_dft(&input, root, F::GROUP_GEN);
+
+            let run = |data: &[F], order, direction, type_| {
+                let mut data = data.to_vec();
+                compute_ntt(&mut data, order, direction, type_).unwrap();
+                data
+            };
+            use Direction::*;
+
+            // NN and NR take natural order input, RN and RR bit-reversed
+            // one, output is as GS or CT algorithm leaves it...
+            let fwd = Type::Standard;
+            assert_eq!(run(&input, InputOutputOrder::NN, Forward, fwd), evals);
+            assert_eq!(
+                run(&input, InputOutputOrder::NR, Forward, fwd),
+                reversed(&evals)
+            );
+            assert_eq!(
+                run(&reversed(&input), InputOutputOrder::RN, Forward, fwd),
+                evals
+            );
+            // ... while RR is GS followed by bit reversal, same as NN.
+            assert_eq!(run(&input, InputOutputOrder::RR, Forward, fwd), evals);
+
+            assert_eq!(
+                run(&input, InputOutputOrder::NN, Forward, Type::Coset),
+                coset
+            );
+            assert_eq!(
+                run(&input, InputOutputOrder::NR, Forward, Type::Coset),
+                reversed(&coset)
+            );
+
+            for (fwd, inv, coset) in ROUND_TRIPS {
+                let out = run(&input, fwd, Forward, Type::Standard);
+                assert_eq!(run(&out, inv, Inverse, Type::Standard), input);
+                if coset {
+                    let out = run(&input, fwd, Forward, Type::Coset);
+                    assert_eq!(run(&out, inv, Inverse, Type::Coset), input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ntt_vs_naive() {
+        check_ntt::<bls12_381::Fr>();
+        check_ntt::<bls12_377::Fr>();
+        check_ntt::<alt_bn128::Fr>();
+        check_ntt::<pasta::Pallas>();
+        check_ntt::<pasta::Vesta>();
+        check_ntt::<Goldilocks>();
+        check_ntt::<BabyBear>();
+    }
+
+    #[test]
+    fn ntt_multithreaded() {
+        // large enough to engage the threaded paths in all layers
+        let input = random::<Goldilocks>(1 << 16, 42);
+        for (fwd, inv, coset) in ROUND_TRIPS {
+            let type_ = if coset { Type::Coset } else { Type::Standard };
+            let mut data = input.clone();
+            compute_ntt(&mut data, fwd, Direction::Forward, type_).unwrap();
+            compute_ntt(&mut data, inv, Direction::Inverse, type_).unwrap();
+            assert_eq!(data, input);
+        }
+
+        let mut data = input.clone();
+        compute_ntt(
+            &mut data,
+            InputOutputOrder::NR,
+            Direction::Forward,
+            Type::Standard,
+        )
+        .unwrap();
+        let root = Goldilocks::root_of_unity(16, false);
+        let x = root.pow(12345);
+        let eval = input
+            .iter()
+            .rev()
+            .fold(Goldilocks::ZERO, |acc, &a| acc * x + a);
+        assert_eq!(data[12345usize.reverse_bits() >> (usize::BITS - 16)], eval);
+    }
+
+    #[test]
+    fn coset_ntt_vs_naive() {
+        fn check_coset<F: NTTParameters>() {
+            for lg in [0, 3, 8] {
+                let len = 1usize << lg;
+                let input = random::<F>(len, 0xc05e7 + lg as u64);
+                let root = F::root_of_unity(lg, false);
+
+                for shift in [F::from_u64(7), random::<F>(1, lg as u64)[0]] {
+                    let coset = naive_dft(&input, root, shift);
+
+                    let mut data = input.clone();
+                    compute_coset_ntt(&mut data, shift, InputOutputOrder::NN, Direction::Forward)
+                        .unwrap();
+                    assert_eq!(data, coset);
+
+                    for (fwd, inv, _) in ROUND_TRIPS.into_iter().filter(|&(.., coset)| coset) {
+                        let mut data = input.clone();
+                        compute_coset_ntt(&mut data, shift, fwd, Direction::Forward).unwrap();
+                        compute_coset_ntt(&mut data, shift, inv, Direction::Inverse).unwrap();
+                        assert_eq!(data, input);
+                    }
+                }
+
+                // the group generator is just another shift
+                let mut a = input.clone();
+                compute_ntt(
+                    &mut a,
+                    InputOutputOrder::NR,
+                    Direction::Forward,
+                    Type::Coset,
+                )
+                .unwrap();
+                let mut b = input.clone();
+                compute_coset_ntt(
+                    &mut b,
+                    F::GROUP_GEN,
+                    InputOutputOrder::NR,
+                    Direction::Forward,
+                )
+                .unwrap();
+                assert_eq!(a, b);
+            }
+        }
+
+        check_coset::<bls12_381::Fr>();
+        check_coset::<alt_bn128::Fr>();
+        check_coset::<Goldilocks>();
+        check_coset::<BabyBear>();
+    }
+
+    #[test]
+    fn lde_vs_naive() {
+        fn check_lde<F: NTTParameters>() {
+            for (lg, lg_blowup) in [(0, 1), (1, 0), (3, 1), (4, 2), (6, 3)] {
+                let len = 1usize << lg;
+                let ext_len = len << lg_blowup;
+                let input = random::<F>(len, 0x1de + lg as u64);
+
+                let mut coeffs = input.clone();
+                compute_ntt(
+                    &mut coeffs,
+                    InputOutputOrder::NN,
+                    Direction::Inverse,
+                    Type::Standard,
+                )
+                .unwrap();
+                coeffs.resize(ext_len, F::ZERO);
+                let root = F::root_of_unity(lg + lg_blowup, false);
+
+                for ext_pow in [false, true] {
+                    let mut data = vec![F::ZERO; ext_len];
+                    data[..len].copy_from_slice(&input);
+                    lde(&mut data, lg_blowup, ext_pow).unwrap();
+
+                    let shift = F::GROUP_GEN.pow(if ext_pow { 1 << lg_blowup } else { 1 });
+                    assert_eq!(data, naive_dft(&coeffs, root, shift));
+                }
+            }
+        }
+
+        check_lde::<bls12_381::Fr>();
+        check_lde::<alt_bn128::Fr>();
+        check_lde::<Goldilocks>();
+        check_lde::<BabyBear>();
+    }
+
+    #[test]
+    fn ntt_batch() {
+        let (lg, ncolumns) = (6, 5);
+        let len = 1usize << lg;
+        let columns = random::<Goldilocks>(len * ncolumns, 0xba7c);
+        let mut rows = vec![Goldilocks::ZERO; columns.len()];
+        transpose(&mut rows, &columns, ncolumns, len);
+
+        for type_ in [Type::Standard, Type::Coset] {
+            for (fwd, inv, _) in ROUND_TRIPS {
+                let mut expected = columns.clone();
+                for column in expected.chunks_mut(len) {
+                    compute_ntt(column, fwd, Direction::Forward, type_).unwrap();
+                }
+
+                let mut data = columns.clone();
+                compute_ntt_batch(
+                    &mut data,
+                    lg,
+                    ncolumns,
+                    Layout::ColumnMajor,
+                    fwd,
+                    Direction::Forward,
+                    type_,
+                )
+                .unwrap();
+                assert_eq!(data, expected);
+
+                let mut data = rows.clone();
+                compute_ntt_batch(
+                    &mut data,
+                    lg,
+                    ncolumns,
+                    Layout::RowMajor,
+                    fwd,
+                    Direction::Forward,
+                    type_,
+                )
+                .unwrap();
+                let mut transposed = vec![Goldilocks::ZERO; data.len()];
+                transpose(&mut transposed, &data, len, ncolumns);
+                assert_eq!(transposed, expected);
+
+                if type_ == Type::Standard || fwd != InputOutputOrder::RR {
+                    compute_ntt_batch(
+                        &mut data,
+                        lg,
+                        ncolumns,
+                        Layout::RowMajor,
+                        inv,
+                        Direction::Inverse,
+                        type_,
+                    )
+                    .unwrap();
+                    assert_eq!(data, rows);
+                }
+            }
+        }
+
+        let mut data = vec![Goldilocks::ZERO; 12];
+        let err = compute_ntt_batch(
+            &mut data,
+            2,
+            4,
+            Layout::RowMajor,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = compute_ntt_batch(
+            &mut data,
+            33,
+            0,
+            Layout::ColumnMajor,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Error::DOMAIN_TOO_LARGE);
+    }
+
+    #[test]
+    fn ntt_errors() {
+        let mut data = vec![BabyBear::ONE; 3];
+        let err = compute_ntt(
+            &mut data,
+            InputOutputOrder::NN,
+            Direction::Forward,
+            Type::Standard,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, Error::NOT_POWER_OF_TWO);
+
+        let mut data = vec![BabyBear::ONE; 8];
+        assert_eq!(
+            lde(&mut data, 4, false).unwrap_err().code,
+            Error::LENGTH_MISMATCH
+        );
+        let mut data = vec![BabyBear::ONE; 6];
+        assert_eq!(
+            lde(&mut data, 1, false).unwrap_err().code,
+            Error::NOT_POWER_OF_TWO
+        );
+    }
+
+    #[test]
+    fn bit_rev_batched() {
+        for (lg, ncolumns) in [(0, 3), (1, 2), (5, 1), (7, 3), (13, 2)] {
+            let len = ncolumns << lg;
+            let input: Vec<u64> = (0..len as u64).collect();
+
+            let mut expected = input.clone();
+            for column in expected.chunks_mut(1 << lg) {
+                bit_rev(column);
+            }
+            // spot-check the reference itself
+            if lg == 7 {
+                assert_eq!(expected[1], 64);
+                assert_eq!(expected[128 + 3], 128 + 96);
+            }
+
+            let mut data = input.clone();
+            bit_rev_batch(&mut data, lg, ncolumns).unwrap();
+            assert_eq!(data, expected);
+
+            let mut out = vec![0; len];
+            bit_rev_batch_into(&mut out, &input, lg, ncolumns).unwrap();
+            assert_eq!(out, expected);
+            bit_rev_batch_into(&mut data, &out, lg, ncolumns).unwrap();
+            assert_eq!(data, input);
+        }
+
+        let mut data = vec![0u32; 12];
+        let err = bit_rev_batch(&mut data, 2, 4).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = bit_rev_batch_into(&mut data, &[0; 8], 2, 2).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = bit_rev_batch(&mut data, 64, 0).unwrap_err();
+        assert_eq!(err.code, Error::DOMAIN_TOO_LARGE);
+    }
+}
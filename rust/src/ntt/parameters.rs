@@ -0,0 +1,199 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ff::{
+    alt_bn128, baby_bear::BabyBear, bls12_377, bls12_381, goldilocks::Goldilocks, pasta, HostField,
+};
+
+/// Counterpart of ntt/parameters/*.h. The values are in the respective
+/// field's internal representation.
+pub trait NTTParameters: HostField {
+    /// log2 of the largest power-of-2 multiplicative subgroup.
+    const S: u32;
+    /// Coset shift used by the Coset NTT type.
+    const GROUP_GEN: Self;
+    const GROUP_GEN_INVERSE: Self;
+    /// Primitive 2^S-th root of unity.
+    const ROOT_OF_UNITY: Self;
+    const INVERSE_ROOT_OF_UNITY: Self;
+
+    /// Primitive 2^lg_domain_size-th root of unity, the one that the GPU
+    /// implementation uses for the domain of the said size.
+    fn root_of_unity(lg_domain_size: u32, inverse: bool) -> Self {
+        assert!(lg_domain_size <= Self::S);
+        let mut root = if inverse {
+            Self::INVERSE_ROOT_OF_UNITY
+        } else {
+            Self::ROOT_OF_UNITY
+        };
+        for _ in lg_domain_size..Self::S {
+            root = root.sqr();
+        }
+        root
+    }
+}
+
+// ntt/parameters/bls12_381.h
+impl NTTParameters for bls12_381::Fr {
+    const S: u32 = 32;
+    const GROUP_GEN: Self = Self::from_raw([
+        0x0000000efffffff1,
+        0x17e363d300189c0f,
+        0xff9c57876f8457b0,
+        0x351332208fc5a8c4,
+    ]);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw([
+        0xdb6db6dadb6db6dc,
+        0xe6b5824adb6cc6da,
+        0xf8b356e005810db9,
+        0x66d0f1e660ec4796,
+    ]);
+    const ROOT_OF_UNITY: Self = Self::from_raw([
+        0xb9b58d8c5f0e466a,
+        0x5b1b4c801819d7ec,
+        0x0af53ae352a31e64,
+        0x5bf3adda19e9b27b,
+    ]);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw([
+        0x4256481adcf3219a,
+        0x45f37b7f96b6cad3,
+        0xf9c3f1d75f7a3b27,
+        0x2d2fc049658afd43,
+    ]);
+}
+
+// ntt/parameters/bls12_377.h
+impl NTTParameters for bls12_377::Fr {
+    const S: u32 = 47;
+    const GROUP_GEN: Self = Self::from_raw([
+        0x296c7ffffffffed3,
+        0x929216656ffffec7,
+        0x4c01534d92860e69,
+        0x0c79cfc4b9819970,
+    ]);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw([
+        0xb76f9745d1745d17,
+        0xfed18274afffffff,
+        0xfce619835b36a173,
+        0x068b6ffd78dc8d16,
+    ]);
+    const ROOT_OF_UNITY: Self = Self::from_raw([
+        0xaf80da4dda3ad648,
+        0x5e223adbfc381dac,
+        0x03ba0666b2f92525,
+        0x0f906c5b3befb0ce,
+    ]);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw([
+        0x0d248e974767f5bd,
+        0xfa72032b32f67f4c,
+        0x7ec7e591ee4ee58f,
+        0x1227d66f8e126f27,
+    ]);
+}
+
+// ntt/parameters/alt_bn128.h, GROUP_GEN is 7, while arkworks uses 5, see
+// Domain::new for matching the latter.
+impl NTTParameters for alt_bn128::Fr {
+    const S: u32 = 28;
+    const GROUP_GEN: Self = Self::from_raw([
+        0x3057819e4fffffdb,
+        0x307f6d866832bb01,
+        0x5c65ec9f484e3a89,
+        0x0180a96573d3d9f8,
+    ]);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw([
+        0xf41575289db6db6d,
+        0x07daec5e847b8b05,
+        0xea0fce347eecc0e2,
+        0x02017ed283b7fb4f,
+    ]);
+    const ROOT_OF_UNITY: Self = Self::from_raw([
+        0x9632c7c5b639feb8,
+        0x985ce3400d0ff299,
+        0xb2dd880001b0ecd8,
+        0x1d69070d6d98ce29,
+    ]);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw([
+        0x05f05c05affb3d96,
+        0xb8e594ebfc3b5137,
+        0x60314620b85bc4c1,
+        0x2a4129bebb6fc591,
+    ]);
+}
+
+// ntt/parameters/pallas.h
+impl NTTParameters for pasta::Pallas {
+    const S: u32 = 32;
+    const GROUP_GEN: Self = Self::from_raw([
+        0xa1a55e68ffffffed,
+        0x74c2a54b4f4982f3,
+        0xfffffffffffffffd,
+        0x3fffffffffffffff,
+    ]);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw([
+        0x0a7e7c3e99999999,
+        0xb83c0a9bfa6b6a89,
+        0xcccccccccccccccc,
+        0x0ccccccccccccccc,
+    ]);
+    const ROOT_OF_UNITY: Self = Self::from_raw([
+        0xa28db849bad6dbf0,
+        0x9083cd03d3b539df,
+        0xfba6b9ca9dc8448e,
+        0x3ec928747b89c6da,
+    ]);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw([
+        0x5cfe5f67cb155442,
+        0x176460c2734c4621,
+        0xdf81001645214110,
+        0x18047fb9f91068bc,
+    ]);
+}
+
+// ntt/parameters/vesta.h
+impl NTTParameters for pasta::Vesta {
+    const S: u32 = 32;
+    const GROUP_GEN: Self = Self::from_raw([
+        0x96bc8c8cffffffed,
+        0x74c2a54b49f7778e,
+        0xfffffffffffffffd,
+        0x3fffffffffffffff,
+    ]);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw([
+        0x123bd95299999999,
+        0xb83c0a9bfa40677b,
+        0xcccccccccccccccc,
+        0x0ccccccccccccccc,
+    ]);
+    const ROOT_OF_UNITY: Self = Self::from_raw([
+        0x218077428c9942de,
+        0xcc49578921b60494,
+        0xac2e5d27b2efbee2,
+        0x0b79fa897f2db056,
+    ]);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw([
+        0xb990773d23d22e85,
+        0x4ece919a03f3c012,
+        0x2c9ac8b9aa3ba50b,
+        0x364d5dfa434d9efa,
+    ]);
+}
+
+// ntt/parameters/goldilocks.h
+impl NTTParameters for Goldilocks {
+    const S: u32 = 32;
+    const GROUP_GEN: Self = Self::from_raw(0x0000000000000007);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw(0x249249246db6db6e);
+    const ROOT_OF_UNITY: Self = Self::from_raw(0x185629dcda58878c);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw(0x76b6b635b6fc8719);
+}
+
+// ntt/parameters/baby_bear.h
+impl NTTParameters for BabyBear {
+    const S: u32 = 27;
+    const GROUP_GEN: Self = Self::from_raw(0x2ffffffa);
+    const GROUP_GEN_INVERSE: Self = Self::from_raw(0x2d555555);
+    const ROOT_OF_UNITY: Self = Self::from_raw(0x1ffffedc);
+    const INVERSE_ROOT_OF_UNITY: Self = Self::from_raw(0x50b3630a);
+}
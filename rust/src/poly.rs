@@ -0,0 +1,282 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Host implementations of operations on polynomials given by their
+//! evaluations over a [`Domain`].
+
+use crate::ff::{batch_inverse, HostField, Zeros};
+use crate::ntt::{par_chunks, Domain, NTTParameters, MIN_GRAIN};
+use crate::Error;
+
+/// Evaluate the polynomial of degree less than the domain size, given by
+/// its |evals| over |domain| in natural order, at an arbitrary |point|
+/// with the barycentric formula.
+pub fn evaluate_lagrange<F: NTTParameters>(
+    evals: &[F],
+    domain: &Domain<F>,
+    point: F,
+) -> Result<F, Error> {
+    Ok(evaluate_lagrange_batch(evals, domain, 1, point)?[0])
+}
+
+/// Same as [`evaluate_lagrange`], but for |ncolumns| polynomials laid out
+/// one after another, all evaluated at the same |point|. The weights are
+/// computed once, with a single inversion, so that the cost per column is
+/// one multiplication per element.
+pub fn evaluate_lagrange_batch<F: NTTParameters>(
+    evals: &[F],
+    domain: &Domain<F>,
+    ncolumns: usize,
+    point: F,
+) -> Result<Vec<F>, Error> {
+    let size = domain.size();
+    if Some(evals.len()) != ncolumns.checked_mul(size) {
+        return Err(Error::length_mismatch(
+            evals.len(),
+            ncolumns.saturating_mul(size),
+        ));
+    }
+
+    // Over the coset s·H of size n with generator w, the Lagrange basis is
+    //   L_i(z) = (z^n - s^n) / (n·s^(n-1)) · w^i / (z - s·w^i),
+    // which is undefined in the domain itself, where L_i(s·w^i) = 1.
+    let root = domain.root();
+    let shift = domain.shift();
+    let mut weights = Vec::with_capacity(size);
+    let mut x = shift;
+    for i in 0..size {
+        let diff = point - x;
+        if diff.is_zero() {
+            return Ok(evals.iter().skip(i).step_by(size).copied().collect());
+        }
+        weights.push(diff);
+        x *= root;
+    }
+    // |diff| is non-zero
+    batch_inverse(&mut weights, Zeros::Skip)?;
+
+    let shift_n = shift.pow(size as u64);
+    let scale = (point.pow(size as u64) - shift_n)
+        * (F::from_u64(size as u64) * shift_n * shift.reciprocal()).reciprocal();
+    let mut w = scale;
+    for x in weights.iter_mut() {
+        *x *= w;
+        w *= root;
+    }
+
+    Ok(evals
+        .chunks(size)
+        .map(|column| {
+            column
+                .iter()
+                .zip(&weights)
+                .fold(F::ZERO, |acc, (f, w)| acc + *f * *w)
+        })
+        .collect())
+}
+
+/// Whether the i-th running product of [`grand_product`] includes the
+/// i-th factor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scan {
+    /// z_i = Π_{j<=i}, the last one being the product of all factors.
+    Inclusive,
+    /// z_i = Π_{j<i}, starting with z_0 = 1, e.g. Plonk's permutation
+    /// accumulator.
+    Exclusive,
+}
+
+/// Replace the factors in |inout| with their running products, divided by
+/// the running products of |den| if it's given, i.e. z_i = Π num_j/den_j,
+/// the denominators being inverted with [`batch_inverse`]. Returns the
+/// product of all factors, e.g. for checking that a permutation argument
+/// closes with 1. A zero denominator is a DIVISION_BY_ZERO error.
+pub fn grand_product<F: HostField>(
+    inout: &mut [F],
+    den: Option<&[F]>,
+    scan: Scan,
+) -> Result<F, Error> {
+    if let Some(den) = den {
+        if den.len() != inout.len() {
+            return Err(Error::length_mismatch(den.len(), inout.len()));
+        }
+        let mut inv = den.to_vec();
+        batch_inverse(&mut inv, Zeros::Reject)?;
+        par_chunks(inout, MIN_GRAIN, |i, chunk| {
+            for (x, d) in chunk.iter_mut().zip(&inv[i * MIN_GRAIN..]) {
+                *x *= *d;
+            }
+        });
+    }
+
+    // scan chunks independently, then carry the chunks' products over
+    par_chunks(inout, MIN_GRAIN, |_, chunk| {
+        for j in 1..chunk.len() {
+            let prev = chunk[j - 1];
+            chunk[j] *= prev;
+        }
+    });
+    let mut carries = Vec::with_capacity(inout.len().div_ceil(MIN_GRAIN));
+    let mut total = F::ONE;
+    for chunk in inout.chunks(MIN_GRAIN) {
+        carries.push(total);
+        total *= chunk[chunk.len() - 1];
+    }
+    par_chunks(inout, MIN_GRAIN, |i, chunk| {
+        let carry = carries[i];
+        match scan {
+            Scan::Inclusive if i != 0 => chunk.iter_mut().for_each(|x| *x *= carry),
+            Scan::Inclusive => {}
+            Scan::Exclusive => {
+                for j in (1..chunk.len()).rev() {
+                    chunk[j] = carry * chunk[j - 1];
+                }
+                chunk[0] = carry;
+            }
+        }
+    });
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{alt_bn128, baby_bear::BabyBear, bls12_381, goldilocks::Goldilocks};
+    use crate::ntt::{compute_ntt, Direction, InputOutputOrder, Type};
+
+    // Horner's rule
+    fn evaluate<F: HostField>(coeffs: &[F], point: F) -> F {
+        coeffs.iter().rev().fold(F::ZERO, |acc, c| acc * point + *c)
+    }
+
+    fn check<F: NTTParameters>() {
+        let lg = 6;
+        let coeffs: Vec<F> = (0..3u64 << lg)
+            .map(|i| F::from_u64(i * i + 5).pow(i | 1))
+            .collect();
+
+        for (type_, domain) in [
+            (Type::Standard, Domain::subgroup(lg).unwrap()),
+            (Type::Coset, Domain::standard_coset(lg).unwrap()),
+        ] {
+            let mut evals = coeffs.clone();
+            for column in evals.chunks_mut(1 << lg) {
+                compute_ntt(column, InputOutputOrder::NN, Direction::Forward, type_).unwrap();
+            }
+
+            for point in [F::from_u64(12345), F::GROUP_GEN.pow(1000) + F::ONE] {
+                let expected: Vec<F> = coeffs.chunks(1 << lg).map(|c| evaluate(c, point)).collect();
+                let actual = evaluate_lagrange_batch(&evals, &domain, 3, point).unwrap();
+                assert_eq!(actual, expected);
+                assert_eq!(
+                    evaluate_lagrange(&evals[..1 << lg], &domain, point).unwrap(),
+                    expected[0]
+                );
+            }
+
+            // points of the domain itself
+            let point = domain.element(5);
+            let actual = evaluate_lagrange_batch(&evals, &domain, 3, point).unwrap();
+            assert_eq!(actual, [evals[5], evals[64 + 5], evals[128 + 5]]);
+        }
+
+        // an arbitrary coset
+        let domain = Domain::coset(lg, F::from_u64(7)).unwrap();
+        let coeffs = &coeffs[..1 << lg];
+        let evals: Vec<F> = (0..domain.size())
+            .map(|i| evaluate(coeffs, domain.element(i)))
+            .collect();
+        let point = F::from_u64(3);
+        assert_eq!(
+            evaluate_lagrange(&evals, &domain, point).unwrap(),
+            evaluate(coeffs, point)
+        );
+
+        let err = evaluate_lagrange(&evals[1..], &domain, point).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+    }
+
+    #[test]
+    fn lagrange_vs_horner() {
+        check::<bls12_381::Fr>();
+        check::<alt_bn128::Fr>();
+        check::<Goldilocks>();
+        check::<BabyBear>();
+    }
+
+    #[test]
+    fn domains() {
+        let domain = Domain::<Goldilocks>::subgroup(3).unwrap();
+        assert_eq!(domain.size(), 8);
+        assert_eq!(domain.element(8), Goldilocks::ONE);
+        assert_ne!(domain.element(4), Goldilocks::ONE);
+
+        let err = Domain::<BabyBear>::subgroup(28).unwrap_err();
+        assert_eq!(err.code, Error::DOMAIN_TOO_LARGE);
+        let err = Domain::coset(3, Goldilocks::ZERO).unwrap_err();
+        assert_eq!(err.code, Error::DIVISION_BY_ZERO);
+
+        // caller-chosen roots
+        let root = Goldilocks::root_of_unity(5, false);
+        for k in [1, 3, 17, 31] {
+            let domain = Domain::new(5, root.pow(k), Goldilocks::from_u64(5)).unwrap();
+            assert_eq!(domain.root_exponent(), k as usize);
+            assert_eq!(domain.element(1), root.pow(k) * Goldilocks::from_u64(5));
+        }
+        assert_eq!(
+            domain,
+            Domain::new(3, domain.root(), Goldilocks::ONE).unwrap()
+        );
+        assert_eq!(Domain::<BabyBear>::subgroup(0).unwrap().root_exponent(), 1);
+        for bad in [root.sqr(), root * Goldilocks::from_u64(2), Goldilocks::ONE] {
+            let err = Domain::new(5, bad, Goldilocks::ONE).unwrap_err();
+            assert_eq!(err.code, Error::BAD_ROOT_OF_UNITY);
+        }
+    }
+
+    #[test]
+    fn grand_products() {
+        fn check<F: HostField>() {
+            for len in [0, 1, 5, MIN_GRAIN, 3 * MIN_GRAIN + 17] {
+                let num: Vec<F> = (0..len as u64).map(|i| F::from_u64(i + 2)).collect();
+                let den: Vec<F> = (0..len as u64).map(|i| F::from_u64(i * i + 3)).collect();
+
+                let mut expected = Vec::with_capacity(len);
+                let mut acc = F::ONE;
+                for (n, d) in num.iter().zip(&den) {
+                    expected.push(acc);
+                    acc *= *n * d.reciprocal();
+                }
+
+                let mut z = num.clone();
+                assert_eq!(
+                    grand_product(&mut z, Some(&den), Scan::Exclusive).unwrap(),
+                    acc
+                );
+                assert_eq!(z, expected);
+
+                let mut z = num.clone();
+                grand_product(&mut z, None, Scan::Inclusive).unwrap();
+                let mut acc = F::ONE;
+                for (z, n) in z.iter().zip(&num) {
+                    acc *= *n;
+                    assert_eq!(*z, acc);
+                }
+            }
+
+            let mut z = vec![F::ONE; 4];
+            let mut den = vec![F::ONE; 4];
+            let err = grand_product(&mut z, Some(&den[1..]), Scan::Inclusive).unwrap_err();
+            assert_eq!(err.code, Error::LENGTH_MISMATCH);
+            den[2] = F::ZERO;
+            let err = grand_product(&mut z, Some(&den), Scan::Inclusive).unwrap_err();
+            assert_eq!(err.code, Error::DIVISION_BY_ZERO);
+            assert_eq!(z, [F::ONE; 4]);
+        }
+
+        check::<bls12_381::Fr>();
+        check::<Goldilocks>();
+        check::<BabyBear>();
+    }
+}
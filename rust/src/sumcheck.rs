@@ -0,0 +1,211 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Host implementations of the multilinear primitives of sumcheck provers
+//! in sumcheck/sumcheck.cuh. A multilinear polynomial in n variables is
+//! given by its 2^n evaluations over the boolean hypercube, the first
+//! variable being the most significant bit of the index. Hence fixing the
+//! first variable picks one of the two halves of the table.
+
+use crate::ff::HostField;
+use crate::ntt::{num_threads, par_chunks, MIN_GRAIN};
+use crate::Error;
+
+/// Tables of products of more polynomials are rejected, same as on the GPU.
+pub const MAX_TABLES: usize = 8;
+
+/// Evaluations of eq(r, x) = Π (r_k·x_k + (1 - r_k)·(1 - x_k)) over the
+/// hypercube of |r.len()| dimensions, i.e. the multilinear extension of
+/// the indicator of |r|.
+pub fn eq_table<F: HostField>(r: &[F]) -> Vec<F> {
+    let mut table = vec![F::ZERO; 1 << r.len()];
+    table[0] = F::ONE;
+
+    // each round appends a variable as the most significant one
+    for (k, r_k) in r.iter().rev().enumerate() {
+        let size = 1 << k;
+        let (lo, hi) = table[..2 * size].split_at_mut(size);
+        for (l, h) in lo.iter_mut().zip(hi) {
+            *h = *l * *r_k;
+            *l -= *h;
+        }
+    }
+    table
+}
+
+/// Fix the first variable of the multilinear polynomial given by |inp| at
+/// |challenge| r, i.e. f(r, x') = f(0, x') + r·(f(1, x') - f(0, x')), and
+/// store the table of half the size to |out|.
+pub fn fold<F: HostField>(out: &mut [F], inp: &[F], challenge: F) -> Result<(), Error> {
+    check_table_len(inp.len())?;
+    let half = inp.len() / 2;
+    if out.len() != half {
+        return Err(Error::length_mismatch(out.len(), half));
+    }
+
+    let (lo, hi) = inp.split_at(half);
+    par_chunks(out, MIN_GRAIN, |i, chunk| {
+        let off = i * MIN_GRAIN;
+        for (j, y) in chunk.iter_mut().enumerate() {
+            let (l, h) = (lo[off + j], hi[off + j]);
+            *y = l + challenge * (h - l);
+        }
+    });
+    Ok(())
+}
+
+/// Round polynomial g(X) = Σ Π f_i(X, x') over the hypercube of x', of
+/// the product of |ntables| multilinear polynomials, whose tables are laid
+/// out one after another in |tables|. g is returned as its evaluations at
+/// X = 0, 1, ..., ntables, which determine it, its degree being |ntables|.
+pub fn round_poly<F: HostField>(tables: &[F], ntables: usize) -> Result<Vec<F>, Error> {
+    let size = check_tables(tables.len(), ntables)?;
+    let half = size / 2;
+    let degree = ntables;
+
+    let nchunks = half.div_ceil(MIN_GRAIN);
+    let nthreads = num_threads().min(nchunks);
+    let per_thread = nchunks.div_ceil(nthreads) * MIN_GRAIN;
+
+    let partial = |start: usize, end: usize| {
+        let mut acc = vec![F::ZERO; degree + 1];
+        let mut vals = [F::ZERO; MAX_TABLES];
+        let mut diffs = [F::ZERO; MAX_TABLES];
+        for j in start..end {
+            for (i, table) in tables.chunks_exact(size).enumerate() {
+                vals[i] = table[j];
+                diffs[i] = table[j + half] - table[j];
+            }
+            // f_i(t + 1, x') = f_i(t, x') + (f_i(1, x') - f_i(0, x'))
+            for a in acc.iter_mut() {
+                *a += vals[..ntables].iter().fold(F::ONE, |p, v| p * *v);
+                for (v, d) in vals.iter_mut().zip(&diffs) {
+                    *v += *d;
+                }
+            }
+        }
+        acc
+    };
+
+    let partials: Vec<Vec<F>> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..half)
+            .step_by(per_thread)
+            .map(|start| {
+                let partial = &partial;
+                s.spawn(move || partial(start, half.min(start + per_thread)))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut ret = vec![F::ZERO; degree + 1];
+    for acc in partials {
+        for (r, a) in ret.iter_mut().zip(acc) {
+            *r += a;
+        }
+    }
+    Ok(ret)
+}
+
+// A table of at least one variable.
+fn check_table_len(len: usize) -> Result<(), Error> {
+    if !len.is_power_of_two() || len < 2 {
+        return Err(Error::not_power_of_two(len));
+    }
+    Ok(())
+}
+
+// Validate |ntables| tables of total length |len|, return the size of one.
+fn check_tables(len: usize, ntables: usize) -> Result<usize, Error> {
+    if ntables == 0 || ntables > MAX_TABLES {
+        return Err(Error::new(
+            Error::LENGTH_MISMATCH,
+            &format!("number of tables {} is not in 1..={}", ntables, MAX_TABLES),
+        ));
+    }
+    if !len.is_multiple_of(ntables) {
+        return Err(Error::length_mismatch(len, len / ntables * ntables));
+    }
+    check_table_len(len / ntables)?;
+    Ok(len / ntables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{baby_bear::BabyBear, bls12_381, goldilocks::Goldilocks};
+    use crate::test_util::random;
+
+    // Evaluate the multilinear extension of |table| at |point| directly.
+    fn evaluate<F: HostField>(table: &[F], point: &[F]) -> F {
+        table
+            .iter()
+            .zip(eq_table(point))
+            .fold(F::ZERO, |acc, (f, e)| acc + *f * e)
+    }
+
+    fn check<F: HostField>() {
+        // eq(r, x) at the hypercube's points is Π r_k or 1 - r_k
+        let r: Vec<F> = random(4, 0xe9);
+        let eq = eq_table(&r);
+        assert_eq!(eq.len(), 16);
+        assert_eq!(eq[0b1010], r[0] * (F::ONE - r[1]) * r[2] * (F::ONE - r[3]));
+        assert_eq!(eq.iter().fold(F::ZERO, |acc, e| acc + *e), F::ONE);
+        assert_eq!(eq_table::<F>(&[]), [F::ONE]);
+
+        for lg in [1, 5, 14] {
+            let tables: Vec<F> = random(3 << lg, 0x7ab1e + lg as u64);
+            let size = 1 << lg;
+            let point: Vec<F> = random(lg, 0x9017 + lg as u64);
+
+            // folding all variables one by one evaluates the polynomial
+            let mut table = tables[..size].to_vec();
+            for r in &point {
+                let mut folded = vec![F::ZERO; table.len() / 2];
+                fold(&mut folded, &table, *r).unwrap();
+                table = folded;
+            }
+            assert_eq!(table, [evaluate(&tables[..size], &point)]);
+
+            // g(t) = Σ Π f_i(t, x'), t being the first variable
+            let g = round_poly(&tables, 3).unwrap();
+            assert_eq!(g.len(), 4);
+            for (t, g_t) in g.iter().enumerate() {
+                let x0 = F::from_u64(t as u64);
+                let mut expected = F::ZERO;
+                let mut folded = vec![vec![F::ZERO; size / 2]; 3];
+                for (f, table) in folded.iter_mut().zip(tables.chunks(size)) {
+                    fold(f, table, x0).unwrap();
+                }
+                for ((a, b), c) in folded[0].iter().zip(&folded[1]).zip(&folded[2]) {
+                    expected += *a * *b * *c;
+                }
+                assert_eq!(*g_t, expected);
+            }
+            let sum = tables[..size].iter().fold(F::ZERO, |acc, f| acc + *f);
+            let g = round_poly(&tables[..size], 1).unwrap();
+            assert_eq!(g[0] + g[1], sum);
+        }
+
+        let table = vec![F::ONE; 8];
+        let mut out = vec![F::ZERO; 3];
+        let err = fold(&mut out, &table, F::ONE).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = fold(&mut out[..1], &table[..1], F::ONE).unwrap_err();
+        assert_eq!(err.code, Error::NOT_POWER_OF_TWO);
+        let err = round_poly(&table, 3).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = round_poly(&table, 0).unwrap_err();
+        assert_eq!(err.code, Error::LENGTH_MISMATCH);
+        let err = round_poly(&table[..6], 2).unwrap_err();
+        assert_eq!(err.code, Error::NOT_POWER_OF_TWO);
+    }
+
+    #[test]
+    fn multilinear() {
+        check::<bls12_381::Fr>();
+        check::<Goldilocks>();
+        check::<BabyBear>();
+    }
+}
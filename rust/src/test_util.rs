@@ -0,0 +1,21 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures shared by the unit tests.
+
+use crate::ff::HostField;
+
+/// Deterministic pseudo-random elements spread over the whole field,
+/// distinct for distinct seeds.
+pub(crate) fn random<F: HostField>(len: usize, seed: u64) -> Vec<F> {
+    let mut rng = seed | 1;
+    (0..len)
+        .map(|_| {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            F::from_u64(rng).pow(rng)
+        })
+        .collect()
+}
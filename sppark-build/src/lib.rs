@@ -0,0 +1,428 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helper for build scripts of crates that compile sppark CUDA code, e.g.
+//!
+//! ```no_run
+//! use sppark_build::{Build, Field};
+//!
+//! let field = Field::from_features(&[Field::Bls12_381, Field::Goldilocks]);
+//! if sppark_build::nvcc().is_some() {
+//!     Build::new()
+//!         .field(field)
+//!         .max_lg_domain_size(24)
+//!         .file("cuda/ntt_api.cu")
+//!         .compile("ntt_cuda");
+//! }
+//! ```
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Fields and curves selectable with FEATURE_* preprocessor symbols.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Field {
+    Bls12_377,
+    Bls12_381,
+    Pallas,
+    Vesta,
+    Bn254,
+    Goldilocks,
+    BabyBear,
+}
+
+impl Field {
+    pub const ALL: [Field; 7] = [
+        Field::Bls12_377,
+        Field::Bls12_381,
+        Field::Pallas,
+        Field::Vesta,
+        Field::Bn254,
+        Field::Goldilocks,
+        Field::BabyBear,
+    ];
+
+    /// Cargo feature name conventionally used to select the field.
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Field::Bls12_377 => "bls12_377",
+            Field::Bls12_381 => "bls12_381",
+            Field::Pallas => "pallas",
+            Field::Vesta => "vesta",
+            Field::Bn254 => "bn254",
+            Field::Goldilocks => "gl64",
+            Field::BabyBear => "bb31",
+        }
+    }
+
+    pub fn define(&self) -> &'static str {
+        match self {
+            Field::Bls12_377 => "FEATURE_BLS12_377",
+            Field::Bls12_381 => "FEATURE_BLS12_381",
+            Field::Pallas => "FEATURE_PALLAS",
+            Field::Vesta => "FEATURE_VESTA",
+            Field::Bn254 => "FEATURE_BN254",
+            Field::Goldilocks => "FEATURE_GOLDILOCKS",
+            Field::BabyBear => "FEATURE_BABY_BEAR",
+        }
+    }
+
+    /// Pick the one of |candidates| enabled as a cargo feature of the
+    /// crate being built, panic if there is none or more than one.
+    pub fn from_features(candidates: &[Field]) -> Field {
+        select(candidates, |var| env::var_os(var).is_some()).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Pick all of |candidates| enabled as cargo features of the crate
+    /// being built, panic if there is none.
+    pub fn all_from_features(candidates: &[Field]) -> Vec<Field> {
+        select_all(candidates, |var| env::var_os(var).is_some()).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+fn select_all(candidates: &[Field], enabled: impl Fn(&str) -> bool) -> Result<Vec<Field>, String> {
+    let selected: Vec<_> = candidates
+        .iter()
+        .filter(|f| enabled(&format!("CARGO_FEATURE_{}", f.feature().to_uppercase())))
+        .copied()
+        .collect();
+
+    if selected.is_empty() {
+        return Err(format!(
+            "Can't run without a field being specified,\nplease select one with --features=<field>. Available options are\n{:#?}\n",
+            candidates.iter().map(Field::feature).collect::<Vec<_>>()
+        ));
+    }
+    Ok(selected)
+}
+
+fn select(candidates: &[Field], enabled: impl Fn(&str) -> bool) -> Result<Field, String> {
+    match select_all(candidates, enabled)?[..] {
+        [field] => Ok(field),
+        _ => Err("Multiple fields are not supported, please select only one.".to_string()),
+    }
+}
+
+/// Locate the CUDA compiler, $NVCC or nvcc on the $PATH. Also declare the
+/// "cuda" cfg that [`Build::compile`] engages.
+pub fn nvcc() -> Option<PathBuf> {
+    println!("cargo:rustc-check-cfg=cfg(feature, values(\"cuda\"))");
+    println!("cargo:rerun-if-env-changed=NVCC");
+    match env::var("NVCC") {
+        Ok(var) => which::which(var),
+        Err(_) => which::which("nvcc"),
+    }
+    .ok()
+}
+
+/// Configuration of an nvcc compilation of sppark-based .cu files.
+#[derive(Clone, Debug)]
+pub struct Build {
+    field: Option<Field>,
+    archs: Vec<u32>,
+    max_lg_domain_size: Option<u32>,
+    digit_bits: Option<u32>,
+    error_messages: bool,
+    defines: Vec<(String, Option<String>)>,
+    flags: Vec<String>,
+    includes: Vec<PathBuf>,
+    sppark_root: Option<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+impl Default for Build {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Build {
+    pub fn new() -> Self {
+        Self {
+            field: None,
+            archs: Vec::new(),
+            max_lg_domain_size: None,
+            digit_bits: None,
+            error_messages: true,
+            defines: Vec::new(),
+            flags: Vec::new(),
+            includes: Vec::new(),
+            sppark_root: None,
+            files: Vec::new(),
+        }
+    }
+
+    pub fn field(&mut self, field: Field) -> &mut Self {
+        self.field = Some(field);
+        self
+    }
+
+    /// Add a target architecture, e.g. 80 for sm_80. The first one is
+    /// passed as -arch, the rest as -gencode. Defaults to 70.
+    pub fn arch(&mut self, sm: u32) -> &mut Self {
+        if !self.archs.contains(&sm) {
+            self.archs.push(sm);
+        }
+        self
+    }
+
+    /// Override the NTT domain size limit set in ntt/parameters.cuh.
+    pub fn max_lg_domain_size(&mut self, lg: u32) -> &mut Self {
+        assert!(
+            (1..=40).contains(&lg),
+            "impossible MAX_LG_DOMAIN_SIZE {}",
+            lg
+        );
+        self.max_lg_domain_size = Some(lg);
+        self
+    }
+
+    /// Override the MSM sort digit width set in msm/sort.cuh.
+    pub fn digit_bits(&mut self, bits: u32) -> &mut Self {
+        assert!((10..=14).contains(&bits), "impossible DIGIT_BITS {}", bits);
+        self.digit_bits = Some(bits);
+        self
+    }
+
+    /// Have errors returned as RustError carry a message, which is on by
+    /// default. Otherwise only the code is returned.
+    pub fn error_messages(&mut self, on: bool) -> &mut Self {
+        self.error_messages = on;
+        self
+    }
+
+    pub fn define(&mut self, name: &str, value: Option<&str>) -> &mut Self {
+        self.defines
+            .push((name.to_string(), value.map(str::to_string)));
+        self
+    }
+
+    /// Pass an extra flag to nvcc.
+    pub fn flag(&mut self, flag: &str) -> &mut Self {
+        self.flags.push(flag.to_string());
+        self
+    }
+
+    pub fn include<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.includes.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Where the sppark headers are, $DEP_SPPARK_ROOT by default.
+    pub fn sppark_root<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.sppark_root = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn file<P: AsRef<Path>>(&mut self, file: P) -> &mut Self {
+        self.files.push(file.as_ref().to_path_buf());
+        self
+    }
+
+    /// Flags to pass to nvcc, defines and include directories aside.
+    pub fn nvcc_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        let mut archs = self.archs.iter();
+        let first = archs.next().copied().unwrap_or(70);
+        flags.push(format!("-arch=sm_{}", first));
+        for sm in archs {
+            flags.push("-gencode".to_string());
+            flags.push(format!("arch=compute_{0},code=sm_{0}", sm));
+        }
+        if !cfg!(target_env = "msvc") {
+            flags.push("-Xcompiler".to_string());
+            flags.push("-Wno-unused-function".to_string());
+        }
+        flags.extend(self.flags.iter().cloned());
+        flags
+    }
+
+    pub fn defines(&self) -> Vec<(String, Option<String>)> {
+        let mut defines = Vec::new();
+        if self.error_messages {
+            defines.push(("TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE".to_string(), None));
+        }
+        if let Some(field) = self.field {
+            defines.push((field.define().to_string(), None));
+        }
+        if let Some(lg) = self.max_lg_domain_size {
+            defines.push(("MAX_LG_DOMAIN_SIZE".to_string(), Some(lg.to_string())));
+        }
+        if let Some(bits) = self.digit_bits {
+            defines.push(("DIGIT_BITS".to_string(), Some(bits.to_string())));
+        }
+        defines.extend(self.defines.iter().cloned());
+        defines
+    }
+
+    /// Explicitly added directories, followed by the ones advertised by
+    /// blst, semolina and sppark through DEP_* variables.
+    pub fn include_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = self.includes.clone();
+        for var in ["DEP_BLST_C_SRC", "DEP_SEMOLINA_C_INCLUDE"] {
+            if let Some(dir) = env::var_os(var) {
+                dirs.push(dir.into());
+            }
+        }
+        match &self.sppark_root {
+            Some(dir) => dirs.push(dir.clone()),
+            None => {
+                if let Some(dir) = env::var_os("DEP_SPPARK_ROOT") {
+                    dirs.push(dir.into());
+                }
+            }
+        }
+        dirs
+    }
+
+    /// Lines to print on successful compilation.
+    pub fn cargo_directives(&self) -> Vec<String> {
+        let mut lines = vec!["cargo:rustc-cfg=feature=\"cuda\"".to_string()];
+        let mut dirs: Vec<&Path> = Vec::new();
+        for file in self.files.iter() {
+            let dir = match file.parent() {
+                Some(dir) if dir != Path::new("") => dir,
+                _ => file.as_path(),
+            };
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        for dir in dirs {
+            lines.push(format!("cargo:rerun-if-changed={}", dir.display()));
+        }
+        lines.push("cargo:rerun-if-env-changed=CXXFLAGS".to_string());
+        lines
+    }
+
+    /// The cc::Build to compile the files with.
+    pub fn cc(&self) -> cc::Build {
+        let mut nvcc = cc::Build::new();
+        nvcc.cuda(true);
+        for flag in self.nvcc_flags() {
+            nvcc.flag(&flag);
+        }
+        for (name, value) in self.defines() {
+            nvcc.define(&name, value.as_deref());
+        }
+        for dir in self.include_dirs() {
+            nvcc.include(dir);
+        }
+        nvcc.files(&self.files);
+        nvcc
+    }
+
+    /// Compile the files into lib|name|.a, and engage the "cuda" feature.
+    pub fn compile(&self, name: &str) {
+        self.cc().compile(name);
+        for line in self.cargo_directives() {
+            println!("{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn field_selection() {
+        let only = |var: &'static str| move |v: &str| v == var;
+
+        let field = select(&Field::ALL, only("CARGO_FEATURE_GL64"));
+        assert_eq!(field, Ok(Field::Goldilocks));
+        let field = select(&Field::ALL, only("CARGO_FEATURE_BLS12_381"));
+        assert_eq!(field.unwrap().define(), "FEATURE_BLS12_381");
+
+        let curves = [Field::Bn254, Field::Bls12_377, Field::Bls12_381];
+        let err = select(&curves, only("CARGO_FEATURE_BB31")).unwrap_err();
+        assert!(err.contains("\"bls12_377\"") && !err.contains("bb31"));
+        assert!(select(&Field::ALL, |_| true).is_err());
+
+        let pasta = |v: &str| v == "CARGO_FEATURE_PALLAS" || v == "CARGO_FEATURE_VESTA";
+        let fields = select_all(&Field::ALL, pasta);
+        assert_eq!(fields, Ok(vec![Field::Pallas, Field::Vesta]));
+        assert!(select_all(&curves, pasta).is_err());
+    }
+
+    #[test]
+    fn flags() {
+        let mut build = Build::new();
+        let wno = if cfg!(target_env = "msvc") {
+            vec![]
+        } else {
+            strings(&["-Xcompiler", "-Wno-unused-function"])
+        };
+
+        assert_eq!(build.nvcc_flags()[0], "-arch=sm_70");
+
+        build.arch(80).arch(70).arch(80).flag("-t0");
+        let mut expected = strings(&["-arch=sm_80", "-gencode", "arch=compute_70,code=sm_70"]);
+        expected.extend(wno);
+        expected.push("-t0".to_string());
+        assert_eq!(build.nvcc_flags(), expected);
+    }
+
+    #[test]
+    fn defines() {
+        let mut build = Build::new();
+        let def = |name: &str, value: Option<&str>| (name.to_string(), value.map(str::to_string));
+
+        assert_eq!(
+            build.defines(),
+            [def("TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE", None)]
+        );
+
+        build
+            .field(Field::BabyBear)
+            .max_lg_domain_size(24)
+            .digit_bits(12)
+            .define("__ADX__", None)
+            .error_messages(false);
+        assert_eq!(
+            build.defines(),
+            [
+                def("FEATURE_BABY_BEAR", None),
+                def("MAX_LG_DOMAIN_SIZE", Some("24")),
+                def("DIGIT_BITS", Some("12")),
+                def("__ADX__", None),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "impossible DIGIT_BITS")]
+    fn bad_digit_bits() {
+        Build::new().digit_bits(16);
+    }
+
+    #[test]
+    fn directives() {
+        let mut build = Build::new();
+        build
+            .sppark_root("/opt/sppark")
+            .include("include")
+            .file("cuda/ntt_api.cu")
+            .file("cuda/extra.cu")
+            .file("top.cu");
+
+        let dirs = build.include_dirs();
+        assert_eq!(dirs.first(), Some(&PathBuf::from("include")));
+        assert_eq!(dirs.last(), Some(&PathBuf::from("/opt/sppark")));
+
+        assert_eq!(
+            build.cargo_directives(),
+            strings(&[
+                "cargo:rustc-cfg=feature=\"cuda\"",
+                "cargo:rerun-if-changed=cuda",
+                "cargo:rerun-if-changed=top.cu",
+                "cargo:rerun-if-env-changed=CXXFLAGS",
+            ])
+        );
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+#ifndef __SUMCHECK_KERNELS_CU__
+#define __SUMCHECK_KERNELS_CU__
+
+// Tables of products of more polynomials are rejected, keep in sync with
+// MAX_TABLES in rust/src/sumcheck.rs.
+#ifndef SUMCHECK_MAX_TABLES
+# define SUMCHECK_MAX_TABLES 8
+#endif
+
+// The eq table of no variables.
+__global__
+void sumcheck_eq_one(fr_t* d_eq)
+{
+    *d_eq = fr_t::one();
+}
+
+// Prepend a variable with challenge *d_r to the eq table of 2^lg_size
+// elements, doubling it. The new variable is the most significant one,
+// hence x = 0 keeps the lower half and x = 1 fills the upper one.
+__launch_bounds__(1024) __global__
+void sumcheck_eq_step(fr_t* d_eq, uint32_t lg_size, const fr_t* d_r)
+{
+    size_t idx = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+    size_t size = (size_t)1 << lg_size;
+
+    if (idx >= size)
+        return;
+
+    fr_t v = d_eq[idx];
+    fr_t hi = v * *d_r;
+
+    d_eq[idx + size] = hi;
+    d_eq[idx] = v - hi;
+}
+
+// Fix the most significant variable of the table of 2^(lg_half+1) elements
+// at |challenge|, in place, so that the lower half is the folded table.
+__launch_bounds__(1024) __global__
+void sumcheck_fold(fr_t* d_inout, uint32_t lg_half, fr_t challenge)
+{
+    size_t idx = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+    size_t half = (size_t)1 << lg_half;
+
+    if (idx >= half)
+        return;
+
+    fr_t lo = d_inout[idx];
+    fr_t hi = d_inout[idx + half];
+
+    d_inout[idx] = lo + challenge * (hi - lo);
+}
+
+// Partial sums of Π f_i(t, x') for t = 0..ntables over a grid-stride subset
+// of x', |ntables| tables of 2^(lg_half+1) elements each being laid out one
+// after another. The sum for t by thread tid goes to
+// d_partials[t*nthreads + tid].
+__launch_bounds__(256) __global__
+void sumcheck_round_partials(fr_t* d_partials, const fr_t* d_tables,
+                             uint32_t ntables, uint32_t lg_half)
+{
+    const size_t nthreads = blockDim.x * (size_t)gridDim.x;
+    const size_t tid = threadIdx.x + blockDim.x * (size_t)blockIdx.x;
+    const size_t half = (size_t)1 << lg_half;
+
+    fr_t acc[SUMCHECK_MAX_TABLES + 1];
+    fr_t vals[SUMCHECK_MAX_TABLES], diffs[SUMCHECK_MAX_TABLES];
+
+    for (uint32_t t = 0; t <= ntables; t++)
+        acc[t].zero();
+
+    for (size_t j = tid; j < half; j += nthreads) {
+        for (uint32_t i = 0; i < ntables; i++) {
+            const fr_t* table = d_tables + 2 * half * i;
+            vals[i] = table[j];
+            diffs[i] = table[j + half] - vals[i];
+        }
+
+        // f_i(t+1, x') = f_i(t, x') + (f_i(1, x') - f_i(0, x'))
+        for (uint32_t t = 0; t <= ntables; t++) {
+            fr_t prod = vals[0];
+            vals[0] += diffs[0];
+            for (uint32_t i = 1; i < ntables; i++) {
+                prod *= vals[i];
+                vals[i] += diffs[i];
+            }
+            acc[t] += prod;
+        }
+    }
+
+    for (uint32_t t = 0; t <= ntables; t++)
+        d_partials[t * nthreads + tid] = acc[t];
+}
+
+// Sum |nthreads| partial sums for each t, one thread per t.
+__launch_bounds__(SUMCHECK_MAX_TABLES + 1) __global__
+void sumcheck_round_sum(fr_t* d_out, const fr_t* d_partials,
+                        uint32_t ntables, size_t nthreads)
+{
+    uint32_t t = threadIdx.x;
+
+    if (t > ntables)
+        return;
+
+    fr_t sum = d_partials[t * nthreads];
+    for (size_t i = 1; i < nthreads; i++)
+        sum += d_partials[t * nthreads + i];
+
+    d_out[t] = sum;
+}
+
+#endif
@@ -0,0 +1,157 @@
+// Copyright Supranational LLC
+// Licensed under the Apache License, Version 2.0, see LICENSE for details.
+// SPDX-License-Identifier: Apache-2.0
+
+#ifndef __SPPARK_SUMCHECK_SUMCHECK_CUH__
+#define __SPPARK_SUMCHECK_SUMCHECK_CUH__
+
+// Multilinear primitives of sumcheck provers over fr_t. A multilinear
+// polynomial in n variables is given by its 2^n evaluations over the
+// boolean hypercube, the first variable being the most significant bit
+// of the index, see rust/src/sumcheck.rs for the host implementation.
+
+#include <util/exception.cuh>
+#include <util/rusterror.h>
+#include <util/gpu_t.cuh>
+
+#include "kernels.cu"
+
+#ifndef __CUDA_ARCH__
+
+class Sumcheck {
+protected:
+    static void check_table_size(uint32_t lg_size)
+    {
+        if (lg_size == 0 || lg_size >= 8 * sizeof(size_t))
+            throw cuda_error{SPPARK_ERR_NOT_POWER_OF_TWO,
+                             fmt("table of 2^%u elements is not foldable",
+                                 lg_size)};
+    }
+
+public:
+    // Evaluations of eq(r, x) over the hypercube of |nvars| dimensions,
+    // |r| holding |nvars| challenges, to |eq| of 2^nvars elements.
+    static RustError EqTable(const gpu_t& gpu, fr_t* eq, const fr_t* r,
+                             uint32_t nvars)
+    {
+        try {
+            gpu.select();
+
+            size_t size = (size_t)1 << nvars;
+            dev_ptr_t<fr_t> d_eq{size, gpu};
+            dev_ptr_t<fr_t> d_r{nvars, gpu};
+
+            if (nvars)
+                gpu.HtoD(&d_r[0], r, nvars);
+            sumcheck_eq_one<<<1, 1, 0, gpu>>>(&d_eq[0]);
+            CUDA_OK(cudaGetLastError());
+
+            // the last variable comes first, as the least significant one
+            for (uint32_t lg = 0; lg < nvars; lg++) {
+                size_t half = (size_t)1 << lg;
+                const uint32_t bsize = (uint32_t)std::min(half, (size_t)1024);
+
+                sumcheck_eq_step<<<(half + bsize - 1) / bsize, bsize, 0, gpu>>>
+                                (&d_eq[0], lg, &d_r[nvars - 1 - lg]);
+                CUDA_OK(cudaGetLastError());
+            }
+
+            gpu.DtoH(eq, &d_eq[0], size);
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("Sumcheck::EqTable: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
+
+    // Fix the first variable of the table of 2^|lg_size| elements at |inp|
+    // at |challenge|, storing 2^(lg_size-1) elements to |out|.
+    static RustError Fold(const gpu_t& gpu, fr_t* out, const fr_t* inp,
+                          uint32_t lg_size, const fr_t& challenge)
+    {
+        try {
+            gpu.select();
+
+            check_table_size(lg_size);
+
+            size_t size = (size_t)1 << lg_size;
+            size_t half = size / 2;
+            const uint32_t bsize = (uint32_t)std::min(half, (size_t)1024);
+            dev_ptr_t<fr_t> d_inout{size, gpu};
+
+            gpu.HtoD(&d_inout[0], inp, size);
+            sumcheck_fold<<<(half + bsize - 1) / bsize, bsize, 0, gpu>>>
+                         (&d_inout[0], lg_size - 1, challenge);
+            CUDA_OK(cudaGetLastError());
+            gpu.DtoH(out, &d_inout[0], half);
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("Sumcheck::Fold: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
+
+    // Evaluations at 0, 1, ..., |ntables| of the round polynomial of the
+    // product of |ntables| tables of 2^|lg_size| elements each, laid out
+    // one after another at |tables|, to |evals|.
+    static RustError RoundPoly(const gpu_t& gpu, fr_t* evals,
+                               const fr_t* tables, uint32_t ntables,
+                               uint32_t lg_size)
+    {
+        try {
+            gpu.select();
+
+            check_table_size(lg_size);
+            if (ntables == 0 || ntables > SUMCHECK_MAX_TABLES)
+                throw cuda_error{SPPARK_ERR_LENGTH_MISMATCH,
+                                 fmt("number of tables %u is not in 1..=%u",
+                                     ntables, (uint32_t)SUMCHECK_MAX_TABLES)};
+
+            size_t size = (size_t)1 << lg_size;
+            size_t half = size / 2;
+            const uint32_t bsize = (uint32_t)std::min(half, (size_t)256);
+            size_t nblocks = std::min((half + bsize - 1) / bsize,
+                                      (size_t)gpu.sm_count() * 4);
+            size_t nthreads = nblocks * bsize;
+
+            dev_ptr_t<fr_t> d_tables{ntables * size, gpu};
+            dev_ptr_t<fr_t> d_partials{(ntables + 1) * nthreads, gpu};
+            dev_ptr_t<fr_t> d_evals{ntables + 1, gpu};
+
+            gpu.HtoD(&d_tables[0], tables, ntables * size);
+            sumcheck_round_partials<<<nblocks, bsize, 0, gpu>>>
+                                   (&d_partials[0], &d_tables[0], ntables,
+                                    lg_size - 1);
+            CUDA_OK(cudaGetLastError());
+            sumcheck_round_sum<<<1, ntables + 1, 0, gpu>>>
+                              (&d_evals[0], &d_partials[0], ntables, nthreads);
+            CUDA_OK(cudaGetLastError());
+            gpu.DtoH(evals, &d_evals[0], ntables + 1);
+            gpu.sync();
+        } catch (const cuda_error& e) {
+            gpu.sync();
+#ifdef TAKE_RESPONSIBILITY_FOR_ERROR_MESSAGE
+            return RustError{e.code(), fmt("Sumcheck::RoundPoly: %s", e.what())};
+#else
+            return RustError{e.code()};
+#endif
+        }
+
+        return RustError{cudaSuccess};
+    }
+};
+
+#endif
+#endif
@@ -28,23 +28,32 @@ public:
     }
 };
 
-const gpu_t& select_gpu(int id)
+static const gpu_t* checked_gpu(int id, const char* func)
 {
     auto& gpus = gpus_t::all();
+    if (id < 0 || (size_t)id >= gpus.size())
+        throw cuda_error{-cudaErrorInvalidDevice,
+                         fmt("%s: invalid device id %d, %zu available",
+                             func, id, gpus.size())};
+    return gpus[id];
+}
+
+const gpu_t& select_gpu(int id)
+{
     if (id == -1) {
         int cuda_id;
         CUDA_OK(cudaGetDevice(&cuda_id));
-        for (auto* gpu: gpus)
+        for (auto* gpu: gpus_t::all())
            if (gpu->cid() == cuda_id) return *gpu;
         id = 0;
     }
-    auto* gpu = gpus[id];
+    auto* gpu = checked_gpu(id, __func__);
     gpu->select();
     return *gpu;
 }
 
 const cudaDeviceProp& gpu_props(int id)
-{   return gpus_t::all()[id]->props();   }
+{   return checked_gpu(id, __func__)->props();   }
 
 size_t ngpus()
 {   return gpus_t::all().size();   }
@@ -12,23 +12,41 @@
 # include <string.h>
 #endif
 
+/*
+ * sppark-specific error codes, kept clear of errno values [reported by
+ * sppark_error] and negated cudaError_t values [reported by CUDA_OK].
+ * Keep in sync with rust/src/lib.rs.
+ */
+enum {
+    SPPARK_ERR_LENGTH_MISMATCH = 0x10001,
+    SPPARK_ERR_NOT_POWER_OF_TWO,
+    SPPARK_ERR_TYPE_MISMATCH,
+    SPPARK_ERR_DOMAIN_TOO_LARGE,
+    SPPARK_ERR_BAD_LAYOUT,
+    SPPARK_ERR_DIVISION_BY_ZERO,
+    SPPARK_ERR_BAD_ROOT_OF_UNITY,
+    SPPARK_ERR_OUT_OF_MEMORY,
+};
+
 struct RustError { /* to be returned exclusively by value */
     int code;
+    unsigned int detail; /* code-specific payload, set by Rust, see lib.rs */
     char *message;
 #ifdef __cplusplus
-    RustError(int e = 0) : code(e)
+    RustError(int e = 0) : code(e), detail(0)
     {   message = nullptr;   }
-    RustError(int e, const std::string& str) : code(e)
+    RustError(int e, const std::string& str) : code(e), detail(0)
     {   message = str.empty() ? nullptr : strdup(str.c_str());   }
-    RustError(int e, const char *str) : code(e)
+    RustError(int e, const char *str) : code(e), detail(0)
     {   message = str==nullptr ? nullptr : strdup(str);   }
     // no destructor[!], Rust takes care of the |message|
 
     struct by_value {
         int code;
+        unsigned int detail;
         char *message;
     };
-    operator by_value() const { return {code, message}; }
+    operator by_value() const { return {code, detail, message}; }
 #endif
 };
 #ifndef __cplusplus